@@ -5,13 +5,13 @@ use solana_sdk::{
     pubkey::Pubkey,
     signature::{Keypair, Signer},
     transaction::Transaction,
-    system_instruction,
     system_program,
 };
 use std::str::FromStr;
 use borsh::{BorshSerialize, BorshDeserialize};
-use unity_vault::user::state::{UserRole, KycData};
+use unity_vault::user::state::{UserProfile, KycData};
 use unity_vault::user::instructions::UserProfileParams;
+use unity_vault::user::two_factor::commit_two_factor_secret;
 use unity_vault::{Instruction as ProgramInstruction, UserInstruction};
 
 #[tokio::main]
@@ -25,7 +25,8 @@ async fn main() {
 
     // Generate keypairs
     let payer = Keypair::new();
-    let user_profile = Keypair::new();
+    // Each key's profile lives at a PDA derived from it
+    let (user_profile, _) = UserProfile::find_address(&program_id, &payer.pubkey());
 
     // Request airdrop
     let airdrop_amount = 1_000_000_000; // 1 SOL
@@ -45,41 +46,26 @@ async fn main() {
     let create_profile_params = UserProfileParams {
         full_name: "Test User".to_string(),
         email: "test@example.com".to_string(),
-        role: UserRole::User,
     };
 
-    // Calculate minimum rent-exempt balance for the account
-    let account_size = 1126; // Size of UserProfile account (1,126 bytes)
-    let rent = client.get_minimum_balance_for_rent_exemption(account_size).unwrap();
-
-    // Create the account first
-    let create_account_ix = system_instruction::create_account(
-        &payer.pubkey(),
-        &user_profile.pubkey(),
-        rent,
-        account_size as u64,
-        &program_id,
-    );
-
     // Create the user profile instruction
     let create_profile_ix = Instruction::new_with_borsh(
         program_id,
         &ProgramInstruction::User(UserInstruction::CreateUserProfile(create_profile_params)),
         vec![
-            AccountMeta::new(user_profile.pubkey(), false),
+            AccountMeta::new(user_profile, false),
             AccountMeta::new(payer.pubkey(), true),
             AccountMeta::new_readonly(system_program::id(), false),
         ],
     );
 
-    // Add both instructions to the transaction
     let mut transaction = Transaction::new_with_payer(
-        &[create_account_ix, create_profile_ix],
+        &[create_profile_ix],
         Some(&payer.pubkey()),
     );
 
     // Sign and send the transaction
-    transaction.sign(&[&payer, &user_profile], client.get_latest_blockhash().unwrap());
+    transaction.sign(&[&payer], client.get_latest_blockhash().unwrap());
     
     match client.send_and_confirm_transaction(&transaction) {
         Ok(signature) => println!("User Profile Created! Signature: {}", signature),
@@ -90,14 +76,13 @@ async fn main() {
     let update_profile_params = UserProfileParams {
         full_name: "Updated User".to_string(),
         email: "updated@example.com".to_string(),
-        role: UserRole::Moderator,
     };
 
     let update_profile_ix = Instruction::new_with_borsh(
         program_id,
         &ProgramInstruction::User(UserInstruction::UpdateUserProfile(update_profile_params)),
         vec![
-            AccountMeta::new(user_profile.pubkey(), false),
+            AccountMeta::new(user_profile, false),
             AccountMeta::new(payer.pubkey(), true),
         ],
     );
@@ -118,11 +103,15 @@ async fn main() {
     let two_factor_ix = Instruction::new_with_borsh(
         program_id,
         &ProgramInstruction::User(UserInstruction::EnableTwoFactor {
-            secret: "test_secret".to_string(),
+            // Only a commitment goes on-chain; the secret stays with the
+            // user's authenticator and the guardian
+            secret_commitment: commit_two_factor_secret("JBSWY3DPEHPK3PXP"),
             backup_codes: vec!["code1".to_string(), "code2".to_string()],
+            guardian: Pubkey::default(),
+            high_value_threshold: 0,
         }),
         vec![
-            AccountMeta::new(user_profile.pubkey(), false),
+            AccountMeta::new(user_profile, false),
             AccountMeta::new(payer.pubkey(), true),
         ],
     );
@@ -150,7 +139,7 @@ async fn main() {
         program_id,
         &ProgramInstruction::User(UserInstruction::VerifyKyc(kyc_data)),
         vec![
-            AccountMeta::new(user_profile.pubkey(), false),
+            AccountMeta::new(user_profile, false),
             AccountMeta::new(payer.pubkey(), true),
         ],
     );
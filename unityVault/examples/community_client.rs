@@ -11,7 +11,7 @@ use solana_sdk::{
 };
 use std::str::FromStr;
 use borsh::{BorshSerialize, BorshDeserialize};
-use unity_vault::community::state::CommunityParams;
+use unity_vault::community::state::{CommunityParams, CommunityRole, Membership};
 use unity_vault::{Instruction as ProgramInstruction, CommunityInstruction};
 mod mock_data;
 use mock_data::MockData;
@@ -53,6 +53,9 @@ impl CommunityClient {
             rent,
         );
 
+        // The creator becomes the first member, as an admin
+        let (membership_pda, _) = Membership::find_address(&self.program_id, &community_pda, &payer.pubkey());
+
         // Create community instruction
         let create_community_ix = Instruction::new_with_borsh(
             self.program_id,
@@ -61,6 +64,7 @@ impl CommunityClient {
                 AccountMeta::new(community_pda, false),
                 AccountMeta::new(payer.pubkey(), true),
                 AccountMeta::new_readonly(system_program::id(), false),
+                AccountMeta::new(membership_pda, false),
             ],
         );
 
@@ -136,6 +140,224 @@ impl CommunityClient {
         Ok(signature)
     }
 
+    /// Joins a public community, accepts an invite, or asks to join a private one.
+    pub fn join_community(
+        &self,
+        member: &Keypair,
+        community_pda: Pubkey,
+    ) -> Result<Signature, Box<dyn std::error::Error>> {
+        let (membership_pda, _) = Membership::find_address(&self.program_id, &community_pda, &member.pubkey());
+
+        let join_ix = Instruction::new_with_borsh(
+            self.program_id,
+            &ProgramInstruction::Community(CommunityInstruction::JoinCommunity),
+            vec![
+                AccountMeta::new(community_pda, false),
+                AccountMeta::new(membership_pda, false),
+                AccountMeta::new(member.pubkey(), true),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        );
+
+        let mut transaction = Transaction::new_with_payer(
+            &[join_ix],
+            Some(&member.pubkey()),
+        );
+
+        transaction.sign(&[member], self.client.get_latest_blockhash()?);
+        let signature = self.client.send_and_confirm_transaction(&transaction)?;
+        Ok(signature)
+    }
+
+    /// Invites `member`, or admits them if they asked to join. The inviter
+    /// must be an admin or moderator.
+    pub fn invite_member(
+        &self,
+        inviter: &Keypair,
+        community_pda: Pubkey,
+        member: Pubkey,
+    ) -> Result<Signature, Box<dyn std::error::Error>> {
+        let (membership_pda, _) = Membership::find_address(&self.program_id, &community_pda, &member);
+        let (inviter_membership, _) = Membership::find_address(&self.program_id, &community_pda, &inviter.pubkey());
+
+        let invite_ix = Instruction::new_with_borsh(
+            self.program_id,
+            &ProgramInstruction::Community(CommunityInstruction::InviteMember(member)),
+            vec![
+                AccountMeta::new(community_pda, false),
+                AccountMeta::new(membership_pda, false),
+                AccountMeta::new(inviter.pubkey(), true),
+                AccountMeta::new_readonly(inviter_membership, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        );
+
+        let mut transaction = Transaction::new_with_payer(
+            &[invite_ix],
+            Some(&inviter.pubkey()),
+        );
+
+        transaction.sign(&[inviter], self.client.get_latest_blockhash()?);
+        let signature = self.client.send_and_confirm_transaction(&transaction)?;
+        Ok(signature)
+    }
+
+    pub fn leave_community(
+        &self,
+        member: &Keypair,
+        community_pda: Pubkey,
+    ) -> Result<Signature, Box<dyn std::error::Error>> {
+        let (membership_pda, _) = Membership::find_address(&self.program_id, &community_pda, &member.pubkey());
+
+        let leave_ix = Instruction::new_with_borsh(
+            self.program_id,
+            &ProgramInstruction::Community(CommunityInstruction::LeaveCommunity),
+            vec![
+                AccountMeta::new(community_pda, false),
+                AccountMeta::new(membership_pda, false),
+                AccountMeta::new(member.pubkey(), true),
+            ],
+        );
+
+        let mut transaction = Transaction::new_with_payer(
+            &[leave_ix],
+            Some(&member.pubkey()),
+        );
+
+        transaction.sign(&[member], self.client.get_latest_blockhash()?);
+        let signature = self.client.send_and_confirm_transaction(&transaction)?;
+        Ok(signature)
+    }
+
+    /// Changes a member's role. The admin must be an admin or the community authority.
+    pub fn set_member_role(
+        &self,
+        admin: &Keypair,
+        community_pda: Pubkey,
+        member: Pubkey,
+        role: CommunityRole,
+    ) -> Result<Signature, Box<dyn std::error::Error>> {
+        let (membership_pda, _) = Membership::find_address(&self.program_id, &community_pda, &member);
+        let (admin_membership, _) = Membership::find_address(&self.program_id, &community_pda, &admin.pubkey());
+
+        let set_role_ix = Instruction::new_with_borsh(
+            self.program_id,
+            &ProgramInstruction::Community(CommunityInstruction::SetMemberRole(role)),
+            vec![
+                AccountMeta::new(community_pda, false),
+                AccountMeta::new(membership_pda, false),
+                AccountMeta::new_readonly(admin.pubkey(), true),
+                AccountMeta::new_readonly(admin_membership, false),
+            ],
+        );
+
+        let mut transaction = Transaction::new_with_payer(
+            &[set_role_ix],
+            Some(&admin.pubkey()),
+        );
+
+        transaction.sign(&[admin], self.client.get_latest_blockhash()?);
+        let signature = self.client.send_and_confirm_transaction(&transaction)?;
+        Ok(signature)
+    }
+
+    pub fn kick_member(
+        &self,
+        moderator: &Keypair,
+        community_pda: Pubkey,
+        member: Pubkey,
+    ) -> Result<Signature, Box<dyn std::error::Error>> {
+        let (membership_pda, _) = Membership::find_address(&self.program_id, &community_pda, &member);
+        let (moderator_membership, _) = Membership::find_address(&self.program_id, &community_pda, &moderator.pubkey());
+
+        let kick_ix = Instruction::new_with_borsh(
+            self.program_id,
+            &ProgramInstruction::Community(CommunityInstruction::KickMember),
+            vec![
+                AccountMeta::new(community_pda, false),
+                AccountMeta::new(membership_pda, false),
+                AccountMeta::new_readonly(moderator.pubkey(), true),
+                AccountMeta::new_readonly(moderator_membership, false),
+                AccountMeta::new(member, false),
+            ],
+        );
+
+        let mut transaction = Transaction::new_with_payer(
+            &[kick_ix],
+            Some(&moderator.pubkey()),
+        );
+
+        transaction.sign(&[moderator], self.client.get_latest_blockhash()?);
+        let signature = self.client.send_and_confirm_transaction(&transaction)?;
+        Ok(signature)
+    }
+
+    pub fn ban_member(
+        &self,
+        moderator: &Keypair,
+        community_pda: Pubkey,
+        member: Pubkey,
+    ) -> Result<Signature, Box<dyn std::error::Error>> {
+        let (membership_pda, _) = Membership::find_address(&self.program_id, &community_pda, &member);
+        let (moderator_membership, _) = Membership::find_address(&self.program_id, &community_pda, &moderator.pubkey());
+
+        let ban_ix = Instruction::new_with_borsh(
+            self.program_id,
+            &ProgramInstruction::Community(CommunityInstruction::BanMember(member)),
+            vec![
+                AccountMeta::new(community_pda, false),
+                AccountMeta::new(membership_pda, false),
+                AccountMeta::new(moderator.pubkey(), true),
+                AccountMeta::new_readonly(moderator_membership, false),
+                AccountMeta::new_readonly(system_program::id(), false),
+            ],
+        );
+
+        let mut transaction = Transaction::new_with_payer(
+            &[ban_ix],
+            Some(&moderator.pubkey()),
+        );
+
+        transaction.sign(&[moderator], self.client.get_latest_blockhash()?);
+        let signature = self.client.send_and_confirm_transaction(&transaction)?;
+        Ok(signature)
+    }
+
+    /// Hands the community to `new_authority` in two steps: the current
+    /// authority offers it, then the new authority accepts.
+    pub fn transfer_authority(
+        &self,
+        authority: &Keypair,
+        new_authority: &Keypair,
+        community_pda: Pubkey,
+    ) -> Result<Signature, Box<dyn std::error::Error>> {
+        let offer_ix = Instruction::new_with_borsh(
+            self.program_id,
+            &ProgramInstruction::Community(CommunityInstruction::TransferCommunityAuthority(Some(new_authority.pubkey()))),
+            vec![
+                AccountMeta::new(community_pda, false),
+                AccountMeta::new_readonly(authority.pubkey(), true),
+            ],
+        );
+        let accept_ix = Instruction::new_with_borsh(
+            self.program_id,
+            &ProgramInstruction::Community(CommunityInstruction::AcceptCommunityAuthority),
+            vec![
+                AccountMeta::new(community_pda, false),
+                AccountMeta::new_readonly(new_authority.pubkey(), true),
+            ],
+        );
+
+        let mut transaction = Transaction::new_with_payer(
+            &[offer_ix, accept_ix],
+            Some(&authority.pubkey()),
+        );
+
+        transaction.sign(&[authority, new_authority], self.client.get_latest_blockhash()?);
+        let signature = self.client.send_and_confirm_transaction(&transaction)?;
+        Ok(signature)
+    }
+
     pub fn get_community(&self, community_pda: Pubkey) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
         let account_data = self.client.get_account_data(&community_pda)?;
         Ok(account_data)
@@ -11,7 +11,12 @@ use solana_sdk::{
 use std::str::FromStr;
 use borsh::{BorshSerialize, BorshDeserialize};
 use unity_vault::{Instruction as ProgramInstruction, GovernanceInstruction};
-use unity_vault::governance::state::{ProposalParams, VoteType};
+use unity_vault::community::state::Membership;
+use unity_vault::governance::state::{
+    GovernanceConfig, Proposal, ProposalDepositConfig, ProposalParams, Realm, RealmScope, TokenOwnerRecord,
+    VetoCouncil, VoteMode, VoteRecord, VoteType,
+};
+use unity_vault::governance::treasury::find_treasury_token_account;
 mod mock_data;
 use mock_data::MockData;
 
@@ -26,14 +31,63 @@ impl GovernanceClient {
         Self { program_id, client }
     }
 
+    /// Community realms need the member's membership account on proposals and votes.
+    fn membership_meta(&self, scope: &RealmScope, member: &Pubkey) -> Option<AccountMeta> {
+        match scope {
+            RealmScope::Platform => None,
+            RealmScope::Community(community) => {
+                let (membership, _) = Membership::find_address(&self.program_id, community, member);
+                Some(AccountMeta::new_readonly(membership, false))
+            }
+        }
+    }
+
+    /// Creates the realm governing `scope` with `governing_mint`. `authority`
+    /// is the community authority, or the mint authority for the platform realm.
+    pub fn create_realm(
+        &self,
+        authority: &Keypair,
+        scope: RealmScope,
+        governing_mint: Pubkey,
+        config: GovernanceConfig,
+        quorum_floor: u64,
+    ) -> Result<(Pubkey, Signature), Box<dyn std::error::Error>> {
+        let (realm_pda, _) = Realm::find_address(&self.program_id, &scope, &governing_mint);
+
+        let mut accounts = vec![
+            AccountMeta::new(realm_pda, false),
+            AccountMeta::new(authority.pubkey(), true),
+            AccountMeta::new_readonly(governing_mint, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ];
+        if let RealmScope::Community(community) = scope {
+            accounts.push(AccountMeta::new_readonly(community, false));
+        }
+
+        let create_realm_ix = Instruction::new_with_borsh(
+            self.program_id,
+            &ProgramInstruction::Governance(GovernanceInstruction::CreateRealm { scope, config, quorum_floor }),
+            accounts,
+        );
+
+        let mut transaction = Transaction::new_with_payer(
+            &[create_realm_ix],
+            Some(&authority.pubkey()),
+        );
+
+        transaction.sign(&[authority], self.client.get_latest_blockhash()?);
+        let signature = self.client.send_and_confirm_transaction(&transaction)?;
+        Ok((realm_pda, signature))
+    }
+
+    /// Creates a proposal in the realm for `scope`, which sets its voting rules.
     pub fn create_proposal(
         &self,
         payer: &Keypair,
+        scope: RealmScope,
+        governing_mint: Pubkey,
         title: String,
         description: String,
-        voting_duration: i64,
-        min_votes: u32,
-        min_approval_percentage: u8,
     ) -> Result<(Pubkey, Signature), Box<dyn std::error::Error>> {
         // Derive proposal PDA
         let (proposal_pda, _) = Pubkey::find_program_address(
@@ -46,7 +100,7 @@ impl GovernanceClient {
         );
 
         // Calculate minimum rent-exempt balance
-        let account_size = 1024; // Size of Proposal account
+        let account_size = Proposal::LEN;
         let rent = self.client.get_minimum_balance_for_rent_exemption(account_size)?;
 
         // Fund proposal account
@@ -60,19 +114,29 @@ impl GovernanceClient {
         let params = ProposalParams {
             title,
             description,
-            voting_duration,
-            min_votes,
-            min_approval_percentage,
+            vote_mode: VoteMode::YesNo,
+            options: Vec::new(),
         };
 
+        // The proposal deposit, if governance has configured one, comes out of the payer's deposited tokens
+        let (realm, _) = Realm::find_address(&self.program_id, &scope, &governing_mint);
+        let (deposit_config, _) = ProposalDepositConfig::find_address(&self.program_id, &realm);
+        let (token_owner_record, _) = TokenOwnerRecord::find_address(&self.program_id, &governing_mint, &payer.pubkey());
+
+        let mut accounts = vec![
+            AccountMeta::new(proposal_pda, false),
+            AccountMeta::new(payer.pubkey(), true),
+            AccountMeta::new_readonly(system_program::id(), false),
+            AccountMeta::new_readonly(realm, false),
+            AccountMeta::new_readonly(deposit_config, false),
+            AccountMeta::new(token_owner_record, false),
+        ];
+        accounts.extend(self.membership_meta(&scope, &payer.pubkey()));
+
         let create_proposal_ix = Instruction::new_with_borsh(
             self.program_id,
             &ProgramInstruction::Governance(GovernanceInstruction::CreateProposal(params)),
-            vec![
-                AccountMeta::new(proposal_pda, false),
-                AccountMeta::new(payer.pubkey(), true),
-                AccountMeta::new_readonly(system_program::id(), false),
-            ],
+            accounts,
         );
 
         // Get recent blockhash
@@ -98,33 +162,144 @@ impl GovernanceClient {
         Ok((proposal_pda, signature))
     }
 
-    pub fn vote(
+    pub fn activate_proposal(
         &self,
-        voter: &Keypair,
+        authority: &Keypair,
         proposal_pda: Pubkey,
-        vote_type: VoteType,
     ) -> Result<Signature, Box<dyn std::error::Error>> {
-        // Derive vote PDA
-        let (vote_pda, _) = Pubkey::find_program_address(
-            &[
-                b"vote",
-                proposal_pda.as_ref(),
-                voter.pubkey().as_ref(),
+        let activate_ix = Instruction::new_with_borsh(
+            self.program_id,
+            &ProgramInstruction::Governance(GovernanceInstruction::ActivateProposal),
+            vec![
+                AccountMeta::new(proposal_pda, false),
+                AccountMeta::new_readonly(authority.pubkey(), true),
             ],
-            &self.program_id,
         );
 
-        let vote_ix = Instruction::new_with_borsh(
+        let mut transaction = Transaction::new_with_payer(
+            &[activate_ix],
+            Some(&authority.pubkey()),
+        );
+
+        transaction.sign(&[authority], self.client.get_latest_blockhash()?);
+        let signature = self.client.send_and_confirm_transaction(&transaction)?;
+        Ok(signature)
+    }
+
+    pub fn finalize_proposal(
+        &self,
+        payer: &Keypair,
+        proposal_pda: Pubkey,
+    ) -> Result<Signature, Box<dyn std::error::Error>> {
+        let finalize_ix = Instruction::new_with_borsh(
             self.program_id,
-            &ProgramInstruction::Governance(GovernanceInstruction::VoteProposal(vote_type)),
+            &ProgramInstruction::Governance(GovernanceInstruction::FinalizeProposal),
+            vec![AccountMeta::new(proposal_pda, false)],
+        );
+
+        let mut transaction = Transaction::new_with_payer(
+            &[finalize_ix],
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[payer], self.client.get_latest_blockhash()?);
+        let signature = self.client.send_and_confirm_transaction(&transaction)?;
+        Ok(signature)
+    }
+
+    pub fn deposit_governing_tokens(
+        &self,
+        owner: &Keypair,
+        governing_mint: Pubkey,
+        source_token_account: Pubkey,
+        amount: u64,
+    ) -> Result<Signature, Box<dyn std::error::Error>> {
+        self.send_governing_tokens(
+            owner,
+            governing_mint,
+            source_token_account,
+            GovernanceInstruction::DepositGoverningTokens(amount),
+        )
+    }
+
+    /// Locks tokens in the vote escrow; a longer lock votes with more weight.
+    pub fn lock_governing_tokens(
+        &self,
+        owner: &Keypair,
+        governing_mint: Pubkey,
+        source_token_account: Pubkey,
+        amount: u64,
+        duration: i64,
+    ) -> Result<Signature, Box<dyn std::error::Error>> {
+        self.send_governing_tokens(
+            owner,
+            governing_mint,
+            source_token_account,
+            GovernanceInstruction::Lock { amount, duration },
+        )
+    }
+
+    fn send_governing_tokens(
+        &self,
+        owner: &Keypair,
+        governing_mint: Pubkey,
+        source_token_account: Pubkey,
+        instruction: GovernanceInstruction,
+    ) -> Result<Signature, Box<dyn std::error::Error>> {
+        let (token_owner_record, _) = TokenOwnerRecord::find_address(&self.program_id, &governing_mint, &owner.pubkey());
+        let (governing_vault, _) = TokenOwnerRecord::find_governing_vault(&self.program_id, &governing_mint);
+
+        let deposit_ix = Instruction::new_with_borsh(
+            self.program_id,
+            &ProgramInstruction::Governance(instruction),
             vec![
-                AccountMeta::new(proposal_pda, false),
-                AccountMeta::new(vote_pda, false),
-                AccountMeta::new(voter.pubkey(), true),
+                AccountMeta::new(token_owner_record, false),
+                AccountMeta::new(governing_vault, false),
+                AccountMeta::new_readonly(governing_mint, false),
+                AccountMeta::new(source_token_account, false),
+                AccountMeta::new(owner.pubkey(), true),
+                AccountMeta::new_readonly(spl_token::id(), false),
                 AccountMeta::new_readonly(system_program::id(), false),
             ],
         );
 
+        let mut transaction = Transaction::new_with_payer(
+            &[deposit_ix],
+            Some(&owner.pubkey()),
+        );
+
+        transaction.sign(&[owner], self.client.get_latest_blockhash()?);
+        let signature = self.client.send_and_confirm_transaction(&transaction)?;
+        Ok(signature)
+    }
+
+    pub fn vote(
+        &self,
+        voter: &Keypair,
+        proposal_pda: Pubkey,
+        scope: RealmScope,
+        governing_mint: Pubkey,
+        vote_type: VoteType,
+    ) -> Result<Signature, Box<dyn std::error::Error>> {
+        // Derive vote record PDA
+        let (vote_pda, _) = VoteRecord::find_address(&self.program_id, &proposal_pda, &voter.pubkey());
+        let (token_owner_record, _) = TokenOwnerRecord::find_address(&self.program_id, &governing_mint, &voter.pubkey());
+
+        let mut accounts = vec![
+            AccountMeta::new(proposal_pda, false),
+            AccountMeta::new(vote_pda, false),
+            AccountMeta::new(voter.pubkey(), true),
+            AccountMeta::new(token_owner_record, false),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ];
+        accounts.extend(self.membership_meta(&scope, &voter.pubkey()));
+
+        let vote_ix = Instruction::new_with_borsh(
+            self.program_id,
+            &ProgramInstruction::Governance(GovernanceInstruction::VoteProposal(vote_type)),
+            accounts,
+        );
+
         let mut transaction = Transaction::new_with_payer(
             &[vote_ix],
             Some(&voter.pubkey()),
@@ -135,6 +310,143 @@ impl GovernanceClient {
         Ok(signature)
     }
 
+    pub fn change_vote(
+        &self,
+        voter: &Keypair,
+        proposal_pda: Pubkey,
+        scope: RealmScope,
+        governing_mint: Pubkey,
+        vote_type: VoteType,
+    ) -> Result<Signature, Box<dyn std::error::Error>> {
+        let (vote_pda, _) = VoteRecord::find_address(&self.program_id, &proposal_pda, &voter.pubkey());
+        let (token_owner_record, _) = TokenOwnerRecord::find_address(&self.program_id, &governing_mint, &voter.pubkey());
+
+        let mut accounts = vec![
+            AccountMeta::new(proposal_pda, false),
+            AccountMeta::new(vote_pda, false),
+            AccountMeta::new_readonly(voter.pubkey(), true),
+            AccountMeta::new_readonly(token_owner_record, false),
+        ];
+        accounts.extend(self.membership_meta(&scope, &voter.pubkey()));
+
+        let change_vote_ix = Instruction::new_with_borsh(
+            self.program_id,
+            &ProgramInstruction::Governance(GovernanceInstruction::ChangeVote(vote_type)),
+            accounts,
+        );
+
+        let mut transaction = Transaction::new_with_payer(
+            &[change_vote_ix],
+            Some(&voter.pubkey()),
+        );
+
+        transaction.sign(&[voter], self.client.get_latest_blockhash()?);
+        let signature = self.client.send_and_confirm_transaction(&transaction)?;
+        Ok(signature)
+    }
+
+    /// Lets `delegate` vote the owner's deposit; `None` removes the delegate.
+    pub fn set_delegate(
+        &self,
+        owner: &Keypair,
+        governing_mint: Pubkey,
+        delegate: Option<Pubkey>,
+    ) -> Result<Signature, Box<dyn std::error::Error>> {
+        let (token_owner_record, _) = TokenOwnerRecord::find_address(&self.program_id, &governing_mint, &owner.pubkey());
+        let instruction = match delegate {
+            Some(delegate) => GovernanceInstruction::Delegate(delegate),
+            None => GovernanceInstruction::Undelegate,
+        };
+
+        let delegate_ix = Instruction::new_with_borsh(
+            self.program_id,
+            &ProgramInstruction::Governance(instruction),
+            vec![
+                AccountMeta::new(token_owner_record, false),
+                AccountMeta::new_readonly(owner.pubkey(), true),
+            ],
+        );
+
+        let mut transaction = Transaction::new_with_payer(
+            &[delegate_ix],
+            Some(&owner.pubkey()),
+        );
+
+        transaction.sign(&[owner], self.client.get_latest_blockhash()?);
+        let signature = self.client.send_and_confirm_transaction(&transaction)?;
+        Ok(signature)
+    }
+
+    /// Returns a finished proposal's deposit to its author, or sends it to the treasury.
+    pub fn release_proposal_deposit(
+        &self,
+        payer: &Keypair,
+        proposal_pda: Pubkey,
+        proposal_authority: Pubkey,
+        realm: Pubkey,
+        governing_mint: Pubkey,
+    ) -> Result<Signature, Box<dyn std::error::Error>> {
+        let (token_owner_record, _) = TokenOwnerRecord::find_address(&self.program_id, &governing_mint, &proposal_authority);
+        let (governing_vault, _) = TokenOwnerRecord::find_governing_vault(&self.program_id, &governing_mint);
+        let treasury_token_account = find_treasury_token_account(&self.program_id, &realm, &governing_mint);
+
+        let release_ix = Instruction::new_with_borsh(
+            self.program_id,
+            &ProgramInstruction::Governance(GovernanceInstruction::ReleaseProposalDeposit),
+            vec![
+                AccountMeta::new(proposal_pda, false),
+                AccountMeta::new(token_owner_record, false),
+                AccountMeta::new(governing_vault, false),
+                AccountMeta::new(treasury_token_account, false),
+                AccountMeta::new_readonly(spl_token::id(), false),
+            ],
+        );
+
+        let mut transaction = Transaction::new_with_payer(
+            &[release_ix],
+            Some(&payer.pubkey()),
+        );
+
+        transaction.sign(&[payer], self.client.get_latest_blockhash()?);
+        let signature = self.client.send_and_confirm_transaction(&transaction)?;
+        Ok(signature)
+    }
+
+    /// Vetoes a passed proposal during its timelock. At least the council's
+    /// threshold of members must sign.
+    pub fn veto_proposal(
+        &self,
+        payer: &Keypair,
+        proposal_pda: Pubkey,
+        realm: Pubkey,
+        council_members: &[&Keypair],
+    ) -> Result<Signature, Box<dyn std::error::Error>> {
+        let (veto_council, _) = VetoCouncil::find_address(&self.program_id, &realm);
+
+        let mut accounts = vec![
+            AccountMeta::new(proposal_pda, false),
+            AccountMeta::new_readonly(veto_council, false),
+        ];
+        accounts.extend(council_members.iter().map(|member| AccountMeta::new_readonly(member.pubkey(), true)));
+
+        let veto_ix = Instruction::new_with_borsh(
+            self.program_id,
+            &ProgramInstruction::Governance(GovernanceInstruction::VetoProposal),
+            accounts,
+        );
+
+        let mut transaction = Transaction::new_with_payer(
+            &[veto_ix],
+            Some(&payer.pubkey()),
+        );
+
+        let mut signers = vec![payer];
+        signers.extend_from_slice(council_members);
+        transaction.sign(&signers, self.client.get_latest_blockhash()?);
+        let signature = self.client.send_and_confirm_transaction(&transaction)?;
+        Ok(signature)
+    }
+
     pub fn get_proposal(&self, proposal_pda: Pubkey) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
         let account_data = self.client.get_account_data(&proposal_pda)?;
         Ok(account_data)
@@ -166,16 +478,27 @@ async fn main() {
     let mock_data = MockData::new();
     let client = GovernanceClient::new(mock_data.program_id, mock_data.rpc_url);
 
-    // Example: Create a proposal using mock data
-    let (title, description, voting_duration, min_votes, min_approval_percentage) = MockData::mock_proposal_params();
+    // Example: Create a community realm and a proposal in it using mock data
+    let (voting_duration, quorum, min_approval_percentage) = MockData::mock_governance_config();
+    let scope = RealmScope::Community(mock_data.community);
+    let config = GovernanceConfig {
+        quorum,
+        min_approval_percentage,
+        voting_duration,
+        execution_delay: 0,
+    };
+    if let Err(err) = client.create_realm(&mock_data.authority, scope, mock_data.token_mint.pubkey(), config, quorum) {
+        eprintln!("Error creating realm: {}", err);
+    }
+
+    let (title, description) = MockData::mock_proposal_params();
     
     match client.create_proposal(
-        &Keypair::new(),  // Mock payer keypair
+        &mock_data.authority,
+        scope,
+        mock_data.token_mint.pubkey(),
         title,
         description,
-        voting_duration,
-        min_votes,
-        min_approval_percentage,
     ) {
         Ok((proposal_pda, signature)) => {
             println!("Proposal created! PDA: {}, Signature: {}", proposal_pda, signature);
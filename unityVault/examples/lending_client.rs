@@ -12,6 +12,7 @@ use std::str::FromStr;
 use borsh::{BorshSerialize, BorshDeserialize};
 use unity_vault::{Instruction as ProgramInstruction, LendingInstruction};
 use unity_vault::lending::state::{LoanParams, LendingPoolParams};
+use unity_vault::user::state::UserProfile;
 mod mock_data;
 use mock_data::MockData;
 
@@ -114,6 +115,7 @@ impl LendingClient {
             ],
             &self.program_id,
         );
+        let (borrower_profile, _) = UserProfile::find_address(&self.program_id, &borrower.pubkey());
 
         // Calculate minimum rent-exempt balance
         let account_size = 1024; // Size of Loan account
@@ -142,6 +144,9 @@ impl LendingClient {
                 AccountMeta::new(lending_pool, false),
                 AccountMeta::new(borrower.pubkey(), true),
                 AccountMeta::new_readonly(system_program::id(), false),
+                // Checked for the 2FA guardian co-signature; loans above the
+                // profile's threshold also need the guardian as a signer
+                AccountMeta::new_readonly(borrower_profile, false),
             ],
         );
 
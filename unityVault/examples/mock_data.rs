@@ -102,12 +102,18 @@ impl MockData {
     }
 
     // Mock proposal parameters
-    pub fn mock_proposal_params() -> (String, String, i64, u32, u8) {
+    pub fn mock_proposal_params() -> (String, String) {
         (
             "Test Proposal".to_string(),
             "A test proposal".to_string(),
+        )
+    }
+
+    // Mock realm governance config
+    pub fn mock_governance_config() -> (i64, u64, u8) {
+        (
             86400, // 1 day voting duration
-            10,    // Minimum 10 votes
+            10,    // Minimum total vote weight
             60,    // 60% approval required
         )
     }
@@ -128,6 +134,7 @@ impl MockData {
         let _lending_pool = Self::mock_lending_pool_params();
         let _loan = Self::mock_loan_params();
         let _proposal = Self::mock_proposal_params();
+        let _governance_config = Self::mock_governance_config();
         let _community = Self::mock_community_params();
     }
 }
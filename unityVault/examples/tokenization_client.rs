@@ -16,6 +16,7 @@ use spl_token::{
 };
 use unity_vault::{Instruction as ProgramInstruction, TokenizationInstruction};
 use unity_vault::tokenization::state::TokenParams;
+use unity_vault::user::state::UserProfile;
 mod mock_data;
 use mock_data::MockData;
 
@@ -143,6 +144,7 @@ impl TokenizationClient {
         to_token_account: Pubkey,
         amount: u64,
     ) -> Result<Signature, Box<dyn std::error::Error>> {
+        let (from_profile, _) = UserProfile::find_address(&self.program_id, &from.pubkey());
         let transfer_ix = Instruction::new_with_borsh(
             self.program_id,
             &ProgramInstruction::Tokenization(TokenizationInstruction::TransferTokens(amount)),
@@ -151,6 +153,8 @@ impl TokenizationClient {
                 AccountMeta::new(to_token_account, false),
                 AccountMeta::new(from.pubkey(), true),
                 AccountMeta::new(to, false),
+                // Checked for the 2FA guardian co-signature
+                AccountMeta::new_readonly(from_profile, false),
             ],
         );
 
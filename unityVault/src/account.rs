@@ -0,0 +1,251 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke_signed,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use borsh::{BorshSerialize, BorshDeserialize};
+use crate::error::UnityVaultError;
+
+/// Bytes in front of every account's Borsh payload: account type and layout version.
+pub const ACCOUNT_HEADER_LEN: usize = 2;
+
+/// First byte of every initialized account written before layouts were
+/// versioned (their `is_initialized` flag). No `AccountType` uses it.
+pub const LEGACY_INITIALIZED_FLAG: u8 = 1;
+
+/// Leading byte of every program-owned account. Zero means the account
+/// has been allocated but never written.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountType {
+    Uninitialized = 0,
+    UserProfile = 2,
+    Proposal = 3,
+    Community = 4,
+    LendingPool = 5,
+    Loan = 6,
+    TokenInfo = 7,
+    VoteRecord = 8,
+    TokenOwnerRecord = 9,
+    ProposalTransaction = 10,
+    ProposalDepositConfig = 11,
+    Realm = 12,
+    Membership = 13,
+    VetoCouncil = 14,
+    CommunityProject = 15,
+    ProjectContribution = 16,
+}
+
+/// Serialization for program accounts: `[account type, version, borsh payload]`.
+///
+/// `LEN` is the full allocation size including the header, computed from the
+/// field budgets. `validate_bounds` is checked on both pack and unpack so an
+/// account can never hold more than its layout budgets for.
+pub trait AccountState: BorshSerialize + BorshDeserialize {
+    const ACCOUNT_TYPE: AccountType;
+    const VERSION: u8;
+    const LEN: usize;
+
+    fn validate_bounds(&self) -> Result<(), UnityVaultError> {
+        Ok(())
+    }
+
+    /// Whether `data` is already in use, by an account of any type or layout version.
+    fn is_initialized_account(data: &[u8]) -> bool {
+        !matches!(data.first(), None | Some(0))
+    }
+
+    fn unpack(src: &[u8]) -> Result<Self, ProgramError> {
+        if src.len() < ACCOUNT_HEADER_LEN {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        if src[0] == AccountType::Uninitialized as u8 {
+            return Err(ProgramError::UninitializedAccount);
+        }
+        if src[0] == LEGACY_INITIALIZED_FLAG {
+            msg!("{:?} uses the legacy layout and must be migrated", Self::ACCOUNT_TYPE);
+            return Err(UnityVaultError::UnsupportedAccountVersion.into());
+        }
+        if src[0] != Self::ACCOUNT_TYPE as u8 {
+            return Err(UnityVaultError::AccountTypeMismatch.into());
+        }
+        if src[1] != Self::VERSION {
+            msg!("Unsupported {:?} layout version {}", Self::ACCOUNT_TYPE, src[1]);
+            return Err(UnityVaultError::UnsupportedAccountVersion.into());
+        }
+
+        // Accounts are allocated at LEN, so the payload is usually followed by zero padding
+        let mut payload = &src[ACCOUNT_HEADER_LEN..];
+        let account = Self::deserialize(&mut payload).map_err(|_| ProgramError::InvalidAccountData)?;
+        account.validate_bounds()?;
+        Ok(account)
+    }
+
+    fn pack(&self, dst: &mut [u8]) -> ProgramResult {
+        self.validate_bounds()?;
+
+        let payload = borsh::to_vec(self).map_err(|_| ProgramError::InvalidAccountData)?;
+        let end = ACCOUNT_HEADER_LEN + payload.len();
+        if end > dst.len() {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+
+        dst[0] = Self::ACCOUNT_TYPE as u8;
+        dst[1] = Self::VERSION;
+        dst[ACCOUNT_HEADER_LEN..end].copy_from_slice(&payload);
+        // Clear anything left over from a longer previous payload
+        dst[end..].fill(0);
+        Ok(())
+    }
+}
+
+/// Creates a rent-exempt account of `space` bytes owned by `owner` at the PDA
+/// derived from `seeds` (which must include the bump), funded by `payer`.
+pub fn create_pda_account<'a>(
+    owner: &Pubkey,
+    payer: &AccountInfo<'a>,
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    space: usize,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    let lamports = Rent::get()?.minimum_balance(space);
+    invoke_signed(
+        &system_instruction::create_account(payer.key, account.key, lamports, space as u64, owner),
+        &[payer.clone(), account.clone(), system_program.clone()],
+        &[seeds],
+    )
+}
+
+/// Closes a program-owned account, moving its lamports to `destination`.
+/// The zeroed data reads as `AccountType::Uninitialized` until the runtime
+/// removes the account at the end of the transaction.
+pub fn close_account(account: &AccountInfo, destination: &AccountInfo) -> ProgramResult {
+    let lamports = account.lamports();
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.try_borrow_mut_data()?.fill(0);
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::validation::check_str_len;
+
+    /// Packs `account`, filled to its field budgets by the caller, into a
+    /// buffer of exactly `T::LEN` bytes and checks it unpacks unchanged.
+    pub(crate) fn assert_max_len_fits<T: AccountState>(account: &T) {
+        let mut data = vec![0; T::LEN];
+        account.pack(&mut data).expect("account at its field budgets must fit in LEN");
+        let unpacked = T::unpack(&data).unwrap();
+        assert_eq!(borsh::to_vec(&unpacked).unwrap(), borsh::to_vec(account).unwrap());
+    }
+
+    #[derive(Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+    struct TestAccount {
+        value: u64,
+        name: String,
+    }
+
+    impl AccountState for TestAccount {
+        const ACCOUNT_TYPE: AccountType = AccountType::Community;
+        const VERSION: u8 = 3;
+        const LEN: usize = ACCOUNT_HEADER_LEN + 8 + 4 + 8;
+
+        fn validate_bounds(&self) -> Result<(), UnityVaultError> {
+            check_str_len("name", &self.name, 8)
+        }
+    }
+
+    fn test_account() -> TestAccount {
+        TestAccount {
+            value: 42,
+            name: "unity".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_round_trip_writes_header() {
+        let mut data = vec![0; TestAccount::LEN];
+        assert!(!TestAccount::is_initialized_account(&data));
+
+        test_account().pack(&mut data).unwrap();
+        assert_eq!(data[0], AccountType::Community as u8);
+        assert_eq!(data[1], 3);
+        assert!(TestAccount::is_initialized_account(&data));
+        assert_eq!(TestAccount::unpack(&data).unwrap(), test_account());
+    }
+
+    #[test]
+    fn test_repack_shorter_payload_clears_tail() {
+        let mut data = vec![0; TestAccount::LEN];
+        TestAccount { value: 1, name: "12345678".to_string() }.pack(&mut data).unwrap();
+        TestAccount { value: 1, name: "1".to_string() }.pack(&mut data).unwrap();
+        assert!(data[ACCOUNT_HEADER_LEN + 8 + 4 + 1..].iter().all(|b| *b == 0));
+    }
+
+    #[test]
+    fn test_unpack_errors_instead_of_panicking() {
+        let mut data = vec![0; TestAccount::LEN];
+        assert_eq!(TestAccount::unpack(&data), Err(ProgramError::UninitializedAccount));
+        assert_eq!(TestAccount::unpack(&[]), Err(ProgramError::AccountDataTooSmall));
+
+        test_account().pack(&mut data).unwrap();
+        // Truncated payload
+        assert_eq!(TestAccount::unpack(&data[..ACCOUNT_HEADER_LEN + 10]), Err(ProgramError::InvalidAccountData));
+
+        // String length prefix pointing past the end of the buffer
+        let mut corrupt = data.clone();
+        corrupt[ACCOUNT_HEADER_LEN + 8..ACCOUNT_HEADER_LEN + 12].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(TestAccount::unpack(&corrupt), Err(ProgramError::InvalidAccountData));
+
+        let mut wrong_type = data.clone();
+        wrong_type[0] = AccountType::Loan as u8;
+        assert_eq!(TestAccount::unpack(&wrong_type), Err(UnityVaultError::AccountTypeMismatch.into()));
+
+        let mut legacy = data.clone();
+        legacy[0] = LEGACY_INITIALIZED_FLAG;
+        assert!(TestAccount::is_initialized_account(&legacy));
+        assert_eq!(TestAccount::unpack(&legacy), Err(UnityVaultError::UnsupportedAccountVersion.into()));
+
+        let mut wrong_version = data;
+        wrong_version[1] = 2;
+        assert_eq!(TestAccount::unpack(&wrong_version), Err(UnityVaultError::UnsupportedAccountVersion.into()));
+    }
+
+    #[test]
+    fn test_bounds_enforced_on_pack_and_unpack() {
+        let long = TestAccount { value: 0, name: "123456789".to_string() };
+
+        let mut data = vec![0; 64];
+        assert_eq!(long.pack(&mut data), Err(UnityVaultError::StringTooLong.into()));
+
+        // Hand-written oversized account
+        data[0] = AccountType::Community as u8;
+        data[1] = 3;
+        let payload = borsh::to_vec(&long).unwrap();
+        data[ACCOUNT_HEADER_LEN..ACCOUNT_HEADER_LEN + payload.len()].copy_from_slice(&payload);
+        assert_eq!(TestAccount::unpack(&data), Err(UnityVaultError::StringTooLong.into()));
+    }
+
+    #[test]
+    fn test_assert_max_len_fits() {
+        assert_max_len_fits(&TestAccount { value: u64::MAX, name: "12345678".to_string() });
+    }
+
+    #[test]
+    fn test_pack_into_short_buffer_errors() {
+        let mut data = vec![0; ACCOUNT_HEADER_LEN + 4];
+        assert_eq!(test_account().pack(&mut data), Err(ProgramError::AccountDataTooSmall));
+    }
+}
@@ -1,90 +1,580 @@
 use solana_program::{
-    account_info::AccountInfo,
+    account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
     program_error::ProgramError,
-    program_pack::Pack,
     pubkey::Pubkey,
 };
-use crate::community::state::Community;
+use crate::account::AccountState;
+use crate::community::state::{Community, CommunityProject};
+use crate::community::treasury::find_community_treasury;
+use crate::governance::context::check_governance_authority;
+use crate::context::{
+    check_program_owned, check_signer, check_system_program, check_token_program, check_uninitialized, Accounts,
+};
 
 pub struct CreateCommunityContext<'a> {
     pub community: &'a AccountInfo<'a>,
     pub authority: &'a AccountInfo<'a>,
     pub system_program: &'a AccountInfo<'a>,
+    pub membership: &'a AccountInfo<'a>,
 }
 
+/// A community and its signing authority, for updates and authority handoffs.
 pub struct UpdateCommunityContext<'a> {
     pub community: &'a AccountInfo<'a>,
     pub authority: &'a AccountInfo<'a>,
 }
 
-pub struct SuspendCommunityContext<'a> {
+/// A community being suspended, reinstated or archived, by its authority or
+/// by a platform moderator passing their user profile.
+pub struct ModerateCommunityContext<'a> {
     pub community: &'a AccountInfo<'a>,
-    pub authority: &'a AccountInfo<'a>,
+    pub moderator: &'a AccountInfo<'a>,
+    pub user_profile: Option<&'a AccountInfo<'a>>,
+}
+
+pub struct JoinCommunityContext<'a> {
+    pub community: &'a AccountInfo<'a>,
+    pub membership: &'a AccountInfo<'a>,
+    pub member: &'a AccountInfo<'a>,
+    pub system_program: &'a AccountInfo<'a>,
+}
+
+pub struct InviteMemberContext<'a> {
+    pub community: &'a AccountInfo<'a>,
+    pub membership: &'a AccountInfo<'a>,
+    pub inviter: &'a AccountInfo<'a>,
+    pub inviter_membership: &'a AccountInfo<'a>,
+    pub system_program: &'a AccountInfo<'a>,
+}
+
+pub struct LeaveCommunityContext<'a> {
+    pub community: &'a AccountInfo<'a>,
+    pub membership: &'a AccountInfo<'a>,
+    pub member: &'a AccountInfo<'a>,
+}
+
+pub struct SetMemberRoleContext<'a> {
+    pub community: &'a AccountInfo<'a>,
+    pub membership: &'a AccountInfo<'a>,
+    pub admin: &'a AccountInfo<'a>,
+    pub admin_membership: &'a AccountInfo<'a>,
+}
+
+pub struct KickMemberContext<'a> {
+    pub community: &'a AccountInfo<'a>,
+    pub membership: &'a AccountInfo<'a>,
+    pub moderator: &'a AccountInfo<'a>,
+    pub moderator_membership: &'a AccountInfo<'a>,
+    pub member: &'a AccountInfo<'a>,
+}
+
+pub struct BanMemberContext<'a> {
+    pub community: &'a AccountInfo<'a>,
+    pub membership: &'a AccountInfo<'a>,
+    pub moderator: &'a AccountInfo<'a>,
+    pub moderator_membership: &'a AccountInfo<'a>,
+    pub system_program: &'a AccountInfo<'a>,
+}
+
+pub struct AcceptCommunityAuthorityContext<'a> {
+    pub community: &'a AccountInfo<'a>,
+    pub new_authority: &'a AccountInfo<'a>,
+}
+
+pub struct CreateProjectContext<'a> {
+    pub project: &'a AccountInfo<'a>,
+    pub vault: &'a AccountInfo<'a>,
+    pub community: &'a AccountInfo<'a>,
+    pub membership: &'a AccountInfo<'a>,
+    pub owner: &'a AccountInfo<'a>,
+    pub mint: &'a AccountInfo<'a>,
+    pub token_program: &'a AccountInfo<'a>,
+    pub system_program: &'a AccountInfo<'a>,
+}
+
+pub struct ContributeContext<'a> {
+    pub project: &'a AccountInfo<'a>,
+    pub vault: &'a AccountInfo<'a>,
+    pub contribution: &'a AccountInfo<'a>,
+    pub source_token_account: &'a AccountInfo<'a>,
+    pub contributor: &'a AccountInfo<'a>,
+    pub token_program: &'a AccountInfo<'a>,
+    pub system_program: &'a AccountInfo<'a>,
+}
+
+/// A project and its signing owner.
+pub struct ProjectOwnerContext<'a> {
+    pub project: &'a AccountInfo<'a>,
+    pub owner: &'a AccountInfo<'a>,
+}
+
+pub struct VoteMilestoneContext<'a> {
+    pub project: &'a AccountInfo<'a>,
+    pub contribution: &'a AccountInfo<'a>,
+    pub contributor: &'a AccountInfo<'a>,
+}
+
+pub struct FinalizeMilestoneContext<'a> {
+    pub project: &'a AccountInfo<'a>,
+    pub vault: &'a AccountInfo<'a>,
+    pub owner_token_account: &'a AccountInfo<'a>,
+    pub token_program: &'a AccountInfo<'a>,
+}
+
+/// A project being cancelled by its owner, or by the authority of its
+/// community passing the community account.
+pub struct CancelProjectContext<'a> {
+    pub project: &'a AccountInfo<'a>,
+    pub signer: &'a AccountInfo<'a>,
+    pub community: Option<&'a AccountInfo<'a>>,
+}
+
+pub struct ClaimRefundContext<'a> {
+    pub project: &'a AccountInfo<'a>,
+    pub vault: &'a AccountInfo<'a>,
+    pub contribution: &'a AccountInfo<'a>,
+    pub destination_token_account: &'a AccountInfo<'a>,
+    pub contributor: &'a AccountInfo<'a>,
+    pub token_program: &'a AccountInfo<'a>,
+}
+
+pub struct PayDuesContext<'a> {
+    pub community: &'a AccountInfo<'a>,
+    pub membership: &'a AccountInfo<'a>,
+    pub member: &'a AccountInfo<'a>,
+    pub source_token_account: &'a AccountInfo<'a>,
+    pub treasury_token_account: &'a AccountInfo<'a>,
+    pub token_program: &'a AccountInfo<'a>,
+}
+
+pub struct LapseMembershipContext<'a> {
+    pub community: &'a AccountInfo<'a>,
+    pub membership: &'a AccountInfo<'a>,
+}
+
+/// A community choosing the realm that governs its treasury, signed by its
+/// authority the first time and by that realm's governance authority after.
+pub struct SetTreasuryRealmContext<'a> {
+    pub community: &'a AccountInfo<'a>,
+    pub signer: &'a AccountInfo<'a>,
+    pub realm: &'a AccountInfo<'a>,
+}
+
+/// `remaining`: `TreasurySolAccounts` or `TreasuryTokenAccounts`, as for a
+/// realm treasury transfer.
+pub struct CommunityTreasuryTransferContext<'a> {
+    pub governance_authority: &'a AccountInfo<'a>,
+    pub realm: &'a AccountInfo<'a>,
+    pub community: &'a AccountInfo<'a>,
+    pub treasury: &'a AccountInfo<'a>,
+    pub remaining: &'a [AccountInfo<'a>],
 }
 
 impl<'a> CreateCommunityContext<'a> {
-    pub fn validate(&self, _program_id: &Pubkey) -> ProgramResult {
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
         // Verify community is not initialized
-        let community_data = Community::unpack(&self.community.data.borrow())?;
-        if community_data.is_initialized {
-            return Err(ProgramError::AccountAlreadyInitialized);
-        }
-        
+        check_uninitialized::<Community>(program_id, self.community)?;
+
         // Verify authority is signer
-        if !self.authority.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
-        
+        check_signer(self.authority)?;
+
         // Verify system program
-        if self.system_program.key != &solana_program::system_program::id() {
-            return Err(ProgramError::IncorrectProgramId);
-        }
-        
-        Ok(())
+        check_system_program(self.system_program)
+    }
+}
+
+impl<'a> Accounts<'a> for CreateCommunityContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = CreateCommunityContext {
+            community: next_account_info(account_info_iter)?,
+            authority: next_account_info(account_info_iter)?,
+            system_program: next_account_info(account_info_iter)?,
+            membership: next_account_info(account_info_iter)?,
+        };
+        context.validate(program_id)?;
+        Ok(context)
     }
 }
 
 impl<'a> UpdateCommunityContext<'a> {
-    pub fn validate(&self, _program_id: &Pubkey) -> ProgramResult {
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
         // Verify community is initialized
+        check_program_owned(program_id, self.community)?;
         let community_data = Community::unpack(&self.community.data.borrow())?;
         if !community_data.is_initialized {
             return Err(ProgramError::UninitializedAccount);
         }
-        
+
         // Verify authority is signer
-        if !self.authority.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
-        
+        check_signer(self.authority)?;
+
         // Verify authority matches
         if community_data.authority != *self.authority.key {
             return Err(ProgramError::IllegalOwner);
         }
-        
+
         Ok(())
     }
 }
 
-impl<'a> SuspendCommunityContext<'a> {
-    pub fn validate(&self, _program_id: &Pubkey) -> ProgramResult {
-        // Verify community is initialized
-        let community_data = Community::unpack(&self.community.data.borrow())?;
-        if !community_data.is_initialized {
-            return Err(ProgramError::UninitializedAccount);
-        }
-        
-        // Verify authority is signer
-        if !self.authority.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
+impl<'a> Accounts<'a> for UpdateCommunityContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = UpdateCommunityContext {
+            community: next_account_info(account_info_iter)?,
+            authority: next_account_info(account_info_iter)?,
+        };
+        context.validate(program_id)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for ModerateCommunityContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = ModerateCommunityContext {
+            community: next_account_info(account_info_iter)?,
+            moderator: next_account_info(account_info_iter)?,
+            user_profile: account_info_iter.next(),
+        };
+        check_signer(context.moderator)?;
+        check_program_owned(program_id, context.community)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for JoinCommunityContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = JoinCommunityContext {
+            community: next_account_info(account_info_iter)?,
+            membership: next_account_info(account_info_iter)?,
+            member: next_account_info(account_info_iter)?,
+            system_program: next_account_info(account_info_iter)?,
+        };
+        check_signer(context.member)?;
+        check_program_owned(program_id, context.community)?;
+        check_system_program(context.system_program)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for InviteMemberContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = InviteMemberContext {
+            community: next_account_info(account_info_iter)?,
+            membership: next_account_info(account_info_iter)?,
+            inviter: next_account_info(account_info_iter)?,
+            inviter_membership: next_account_info(account_info_iter)?,
+            system_program: next_account_info(account_info_iter)?,
+        };
+        check_signer(context.inviter)?;
+        check_program_owned(program_id, context.community)?;
+        check_system_program(context.system_program)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for LeaveCommunityContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = LeaveCommunityContext {
+            community: next_account_info(account_info_iter)?,
+            membership: next_account_info(account_info_iter)?,
+            member: next_account_info(account_info_iter)?,
+        };
+        check_signer(context.member)?;
+        check_program_owned(program_id, context.community)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for SetMemberRoleContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = SetMemberRoleContext {
+            community: next_account_info(account_info_iter)?,
+            membership: next_account_info(account_info_iter)?,
+            admin: next_account_info(account_info_iter)?,
+            admin_membership: next_account_info(account_info_iter)?,
+        };
+        check_signer(context.admin)?;
+        check_program_owned(program_id, context.community)?;
+        check_program_owned(program_id, context.membership)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for KickMemberContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = KickMemberContext {
+            community: next_account_info(account_info_iter)?,
+            membership: next_account_info(account_info_iter)?,
+            moderator: next_account_info(account_info_iter)?,
+            moderator_membership: next_account_info(account_info_iter)?,
+            member: next_account_info(account_info_iter)?,
+        };
+        check_signer(context.moderator)?;
+        check_program_owned(program_id, context.community)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for BanMemberContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = BanMemberContext {
+            community: next_account_info(account_info_iter)?,
+            membership: next_account_info(account_info_iter)?,
+            moderator: next_account_info(account_info_iter)?,
+            moderator_membership: next_account_info(account_info_iter)?,
+            system_program: next_account_info(account_info_iter)?,
+        };
+        check_signer(context.moderator)?;
+        check_program_owned(program_id, context.community)?;
+        check_system_program(context.system_program)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for AcceptCommunityAuthorityContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = AcceptCommunityAuthorityContext {
+            community: next_account_info(account_info_iter)?,
+            new_authority: next_account_info(account_info_iter)?,
+        };
+        check_signer(context.new_authority)?;
+        check_program_owned(program_id, context.community)?;
+        Ok(context)
+    }
+}
+
+impl<'a> CreateProjectContext<'a> {
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
+        // Verify project is not initialized
+        check_uninitialized::<CommunityProject>(program_id, self.project)?;
+
+        // Verify owner is signer
+        check_signer(self.owner)?;
+
+        check_program_owned(program_id, self.community)?;
+
+        // Verify mint is an SPL token mint
+        if self.mint.owner != &spl_token::id() {
+            return Err(ProgramError::IncorrectProgramId);
         }
-        
-        // Verify authority matches
-        if community_data.authority != *self.authority.key {
+
+        check_token_program(self.token_program)?;
+        check_system_program(self.system_program)
+    }
+}
+
+impl<'a> Accounts<'a> for CreateProjectContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = CreateProjectContext {
+            project: next_account_info(account_info_iter)?,
+            vault: next_account_info(account_info_iter)?,
+            community: next_account_info(account_info_iter)?,
+            membership: next_account_info(account_info_iter)?,
+            owner: next_account_info(account_info_iter)?,
+            mint: next_account_info(account_info_iter)?,
+            token_program: next_account_info(account_info_iter)?,
+            system_program: next_account_info(account_info_iter)?,
+        };
+        context.validate(program_id)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for ContributeContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = ContributeContext {
+            project: next_account_info(account_info_iter)?,
+            vault: next_account_info(account_info_iter)?,
+            contribution: next_account_info(account_info_iter)?,
+            source_token_account: next_account_info(account_info_iter)?,
+            contributor: next_account_info(account_info_iter)?,
+            token_program: next_account_info(account_info_iter)?,
+            system_program: next_account_info(account_info_iter)?,
+        };
+        check_signer(context.contributor)?;
+        check_program_owned(program_id, context.project)?;
+        check_token_program(context.token_program)?;
+        check_system_program(context.system_program)?;
+        Ok(context)
+    }
+}
+
+impl<'a> ProjectOwnerContext<'a> {
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
+        check_program_owned(program_id, self.project)?;
+
+        // Verify owner is signer
+        check_signer(self.owner)?;
+
+        // Verify owner matches
+        let project_data = CommunityProject::unpack(&self.project.data.borrow())?;
+        if project_data.owner != *self.owner.key {
             return Err(ProgramError::IllegalOwner);
         }
-        
+
         Ok(())
     }
-} 
\ No newline at end of file
+}
+
+impl<'a> Accounts<'a> for ProjectOwnerContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = ProjectOwnerContext {
+            project: next_account_info(account_info_iter)?,
+            owner: next_account_info(account_info_iter)?,
+        };
+        context.validate(program_id)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for VoteMilestoneContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = VoteMilestoneContext {
+            project: next_account_info(account_info_iter)?,
+            contribution: next_account_info(account_info_iter)?,
+            contributor: next_account_info(account_info_iter)?,
+        };
+        check_signer(context.contributor)?;
+        check_program_owned(program_id, context.project)?;
+        check_program_owned(program_id, context.contribution)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for FinalizeMilestoneContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = FinalizeMilestoneContext {
+            project: next_account_info(account_info_iter)?,
+            vault: next_account_info(account_info_iter)?,
+            owner_token_account: next_account_info(account_info_iter)?,
+            token_program: next_account_info(account_info_iter)?,
+        };
+        check_program_owned(program_id, context.project)?;
+        check_token_program(context.token_program)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for CancelProjectContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = CancelProjectContext {
+            project: next_account_info(account_info_iter)?,
+            signer: next_account_info(account_info_iter)?,
+            community: account_info_iter.next(),
+        };
+        check_signer(context.signer)?;
+        check_program_owned(program_id, context.project)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for ClaimRefundContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = ClaimRefundContext {
+            project: next_account_info(account_info_iter)?,
+            vault: next_account_info(account_info_iter)?,
+            contribution: next_account_info(account_info_iter)?,
+            destination_token_account: next_account_info(account_info_iter)?,
+            contributor: next_account_info(account_info_iter)?,
+            token_program: next_account_info(account_info_iter)?,
+        };
+        check_signer(context.contributor)?;
+        check_program_owned(program_id, context.project)?;
+        check_program_owned(program_id, context.contribution)?;
+        check_token_program(context.token_program)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for PayDuesContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = PayDuesContext {
+            community: next_account_info(account_info_iter)?,
+            membership: next_account_info(account_info_iter)?,
+            member: next_account_info(account_info_iter)?,
+            source_token_account: next_account_info(account_info_iter)?,
+            treasury_token_account: next_account_info(account_info_iter)?,
+            token_program: next_account_info(account_info_iter)?,
+        };
+        check_signer(context.member)?;
+        check_program_owned(program_id, context.community)?;
+        check_token_program(context.token_program)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for LapseMembershipContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = LapseMembershipContext {
+            community: next_account_info(account_info_iter)?,
+            membership: next_account_info(account_info_iter)?,
+        };
+        check_program_owned(program_id, context.community)?;
+        check_program_owned(program_id, context.membership)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for SetTreasuryRealmContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = SetTreasuryRealmContext {
+            community: next_account_info(account_info_iter)?,
+            signer: next_account_info(account_info_iter)?,
+            realm: next_account_info(account_info_iter)?,
+        };
+        check_signer(context.signer)?;
+        check_program_owned(program_id, context.community)?;
+        check_program_owned(program_id, context.realm)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for CommunityTreasuryTransferContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let governance_authority = next_account_info(account_info_iter)?;
+        let realm = next_account_info(account_info_iter)?;
+        let community = next_account_info(account_info_iter)?;
+        let treasury = next_account_info(account_info_iter)?;
+        let context = CommunityTreasuryTransferContext {
+            governance_authority,
+            realm,
+            community,
+            treasury,
+            remaining: account_info_iter.as_slice(),
+        };
+
+        // Verify governance authority signed
+        check_governance_authority(program_id, context.realm, context.governance_authority)?;
+        check_program_owned(program_id, context.community)?;
+
+        // Verify treasury address
+        let (treasury_key, _) = find_community_treasury(program_id, context.community.key);
+        if context.treasury.key != &treasury_key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        Ok(context)
+    }
+}
@@ -1,35 +1,52 @@
 use solana_program::{
-    account_info::{next_account_info, AccountInfo},
+    account_info::AccountInfo,
     clock::Clock,
     entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
     program_error::ProgramError,
     program_pack::Pack,
     pubkey::Pubkey,
+    system_instruction,
     sysvar::Sysvar,
 };
-use crate::community::state::{Community, CommunityParams, CommunityStatus};
+use spl_token::instruction as token_instruction;
+use crate::account::{close_account, create_pda_account, AccountState};
+use crate::community::context::{
+    AcceptCommunityAuthorityContext, BanMemberContext, CancelProjectContext, ClaimRefundContext,
+    CommunityTreasuryTransferContext, ContributeContext, CreateCommunityContext, CreateProjectContext,
+    FinalizeMilestoneContext, InviteMemberContext, JoinCommunityContext, KickMemberContext, LapseMembershipContext,
+    LeaveCommunityContext, ModerateCommunityContext, PayDuesContext, ProjectOwnerContext, SetMemberRoleContext,
+    SetTreasuryRealmContext, UpdateCommunityContext, VoteMilestoneContext,
+};
+use crate::context::Accounts;
+use crate::community::state::{
+    Community, CommunityParams, CommunityProject, CommunityRole, CommunityStatus, Membership, MembershipDues,
+    MembershipDuesParams, MembershipStatus, ProjectContribution, ProjectMilestone, ProjectParams, ProjectStatus,
+    CONTRIBUTION_SEED, MEMBERSHIP_SEED, PROJECT_VAULT_SEED,
+};
+use crate::community::treasury::{find_community_treasury, find_community_treasury_token_account, COMMUNITY_TREASURY_SEED};
+use crate::governance::context::{TreasurySolAccounts, TreasuryTokenAccounts};
+use crate::governance::state::{find_governance_authority, Realm, RealmScope};
+use crate::governance::treasury::TreasuryAsset;
+use crate::error::UnityVaultError;
+use crate::user::state::UserProfile;
 use borsh::{BorshSerialize, BorshDeserialize};
 
-pub fn create_community(
-    _program_id: &Pubkey,
-    accounts: &[AccountInfo],
+/// Creates a community with its creator as the first member and admin.
+///
+/// 0. `[writable]` The community
+/// 1. `[signer, writable]` The authority, paying for their membership
+/// 2. `[]` The system program
+/// 3. `[writable]` The authority's membership PDA
+pub fn create_community<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
     params: CommunityParams,
 ) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
-    
-    let community = next_account_info(account_info_iter)?;
-    let authority = next_account_info(account_info_iter)?;
-    let system_program = next_account_info(account_info_iter)?;
-    
-    // Verify authority is signer
-    if !authority.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    params.validate()?;
     
-    // Verify system program
-    if system_program.key != &solana_program::system_program::id() {
-        return Err(ProgramError::IncorrectProgramId);
-    }
+    let CreateCommunityContext { community, authority, system_program, membership } =
+        CreateCommunityContext::try_accounts(program_id, accounts)?;
     
     // Create and initialize community
     let community_data = Community {
@@ -41,36 +58,44 @@ pub fn create_community(
         is_private: params.is_private,
         status: CommunityStatus::Active,
         member_count: 1,
+        admin_count: 1,
+        pending_authority: None,
+        suspended_by_platform: false,
+        dues: None,
+        treasury_realm: None,
         created_at: Clock::get()?.unix_timestamp,
         updated_at: Clock::get()?.unix_timestamp,
     };
     
     // Pack the data into the account
-    community_data.pack_into_slice(&mut community.data.borrow_mut());
+    community_data.pack(&mut community.data.borrow_mut())?;
+    
+    // The creator is the member `member_count` starts at
+    create_membership(
+        program_id,
+        community.key,
+        membership,
+        authority.key,
+        authority,
+        system_program,
+        CommunityRole::Admin,
+        MembershipStatus::Active,
+    )?;
     
     Ok(())
 }
 
-pub fn update_community(
-    _program_id: &Pubkey,
-    accounts: &[AccountInfo],
+pub fn update_community<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
     params: CommunityParams,
 ) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
+    params.validate()?;
     
-    let community = next_account_info(account_info_iter)?;
-    let authority = next_account_info(account_info_iter)?;
+    let UpdateCommunityContext { community, .. } = UpdateCommunityContext::try_accounts(program_id, accounts)?;
     
-    // Verify authority is signer
-    if !authority.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
-    }
-    
-    // Verify authority matches
-    let mut community_data = Community::unpack_from_slice(&community.data.borrow())?;
-    if community_data.authority != *authority.key {
-        return Err(ProgramError::IllegalOwner);
-    }
+    let mut community_data = Community::unpack(&community.data.borrow())?;
+    community_data.check_active()?;
     
     // Update community
     community_data.name = params.name;
@@ -80,41 +105,1068 @@ pub fn update_community(
     community_data.updated_at = Clock::get()?.unix_timestamp;
     
     // Pack the updated data
-    community_data.pack_into_slice(&mut community.data.borrow_mut());
+    community_data.pack(&mut community.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Suspends an active community. Its authority or any platform moderator can.
+///
+/// 0. `[writable]` The community
+/// 1. `[signer]` The community authority or platform moderator
+/// 2. `[]` The moderator's user profile, when not the community authority
+pub fn suspend_community<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let ModerateCommunityContext { community, moderator, user_profile } =
+        ModerateCommunityContext::try_accounts(program_id, accounts)?;
+    
+    let mut community_data = Community::unpack(&community.data.borrow())?;
+    let by_platform = moderating_as_platform(program_id, &community_data, user_profile, moderator.key)?;
+    
+    // Suspend community
+    community_data.suspend(by_platform)?;
+    community_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    // Pack the updated data
+    community_data.pack(&mut community.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Lifts a suspension. Platform moderators can lift any, the community
+/// authority only one it made itself.
+///
+/// 0. `[writable]` The community
+/// 1. `[signer]` The community authority or platform moderator
+/// 2. `[]` The moderator's user profile, when not the community authority
+pub fn reinstate_community<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let ModerateCommunityContext { community, moderator, user_profile } =
+        ModerateCommunityContext::try_accounts(program_id, accounts)?;
+    
+    let mut community_data = Community::unpack(&community.data.borrow())?;
+    let by_platform = moderating_as_platform(program_id, &community_data, user_profile, moderator.key)?;
+    
+    community_data.reinstate(by_platform)?;
+    community_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    community_data.pack(&mut community.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Archives an active or suspended community for good. Its authority or any
+/// platform moderator can. Members can still leave to reclaim their rent.
+///
+/// 0. `[writable]` The community
+/// 1. `[signer]` The community authority or platform moderator
+/// 2. `[]` The moderator's user profile, when not the community authority
+pub fn archive_community<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let ModerateCommunityContext { community, moderator, user_profile } =
+        ModerateCommunityContext::try_accounts(program_id, accounts)?;
+    
+    let mut community_data = Community::unpack(&community.data.borrow())?;
+    moderating_as_platform(program_id, &community_data, user_profile, moderator.key)?;
+    
+    community_data.archive()?;
+    community_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    community_data.pack(&mut community.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Joins a community. Public communities admit the member right away; in
+/// private ones this accepts a pending invite or asks to be let in. A lapsed
+/// member can come back this way once the community stops charging dues.
+///
+/// 0. `[writable]` The community
+/// 1. `[writable]` The member's membership PDA
+/// 2. `[signer, writable]` The member, paying for a new membership
+/// 3. `[]` The system program
+pub fn join_community<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let JoinCommunityContext { community, membership, member, system_program } =
+        JoinCommunityContext::try_accounts(program_id, accounts)?;
+    
+    let mut community_data = Community::unpack(&community.data.borrow())?;
+    community_data.check_active()?;
+    
+    let (status, role) = if Membership::is_initialized_account(&membership.data.borrow()) {
+        let mut membership_data = load_membership(program_id, community.key, membership, member.key)?;
+        membership_data.rejoin(&community_data)?;
+        membership_data.joined_at = Clock::get()?.unix_timestamp;
+        membership_data.updated_at = membership_data.joined_at;
+        membership_data.pack(&mut membership.data.borrow_mut())?;
+        (membership_data.status, membership_data.role)
+    } else {
+        // New memberships always start as plain members
+        let status = Membership::join_status(&community_data);
+        create_membership(
+            program_id,
+            community.key,
+            membership,
+            member.key,
+            member,
+            system_program,
+            CommunityRole::Member,
+            status,
+        )?;
+        (status, CommunityRole::Member)
+    };
+    
+    if status == MembershipStatus::Active {
+        community_data.add_member(role)?;
+        community_data.pack(&mut community.data.borrow_mut())?;
+    }
+    
+    Ok(())
+}
+
+/// Invites `member` into a community, or admits them if they asked to join.
+/// Admins, moderators and the community authority can invite.
+///
+/// 0. `[writable]` The community
+/// 1. `[writable]` The invited member's membership PDA
+/// 2. `[signer, writable]` The inviter, paying for a new membership
+/// 3. `[]` The inviter's membership
+/// 4. `[]` The system program
+pub fn invite_member<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    member: Pubkey,
+) -> ProgramResult {
+    let InviteMemberContext { community, membership, inviter, inviter_membership, system_program } =
+        InviteMemberContext::try_accounts(program_id, accounts)?;
+    
+    let mut community_data = Community::unpack(&community.data.borrow())?;
+    community_data.check_active()?;
+    
+    // Verify inviter can manage members
+    let inviter_role = acting_role(program_id, community.key, &community_data, inviter_membership, inviter.key)?;
+    if !inviter_role.can_manage_members() {
+        return Err(UnityVaultError::InsufficientCommunityRole.into());
+    }
+    
+    if Membership::is_initialized_account(&membership.data.borrow()) {
+        let mut membership_data = load_membership(program_id, community.key, membership, &member)?;
+        membership_data.approve_request()?;
+        membership_data.joined_at = Clock::get()?.unix_timestamp;
+        membership_data.updated_at = membership_data.joined_at;
+        membership_data.pack(&mut membership.data.borrow_mut())?;
+        
+        community_data.add_member(membership_data.role)?;
+        community_data.pack(&mut community.data.borrow_mut())?;
+    } else {
+        create_membership(
+            program_id,
+            community.key,
+            membership,
+            &member,
+            inviter,
+            system_program,
+            CommunityRole::Member,
+            MembershipStatus::Invited,
+        )?;
+    }
+    
+    Ok(())
+}
+
+/// Leaves a community, or declines an invite or withdraws a request. The
+/// membership is closed and its rent returned to the member. Banned members
+/// can't leave, which would lift their ban, members owing dues have to pay
+/// them first, and the last admin can't leave either. Nobody can join an
+/// archived community again, so anyone can leave one.
+///
+/// 0. `[writable]` The community
+/// 1. `[writable]` The member's membership PDA
+/// 2. `[signer, writable]` The member
+pub fn leave_community<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let LeaveCommunityContext { community, membership, member } =
+        LeaveCommunityContext::try_accounts(program_id, accounts)?;
+    
+    let membership_data = load_membership(program_id, community.key, membership, member.key)?;
+    let mut community_data = Community::unpack(&community.data.borrow())?;
+    let now = Clock::get()?.unix_timestamp;
+    membership_data.check_can_leave(&community_data, now)?;
+    if membership_data.is_active() {
+        community_data.remove_member(membership_data.role)?;
+        community_data.updated_at = now;
+        community_data.pack(&mut community.data.borrow_mut())?;
+    }
+    
+    close_account(membership, member)
+}
+
+/// Changes the role of an active member. Only admins and the community
+/// authority can, and the last admin can't be demoted.
+///
+/// 0. `[writable]` The community
+/// 1. `[writable]` The member's membership
+/// 2. `[signer]` The admin
+/// 3. `[]` The admin's membership
+pub fn set_member_role<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    role: CommunityRole,
+) -> ProgramResult {
+    let SetMemberRoleContext { community, membership, admin, admin_membership } =
+        SetMemberRoleContext::try_accounts(program_id, accounts)?;
+    
+    let mut community_data = Community::unpack(&community.data.borrow())?;
+    community_data.check_active()?;
+    
+    // Verify admin role
+    if acting_role(program_id, community.key, &community_data, admin_membership, admin.key)? != CommunityRole::Admin {
+        return Err(UnityVaultError::InsufficientCommunityRole.into());
+    }
+    
+    // Verify the member belongs to the community
+    let mut membership_data = Membership::unpack(&membership.data.borrow())?;
+    if membership_data.community != *community.key || !membership_data.is_active() {
+        return Err(UnityVaultError::NotACommunityMember.into());
+    }
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    community_data.change_role(membership_data.role, role)?;
+    community_data.updated_at = current_time;
+    membership_data.role = role;
+    membership_data.updated_at = current_time;
+    
+    community_data.pack(&mut community.data.borrow_mut())?;
+    membership_data.pack(&mut membership.data.borrow_mut())?;
     
     Ok(())
 }
 
-pub fn suspend_community(
-    _program_id: &Pubkey,
-    accounts: &[AccountInfo],
+/// Removes a member, or rejects a request, cancels an invite or lifts a ban.
+/// Moderators can remove members, and admins moderators too. The membership
+/// is closed and its rent returned to the member. Like the rest of member
+/// management, this waits until a suspended community is reinstated.
+///
+/// 0. `[writable]` The community
+/// 1. `[writable]` The member's membership
+/// 2. `[signer]` The admin or moderator
+/// 3. `[]` The admin's or moderator's membership
+/// 4. `[writable]` The member
+pub fn kick_member<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let KickMemberContext { community, membership, moderator, moderator_membership, member } =
+        KickMemberContext::try_accounts(program_id, accounts)?;
+    
+    let mut community_data = Community::unpack(&community.data.borrow())?;
+    community_data.check_active()?;
+    
+    // Verify the moderator outranks the member
+    let moderator_role = acting_role(program_id, community.key, &community_data, moderator_membership, moderator.key)?;
+    let membership_data = load_membership(program_id, community.key, membership, member.key)?;
+    if !moderator_role.can_manage_members() || !moderator_role.outranks(membership_data.role) {
+        return Err(UnityVaultError::InsufficientCommunityRole.into());
+    }
+    
+    if membership_data.is_active() {
+        community_data.remove_member(membership_data.role)?;
+        community_data.updated_at = Clock::get()?.unix_timestamp;
+        community_data.pack(&mut community.data.borrow_mut())?;
+    }
+    
+    close_account(membership, member)
+}
+
+/// Bans `member`, removing them if they are in the community and keeping
+/// them from joining until a kick lifts the ban. Moderators can ban members,
+/// and admins moderators too. Banning someone without a membership creates
+/// one, paid by the moderator.
+///
+/// 0. `[writable]` The community
+/// 1. `[writable]` The member's membership PDA
+/// 2. `[signer, writable]` The admin or moderator
+/// 3. `[]` The admin's or moderator's membership
+/// 4. `[]` The system program
+pub fn ban_member<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    member: Pubkey,
 ) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
+    let BanMemberContext { community, membership, moderator, moderator_membership, system_program } =
+        BanMemberContext::try_accounts(program_id, accounts)?;
+    
+    let mut community_data = Community::unpack(&community.data.borrow())?;
+    community_data.check_active()?;
     
-    let community = next_account_info(account_info_iter)?;
-    let authority = next_account_info(account_info_iter)?;
+    let moderator_role = acting_role(program_id, community.key, &community_data, moderator_membership, moderator.key)?;
+    if !moderator_role.can_manage_members() {
+        return Err(UnityVaultError::InsufficientCommunityRole.into());
+    }
     
-    // Verify authority is signer
-    if !authority.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
+    if !Membership::is_initialized_account(&membership.data.borrow()) {
+        return create_membership(
+            program_id,
+            community.key,
+            membership,
+            &member,
+            moderator,
+            system_program,
+            CommunityRole::Member,
+            MembershipStatus::Banned,
+        );
     }
     
-    // Verify authority matches
-    let mut community_data = Community::unpack_from_slice(&community.data.borrow())?;
-    if community_data.authority != *authority.key {
+    // Verify the moderator outranks the member
+    let mut membership_data = load_membership(program_id, community.key, membership, &member)?;
+    membership_data.check_not_banned()?;
+    if !moderator_role.outranks(membership_data.role) {
+        return Err(UnityVaultError::InsufficientCommunityRole.into());
+    }
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    if membership_data.is_active() {
+        community_data.remove_member(membership_data.role)?;
+        community_data.updated_at = current_time;
+        community_data.pack(&mut community.data.borrow_mut())?;
+    }
+    membership_data.role = CommunityRole::Member;
+    membership_data.status = MembershipStatus::Banned;
+    membership_data.updated_at = current_time;
+    
+    membership_data.pack(&mut membership.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Proposes `new_authority` as the community authority, or withdraws the
+/// proposal with `None`. The handoff completes once the new authority accepts.
+///
+/// 0. `[writable]` The community
+/// 1. `[signer]` The current authority
+pub fn transfer_community_authority<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    new_authority: Option<Pubkey>,
+) -> ProgramResult {
+    let UpdateCommunityContext { community, .. } = UpdateCommunityContext::try_accounts(program_id, accounts)?;
+    
+    let mut community_data = Community::unpack(&community.data.borrow())?;
+    
+    community_data.pending_authority = new_authority;
+    community_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    community_data.pack(&mut community.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Completes an authority handoff started by `transfer_community_authority`.
+///
+/// 0. `[writable]` The community
+/// 1. `[signer]` The pending authority
+pub fn accept_community_authority<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let AcceptCommunityAuthorityContext { community, new_authority } =
+        AcceptCommunityAuthorityContext::try_accounts(program_id, accounts)?;
+    
+    // Verify the handoff was offered to the signer
+    let mut community_data = Community::unpack(&community.data.borrow())?;
+    if community_data.pending_authority != Some(*new_authority.key) {
         return Err(ProgramError::IllegalOwner);
     }
     
-    // Suspend community
-    community_data.status = CommunityStatus::Suspended;
+    community_data.authority = *new_authority.key;
+    community_data.pending_authority = None;
     community_data.updated_at = Clock::get()?.unix_timestamp;
     
-    // Pack the updated data
-    community_data.pack_into_slice(&mut community.data.borrow_mut());
+    community_data.pack(&mut community.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Starts a crowdfunding project in an active community and creates its
+/// escrow vault. The owner must be an active member or the community authority.
+///
+/// 0. `[writable]` The project
+/// 1. `[writable]` The project's vault PDA
+/// 2. `[]` The community
+/// 3. `[]` The owner's membership PDA
+/// 4. `[signer, writable]` The owner, paying for the vault
+/// 5. `[]` The mint contributions are made in
+/// 6. `[]` The token program
+/// 7. `[]` The system program
+pub fn create_project<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    params: ProjectParams,
+) -> ProgramResult {
+    let current_time = Clock::get()?.unix_timestamp;
+    params.validate(current_time)?;
+    
+    let CreateProjectContext { project, vault, community, membership, owner, mint, token_program, system_program } =
+        CreateProjectContext::try_accounts(program_id, accounts)?;
+    
+    let community_data = Community::unpack(&community.data.borrow())?;
+    community_data.check_active()?;
+    acting_role(program_id, community.key, &community_data, membership, owner.key)?;
+    
+    // Verify vault address
+    let (vault_key, vault_bump) = CommunityProject::find_vault(program_id, project.key);
+    if vault.key != &vault_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    create_pda_account(
+        &spl_token::id(),
+        owner,
+        vault,
+        system_program,
+        spl_token::state::Account::LEN,
+        &[PROJECT_VAULT_SEED, project.key.as_ref(), &[vault_bump]],
+    )?;
+    invoke(
+        &token_instruction::initialize_account3(token_program.key, &vault_key, mint.key, &vault_key)?,
+        &[vault.clone(), mint.clone(), token_program.clone()],
+    )?;
+    
+    let project_data = CommunityProject {
+        is_initialized: true,
+        community: *community.key,
+        owner: *owner.key,
+        funding_goal: params.funding_goal()?,
+        title: params.title,
+        description: params.description,
+        mint: *mint.key,
+        funding_deadline: params.funding_deadline,
+        total_contributed: 0,
+        total_released: 0,
+        contributor_count: 0,
+        status: ProjectStatus::Funding,
+        milestones: params.milestones.into_iter().map(ProjectMilestone::from).collect(),
+        current_milestone: 0,
+        vote_round: 0,
+        vault_bump,
+        created_at: current_time,
+        updated_at: current_time,
+    };
+    
+    project_data.pack(&mut project.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Escrows `amount` in a project that is still raising funds. The first
+/// contribution creates the contributor's record.
+///
+/// 0. `[writable]` The project
+/// 1. `[writable]` The project's vault
+/// 2. `[writable]` The contributor's contribution PDA
+/// 3. `[writable]` The contributor's token account to pay from
+/// 4. `[signer, writable]` The contributor, paying for a new contribution record
+/// 5. `[]` The token program
+/// 6. `[]` The system program
+pub fn contribute<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    amount: u64,
+) -> ProgramResult {
+    let ContributeContext { project, vault, contribution, source_token_account, contributor, token_program, system_program } =
+        ContributeContext::try_accounts(program_id, accounts)?;
+    
+    let mut project_data = CommunityProject::unpack(&project.data.borrow())?;
+    check_project_vault(program_id, project.key, &project_data, vault)?;
+    
+    // Verify contribution address
+    let (contribution_key, contribution_bump) = ProjectContribution::find_address(program_id, project.key, contributor.key);
+    if contribution.key != &contribution_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    project_data.contribute(contributor.key, amount, current_time)?;
+    
+    let mut contribution_data = if ProjectContribution::is_initialized_account(&contribution.data.borrow()) {
+        ProjectContribution::unpack(&contribution.data.borrow())?
+    } else {
+        create_pda_account(
+            program_id,
+            contributor,
+            contribution,
+            system_program,
+            ProjectContribution::LEN,
+            &[CONTRIBUTION_SEED, project.key.as_ref(), contributor.key.as_ref(), &[contribution_bump]],
+        )?;
+        project_data.contributor_count =
+            project_data.contributor_count.checked_add(1).ok_or(ProgramError::ArithmeticOverflow)?;
+        ProjectContribution {
+            is_initialized: true,
+            project: *project.key,
+            contributor: *contributor.key,
+            amount: 0,
+            voted_round: 0,
+            refunded: false,
+            bump: contribution_bump,
+            created_at: current_time,
+            updated_at: current_time,
+        }
+    };
+    contribution_data.add(amount)?;
+    contribution_data.updated_at = current_time;
+    project_data.updated_at = current_time;
+    
+    // Move the tokens into escrow
+    invoke(
+        &token_instruction::transfer(token_program.key, source_token_account.key, vault.key, contributor.key, &[], amount)?,
+        &[source_token_account.clone(), vault.clone(), contributor.clone(), token_program.clone()],
+    )?;
+    
+    contribution_data.pack(&mut contribution.data.borrow_mut())?;
+    project_data.pack(&mut project.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Submits the current milestone of a funded project for contributors to
+/// vote on, before its deadline.
+///
+/// 0. `[writable]` The project
+/// 1. `[signer]` The project owner
+pub fn submit_milestone<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let ProjectOwnerContext { project, .. } = ProjectOwnerContext::try_accounts(program_id, accounts)?;
+    
+    let mut project_data = CommunityProject::unpack(&project.data.borrow())?;
+    let current_time = Clock::get()?.unix_timestamp;
+    project_data.submit_milestone(current_time)?;
+    project_data.updated_at = current_time;
+    
+    project_data.pack(&mut project.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Votes for or against releasing the submitted milestone, weighted by the
+/// contributor's contribution.
+///
+/// 0. `[writable]` The project
+/// 1. `[writable]` The contributor's contribution
+/// 2. `[signer]` The contributor
+pub fn vote_milestone<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    approve: bool,
+) -> ProgramResult {
+    let VoteMilestoneContext { project, contribution, contributor } =
+        VoteMilestoneContext::try_accounts(program_id, accounts)?;
+    
+    let mut project_data = CommunityProject::unpack(&project.data.borrow())?;
+    let mut contribution_data = load_contribution(project.key, contribution, contributor.key)?;
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    project_data.vote_milestone(&mut contribution_data, approve, current_time)?;
+    contribution_data.updated_at = current_time;
+    
+    contribution_data.pack(&mut contribution.data.borrow_mut())?;
+    project_data.pack(&mut project.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Settles the vote on the submitted milestone, paying its tranche to the
+/// owner if approved. Anyone can call it once the vote is decided.
+///
+/// 0. `[writable]` The project
+/// 1. `[writable]` The project's vault
+/// 2. `[writable]` The owner's token account for the project mint
+/// 3. `[]` The token program
+pub fn finalize_milestone<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let FinalizeMilestoneContext { project, vault, owner_token_account, token_program } =
+        FinalizeMilestoneContext::try_accounts(program_id, accounts)?;
+    
+    let mut project_data = CommunityProject::unpack(&project.data.borrow())?;
+    check_project_vault(program_id, project.key, &project_data, vault)?;
+    
+    // Verify tranches go to the owner
+    let owner_token_account_data = spl_token::state::Account::unpack(&owner_token_account.data.borrow())?;
+    if owner_token_account_data.owner != project_data.owner {
+        return Err(ProgramError::IllegalOwner);
+    }
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    if let Some(amount) = project_data.finalize_milestone(current_time)? {
+        release_from_vault(project.key, &project_data, vault, owner_token_account, token_program, amount)?;
+    }
+    project_data.updated_at = current_time;
+    
+    project_data.pack(&mut project.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Cancels a project that is raising funds or delivering milestones, opening
+/// refunds of whatever is still in escrow.
+///
+/// 0. `[writable]` The project
+/// 1. `[signer]` The project owner or community authority
+/// 2. `[]` The project's community, when signed by its authority
+pub fn cancel_project<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let CancelProjectContext { project, signer, community } = CancelProjectContext::try_accounts(program_id, accounts)?;
+    
+    let mut project_data = CommunityProject::unpack(&project.data.borrow())?;
+    
+    // Verify signer is the owner or the community authority
+    if project_data.owner != *signer.key {
+        let community = community.ok_or(ProgramError::IllegalOwner)?;
+        if community.key != &project_data.community || community.owner != program_id {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if Community::unpack(&community.data.borrow())?.authority != *signer.key {
+            return Err(ProgramError::IllegalOwner);
+        }
+    }
+    
+    project_data.cancel()?;
+    project_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    project_data.pack(&mut project.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Pays a contributor back their share of the escrow once the project is
+/// cancelled or has missed its funding or current milestone deadline.
+///
+/// 0. `[writable]` The project
+/// 1. `[writable]` The project's vault
+/// 2. `[writable]` The contributor's contribution
+/// 3. `[writable]` The token account to refund to
+/// 4. `[signer]` The contributor
+/// 5. `[]` The token program
+pub fn claim_refund<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let ClaimRefundContext { project, vault, contribution, destination_token_account, contributor, token_program } =
+        ClaimRefundContext::try_accounts(program_id, accounts)?;
+    
+    let mut project_data = CommunityProject::unpack(&project.data.borrow())?;
+    check_project_vault(program_id, project.key, &project_data, vault)?;
+    let mut contribution_data = load_contribution(project.key, contribution, contributor.key)?;
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    project_data.check_refundable(current_time)?;
+    let amount = project_data.refund_amount(&contribution_data);
+    contribution_data.mark_refunded()?;
+    contribution_data.updated_at = current_time;
+    project_data.updated_at = current_time;
+    
+    if amount > 0 {
+        release_from_vault(project.key, &project_data, vault, destination_token_account, token_program, amount)?;
+    }
+    
+    contribution_data.pack(&mut contribution.data.borrow_mut())?;
+    project_data.pack(&mut project.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Sets or clears the dues a community charges. New dues take effect now,
+/// so every unpaid member gets a fresh grace period.
+///
+/// 0. `[writable]` The community
+/// 1. `[signer]` The community authority
+pub fn set_membership_dues<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    dues: Option<MembershipDuesParams>,
+) -> ProgramResult {
+    if let Some(dues) = &dues {
+        dues.validate()?;
+    }
+    
+    let UpdateCommunityContext { community, .. } = UpdateCommunityContext::try_accounts(program_id, accounts)?;
+    
+    let mut community_data = Community::unpack(&community.data.borrow())?;
+    community_data.check_active()?;
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    community_data.dues = dues.map(|dues| MembershipDues::new(dues, current_time));
+    community_data.updated_at = current_time;
+    
+    community_data.pack(&mut community.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Pays `periods` periods of dues into the community treasury. Paying
+/// restores a lapsed membership.
+///
+/// 0. `[writable]` The community
+/// 1. `[writable]` The member's membership
+/// 2. `[signer]` The member
+/// 3. `[writable]` The member's token account for the dues mint
+/// 4. `[writable]` The community treasury's associated token account for the dues mint
+/// 5. `[]` The token program
+pub fn pay_dues<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    periods: u32,
+) -> ProgramResult {
+    let PayDuesContext { community, membership, member, source_token_account, treasury_token_account, token_program } =
+        PayDuesContext::try_accounts(program_id, accounts)?;
+    
+    let mut community_data = Community::unpack(&community.data.borrow())?;
+    community_data.check_active()?;
+    let dues = community_data.dues.ok_or(UnityVaultError::DuesNotCharged)?;
+    let mut membership_data = load_membership(program_id, community.key, membership, member.key)?;
+    
+    // Verify dues go to the treasury
+    if treasury_token_account.key != &find_community_treasury_token_account(program_id, community.key, &dues.mint) {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    let was_lapsed = membership_data.status == MembershipStatus::Lapsed;
+    let amount = membership_data.pay_dues(&community_data, periods, current_time)?;
+    membership_data.updated_at = current_time;
+    
+    invoke(
+        &token_instruction::transfer(
+            token_program.key,
+            source_token_account.key,
+            treasury_token_account.key,
+            member.key,
+            &[],
+            amount,
+        )?,
+        &[source_token_account.clone(), treasury_token_account.clone(), member.clone(), token_program.clone()],
+    )?;
+    
+    membership_data.pack(&mut membership.data.borrow_mut())?;
+    
+    if was_lapsed {
+        community_data.add_member(membership_data.role)?;
+        community_data.pack(&mut community.data.borrow_mut())?;
+    }
+    
+    Ok(())
+}
+
+/// Lapses a membership whose dues are overdue, so it stops counting toward
+/// the community. Anyone can call it while the community is active.
+///
+/// 0. `[writable]` The community
+/// 1. `[writable]` The membership
+pub fn lapse_membership<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let LapseMembershipContext { community, membership } = LapseMembershipContext::try_accounts(program_id, accounts)?;
+    
+    let mut community_data = Community::unpack(&community.data.borrow())?;
+    community_data.check_active()?;
+    let mut membership_data = Membership::unpack(&membership.data.borrow())?;
+    if membership_data.community != *community.key {
+        return Err(UnityVaultError::NotACommunityMember.into());
+    }
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    membership_data.lapse(&community_data, current_time)?;
+    community_data.remove_member(membership_data.role)?;
+    membership_data.updated_at = current_time;
+    
+    membership_data.pack(&mut membership.data.borrow_mut())?;
+    community_data.pack(&mut community.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Chooses the realm whose governance spends the community treasury. The
+/// community authority picks the first one; after that only the current
+/// realm's governance can hand the treasury to another.
+///
+/// 0. `[writable]` The community
+/// 1. `[signer]` The community authority, or the treasury realm's governance authority
+/// 2. `[]` The new treasury realm, scoped to this community
+pub fn set_treasury_realm<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let SetTreasuryRealmContext { community, signer, realm } = SetTreasuryRealmContext::try_accounts(program_id, accounts)?;
+    
+    let mut community_data = Community::unpack(&community.data.borrow())?;
+    
+    // Verify signer
+    let expected_signer = match community_data.treasury_realm {
+        None => community_data.authority,
+        Some(treasury_realm) => find_governance_authority(program_id, &treasury_realm).0,
+    };
+    if *signer.key != expected_signer {
+        return Err(ProgramError::IllegalOwner);
+    }
+    
+    // Verify the realm governs this community
+    let realm_data = Realm::unpack(&realm.data.borrow())?;
+    if realm_data.scope != RealmScope::Community(*community.key) {
+        return Err(UnityVaultError::TreasuryRealmMismatch.into());
+    }
+    
+    community_data.treasury_realm = Some(*realm.key);
+    community_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    community_data.pack(&mut community.data.borrow_mut())?;
     
     Ok(())
 }
 
+/// Pays `amount` of `asset` out of the community treasury. Only the
+/// governance authority of the community's treasury realm can sign, so this
+/// runs as a transaction of an executed proposal.
+///
+/// 0. `[signer]` The treasury realm's governance authority PDA
+/// 1. `[]` The treasury realm
+/// 2. `[]` The community
+/// 3. `[writable]` The community treasury PDA (read-only for tokens)
+///
+/// For `TreasuryAsset::Sol`:
+/// 4. `[writable]` The destination
+/// 5. `[]` System program
+///
+/// For `TreasuryAsset::Token`:
+/// 4. `[writable]` The treasury's token account
+/// 5. `[writable]` The destination token account
+/// 6. `[]` Token program
+pub fn treasury_transfer<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    asset: TreasuryAsset,
+    amount: u64,
+) -> ProgramResult {
+    let CommunityTreasuryTransferContext { realm, community, treasury, remaining, .. } =
+        CommunityTreasuryTransferContext::try_accounts(program_id, accounts)?;
+    
+    // Verify the realm governs the treasury
+    let community_data = Community::unpack(&community.data.borrow())?;
+    if community_data.treasury_realm != Some(*realm.key) {
+        return Err(UnityVaultError::TreasuryRealmMismatch.into());
+    }
+    
+    let (treasury_key, treasury_bump) = find_community_treasury(program_id, community.key);
+    let treasury_seeds: &[&[u8]] = &[COMMUNITY_TREASURY_SEED, community.key.as_ref(), &[treasury_bump]];
+    
+    match asset {
+        TreasuryAsset::Sol => {
+            let TreasurySolAccounts { destination, system_program } = TreasurySolAccounts::try_accounts(program_id, remaining)?;
+            
+            invoke_signed(
+                &system_instruction::transfer(&treasury_key, destination.key, amount),
+                &[treasury.clone(), destination.clone(), system_program.clone()],
+                &[treasury_seeds],
+            )?;
+        }
+        TreasuryAsset::Token { mint } => {
+            let TreasuryTokenAccounts { treasury_token_account, destination_token_account, token_program } =
+                TreasuryTokenAccounts::try_accounts(program_id, remaining)?;
+            
+            // Verify the source holds the requested mint for the treasury
+            let source = spl_token::state::Account::unpack(&treasury_token_account.data.borrow())?;
+            if source.owner != treasury_key || source.mint != mint {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            
+            invoke_signed(
+                &token_instruction::transfer(
+                    token_program.key,
+                    treasury_token_account.key,
+                    destination_token_account.key,
+                    &treasury_key,
+                    &[],
+                    amount,
+                )?,
+                &[
+                    treasury_token_account.clone(),
+                    destination_token_account.clone(),
+                    treasury.clone(),
+                    token_program.clone(),
+                ],
+                &[treasury_seeds],
+            )?;
+        }
+    }
+    
+    Ok(())
+}
+
+/// Whether `signer` moderates a community as a platform moderator rather
+/// than as its authority. Anyone else is rejected.
+fn moderating_as_platform(
+    program_id: &Pubkey,
+    community_data: &Community,
+    user_profile: Option<&AccountInfo>,
+    signer: &Pubkey,
+) -> Result<bool, ProgramError> {
+    if community_data.authority == *signer {
+        return Ok(false);
+    }
+    let user_profile = user_profile.ok_or(UnityVaultError::NotAPlatformModerator)?;
+    if user_profile.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let user_profile_data = UserProfile::unpack(&user_profile.data.borrow())?;
+    if user_profile_data.authority != *signer || !user_profile_data.is_platform_moderator() {
+        return Err(UnityVaultError::NotAPlatformModerator.into());
+    }
+    Ok(true)
+}
+
+/// Role `signer` acts with in a community: admin for the community
+/// authority, otherwise the role of their membership in good standing.
+fn acting_role(
+    program_id: &Pubkey,
+    community: &Pubkey,
+    community_data: &Community,
+    membership: &AccountInfo,
+    signer: &Pubkey,
+) -> Result<CommunityRole, ProgramError> {
+    if community_data.authority == *signer {
+        return Ok(CommunityRole::Admin);
+    }
+    let membership_data = load_membership(program_id, community, membership, signer)?;
+    if !membership_data.is_in_good_standing(community_data, Clock::get()?.unix_timestamp) {
+        return Err(UnityVaultError::NotACommunityMember.into());
+    }
+    Ok(membership_data.role)
+}
+
+/// Loads the membership of `member` in `community`, checking its address.
+fn load_membership(
+    program_id: &Pubkey,
+    community: &Pubkey,
+    membership: &AccountInfo,
+    member: &Pubkey,
+) -> Result<Membership, ProgramError> {
+    let (membership_key, _) = Membership::find_address(program_id, community, member);
+    if membership.key != &membership_key || membership.owner != program_id {
+        return Err(UnityVaultError::NotACommunityMember.into());
+    }
+    Membership::unpack(&membership.data.borrow())
+}
+
+#[allow(clippy::too_many_arguments)]
+fn create_membership<'a>(
+    program_id: &Pubkey,
+    community: &Pubkey,
+    membership: &AccountInfo<'a>,
+    member: &Pubkey,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    role: CommunityRole,
+    status: MembershipStatus,
+) -> ProgramResult {
+    // Verify system program
+    if system_program.key != &solana_program::system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    
+    // Verify membership address
+    let (membership_key, bump) = Membership::find_address(program_id, community, member);
+    if membership.key != &membership_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    create_pda_account(
+        program_id,
+        payer,
+        membership,
+        system_program,
+        Membership::LEN,
+        &[MEMBERSHIP_SEED, community.as_ref(), member.as_ref(), &[bump]],
+    )?;
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    let membership_data = Membership {
+        is_initialized: true,
+        community: *community,
+        member: *member,
+        role,
+        status,
+        bump,
+        dues_paid_until: 0,
+        joined_at: current_time,
+        updated_at: current_time,
+    };
+    
+    membership_data.pack(&mut membership.data.borrow_mut())
+}
+
+/// Checks `vault` is the escrow of `project`.
+fn check_project_vault(
+    program_id: &Pubkey,
+    project: &Pubkey,
+    project_data: &CommunityProject,
+    vault: &AccountInfo,
+) -> ProgramResult {
+    let vault_key = Pubkey::create_program_address(
+        &[PROJECT_VAULT_SEED, project.as_ref(), &[project_data.vault_bump]],
+        program_id,
+    )
+    .map_err(|_| ProgramError::InvalidSeeds)?;
+    if vault.key != &vault_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    Ok(())
+}
+
+/// Loads the contribution of `contributor` to `project`.
+fn load_contribution(
+    project: &Pubkey,
+    contribution: &AccountInfo,
+    contributor: &Pubkey,
+) -> Result<ProjectContribution, ProgramError> {
+    let contribution_data = ProjectContribution::unpack(&contribution.data.borrow())?;
+    if contribution_data.project != *project || contribution_data.contributor != *contributor {
+        return Err(ProgramError::IllegalOwner);
+    }
+    Ok(contribution_data)
+}
+
+/// Pays `amount` out of a project's vault, which signs for itself.
+fn release_from_vault<'a>(
+    project: &Pubkey,
+    project_data: &CommunityProject,
+    vault: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    amount: u64,
+) -> ProgramResult {
+    invoke_signed(
+        &token_instruction::transfer(token_program.key, vault.key, destination.key, vault.key, &[], amount)?,
+        &[vault.clone(), destination.clone(), token_program.clone()],
+        &[&[PROJECT_VAULT_SEED, project.as_ref(), &[project_data.vault_bump]]],
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -169,10 +1221,19 @@ mod tests {
             &mut system_program_data
         );
         
+        // Create membership account
+        let mut membership_data = vec![];
+        let membership_account = create_test_account(
+            0,
+            &system_program_id,
+            &mut membership_data
+        );
+        
         let accounts = vec![
             community_account.clone(),
             authority_account,
             system_program_account,
+            membership_account,
         ];
         
         let params = CommunityParams {
@@ -271,4 +1332,41 @@ mod tests {
         let community = Community::unpack(&community_data.borrow()).unwrap();
         assert_eq!(community.status, CommunityStatus::Suspended);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_member_management_waits_for_reinstatement() {
+        use crate::community::state::tests::test_community;
+        
+        let program_id = Pubkey::new_unique();
+        let mut community = test_community();
+        community.status = CommunityStatus::Suspended;
+        let mut community_data = vec![0; Community::LEN];
+        community.pack(&mut community_data).unwrap();
+        let mut membership_data = vec![0; Membership::LEN];
+        
+        let community_key = Pubkey::new_unique();
+        let membership_key = Pubkey::new_unique();
+        let moderator_key = Pubkey::new_unique();
+        let mut community_lamports = 0;
+        let mut membership_lamports = 0;
+        let mut moderator_lamports = 0;
+        let mut moderator_data = vec![];
+        let membership = AccountInfo::new(&membership_key, false, true, &mut membership_lamports, &mut membership_data, &program_id, false, Epoch::default());
+        let accounts = vec![
+            AccountInfo::new(&community_key, false, true, &mut community_lamports, &mut community_data, &program_id, false, Epoch::default()),
+            membership.clone(),
+            AccountInfo::new(&moderator_key, true, false, &mut moderator_lamports, &mut moderator_data, &program_id, false, Epoch::default()),
+            membership.clone(),
+            membership,
+        ];
+        
+        assert_eq!(
+            kick_member(&program_id, &accounts),
+            Err(UnityVaultError::CommunityNotActive.into())
+        );
+        assert_eq!(
+            lapse_membership(&program_id, &accounts[..2]),
+            Err(UnityVaultError::CommunityNotActive.into())
+        );
+    }
+}
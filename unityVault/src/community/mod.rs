@@ -1,3 +1,4 @@
 pub mod state;
 pub mod instructions;
-pub mod context; 
\ No newline at end of file
+pub mod context;
+pub mod treasury;
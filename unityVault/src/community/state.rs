@@ -1,9 +1,25 @@
-use solana_program::{
-    program_error::ProgramError,
-    program_pack::{IsInitialized, Pack, Sealed},
-    pubkey::Pubkey,
-};
+use solana_program::pubkey::Pubkey;
 use borsh::{BorshSerialize, BorshDeserialize};
+use crate::account::{AccountState, AccountType, ACCOUNT_HEADER_LEN};
+use solana_program::program_error::ProgramError;
+use crate::error::UnityVaultError;
+use crate::migration::{decode_legacy, upgrade_or_decode, Migrate, LEGACY_LAYOUT_VERSION};
+use crate::validation::{check_str_len, check_vec_len};
+
+pub const MAX_COMMUNITY_NAME_LEN: usize = 100;
+pub const MAX_COMMUNITY_DESCRIPTION_LEN: usize = 500;
+pub const MAX_COMMUNITY_RULES_LEN: usize = 1000;
+pub const MEMBERSHIP_SEED: &[u8] = b"membership";
+pub const PROJECT_VAULT_SEED: &[u8] = b"project_vault";
+pub const CONTRIBUTION_SEED: &[u8] = b"contribution";
+pub const MAX_PROJECT_TITLE_LEN: usize = 100;
+pub const MAX_PROJECT_DESCRIPTION_LEN: usize = 500;
+pub const MAX_MILESTONE_TITLE_LEN: usize = 100;
+pub const MAX_PROJECT_MILESTONES: usize = 10;
+/// How long contributors have to vote on a submitted milestone.
+pub const MILESTONE_VOTING_PERIOD: i64 = 3 * 24 * 60 * 60;
+pub const MIN_DUES_PERIOD: i64 = 24 * 60 * 60;
+pub const MAX_DUES_PERIOD: i64 = 366 * 24 * 60 * 60;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
 pub enum CommunityRole {
@@ -12,10 +28,49 @@ pub enum CommunityRole {
     Member,
 }
 
+impl CommunityRole {
+    /// Whether the role can invite, admit and remove members.
+    pub fn can_manage_members(&self) -> bool {
+        matches!(self, CommunityRole::Admin | CommunityRole::Moderator)
+    }
+
+    /// Whether the role can remove members holding `other`: admins can
+    /// remove moderators and members, moderators only members.
+    pub fn outranks(&self, other: CommunityRole) -> bool {
+        let rank = |role: CommunityRole| match role {
+            CommunityRole::Admin => 2,
+            CommunityRole::Moderator => 1,
+            CommunityRole::Member => 0,
+        };
+        rank(*self) > rank(other)
+    }
+}
+
+/// Where a membership stands. Only `Active` members count toward
+/// `Community::member_count`, and only those in good standing on their
+/// dues take part in community governance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum MembershipStatus {
+    Active,
+    /// Invited by an admin or moderator, waiting for the member to join.
+    Invited,
+    /// Asked to join a private community, waiting for an admin or moderator.
+    Requested,
+    /// Removed by an admin or moderator and kept out until the ban is lifted.
+    Banned,
+    /// Dues went unpaid past the grace period; paying them restores the membership.
+    Lapsed,
+}
+
+/// Where a community stands. Only `Active` communities take new members,
+/// role changes, kicks, bans or governance actions; members can still leave,
+/// and banned ones too once the community is archived.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
 pub enum CommunityStatus {
     Active,
+    /// Suspended by its authority or a platform moderator, until reinstated.
     Suspended,
+    /// Closed for good; an archived community can't be reinstated.
     Archived,
 }
 
@@ -27,6 +82,67 @@ pub struct CommunityParams {
     pub is_private: bool,
 }
 
+impl CommunityParams {
+    pub fn validate(&self) -> Result<(), UnityVaultError> {
+        check_str_len("name", &self.name, MAX_COMMUNITY_NAME_LEN)?;
+        check_str_len("description", &self.description, MAX_COMMUNITY_DESCRIPTION_LEN)?;
+        check_str_len("rules", &self.rules, MAX_COMMUNITY_RULES_LEN)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct MembershipDuesParams {
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub period: i64,
+    pub grace_period: i64,
+}
+
+impl MembershipDuesParams {
+    pub fn validate(&self) -> Result<(), UnityVaultError> {
+        if self.amount == 0
+            || !(MIN_DUES_PERIOD..=MAX_DUES_PERIOD).contains(&self.period)
+            || !(0..=MAX_DUES_PERIOD).contains(&self.grace_period)
+        {
+            return Err(UnityVaultError::InvalidDuesConfig);
+        }
+        Ok(())
+    }
+}
+
+/// Recurring dues a community charges, paid into its treasury's token
+/// account for `mint`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct MembershipDues {
+    pub mint: Pubkey,
+    /// Charged for each `period` of membership.
+    pub amount: u64,
+    pub period: i64,
+    /// How long a membership stays in good standing after its paid time runs out.
+    pub grace_period: i64,
+    /// When these dues took effect. Nobody owes for the time before.
+    pub started_at: i64,
+}
+
+impl MembershipDues {
+    pub const LEN: usize = 32 + // mint
+        8 + // amount
+        8 + // period
+        8 + // grace_period
+        8; // started_at
+
+    pub fn new(params: MembershipDuesParams, now: i64) -> Self {
+        MembershipDues {
+            mint: params.mint,
+            amount: params.amount,
+            period: params.period,
+            grace_period: params.grace_period,
+            started_at: now,
+        }
+    }
+}
+
 #[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
 pub struct Community {
     pub is_initialized: bool,
@@ -37,151 +153,1424 @@ pub struct Community {
     pub is_private: bool,
     pub status: CommunityStatus,
     pub member_count: u32,
+    /// Active members holding `CommunityRole::Admin`. Never drops from one to zero.
+    pub admin_count: u32,
+    /// Proposed new authority, which takes over once it accepts.
+    pub pending_authority: Option<Pubkey>,
+    /// Whether a platform moderator suspended the community, in which case
+    /// only a platform moderator can reinstate it.
+    pub suspended_by_platform: bool,
+    /// Dues members pay to stay in good standing; none when membership is free.
+    pub dues: Option<MembershipDues>,
+    /// Realm whose governance authority spends the community treasury.
+    pub treasury_realm: Option<Pubkey>,
     pub created_at: i64,
     pub updated_at: i64,
 }
 
 impl Community {
-    pub const LEN: usize = 1 + // is_initialized
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + // account type and version
+        1 + // is_initialized
         32 + // authority
-        4 + 100 + // name (max 100 chars)
-        4 + 500 + // description (max 500 chars)
-        4 + 1000 + // rules (max 1000 chars)
+        4 + MAX_COMMUNITY_NAME_LEN + // name
+        4 + MAX_COMMUNITY_DESCRIPTION_LEN + // description
+        4 + MAX_COMMUNITY_RULES_LEN + // rules
         1 + // is_private
         1 + // status
         4 + // member_count
+        4 + // admin_count
+        1 + 32 + // pending_authority
+        1 + // suspended_by_platform
+        1 + MembershipDues::LEN + // dues
+        1 + 32 + // treasury_realm
+        8 + // created_at
+        8; // updated_at
+
+    pub fn check_active(&self) -> Result<(), UnityVaultError> {
+        if self.status != CommunityStatus::Active {
+            return Err(UnityVaultError::CommunityNotActive);
+        }
+        Ok(())
+    }
+
+    /// Suspends an active community, `by_platform` when a platform moderator
+    /// rather than the community authority does.
+    pub fn suspend(&mut self, by_platform: bool) -> Result<(), UnityVaultError> {
+        self.check_active()?;
+        self.status = CommunityStatus::Suspended;
+        self.suspended_by_platform = by_platform;
+        Ok(())
+    }
+
+    /// Lifts a suspension. The community authority can only lift its own.
+    pub fn reinstate(&mut self, by_platform: bool) -> Result<(), UnityVaultError> {
+        if self.status != CommunityStatus::Suspended {
+            return Err(UnityVaultError::CommunityNotSuspended);
+        }
+        if self.suspended_by_platform && !by_platform {
+            return Err(UnityVaultError::NotAPlatformModerator);
+        }
+        self.status = CommunityStatus::Active;
+        self.suspended_by_platform = false;
+        Ok(())
+    }
+
+    /// Archives an active or suspended community for good.
+    pub fn archive(&mut self) -> Result<(), UnityVaultError> {
+        if self.status == CommunityStatus::Archived {
+            return Err(UnityVaultError::CommunityNotActive);
+        }
+        self.status = CommunityStatus::Archived;
+        self.suspended_by_platform = false;
+        Ok(())
+    }
+
+    /// Counts a newly active member holding `role`.
+    pub fn add_member(&mut self, role: CommunityRole) -> Result<(), ProgramError> {
+        self.member_count = self.member_count.checked_add(1).ok_or(ProgramError::ArithmeticOverflow)?;
+        if role == CommunityRole::Admin {
+            self.admin_count = self.admin_count.checked_add(1).ok_or(ProgramError::ArithmeticOverflow)?;
+        }
+        Ok(())
+    }
+
+    /// Stops counting an active member holding `role`. Fails for the last
+    /// admin unless the community is archived.
+    pub fn remove_member(&mut self, role: CommunityRole) -> Result<(), ProgramError> {
+        if role == CommunityRole::Admin {
+            self.remove_admin()?;
+        }
+        self.member_count = self.member_count.checked_sub(1).ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Updates the admin count for an active member moving from `from` to `to`.
+    pub fn change_role(&mut self, from: CommunityRole, to: CommunityRole) -> Result<(), ProgramError> {
+        match (from == CommunityRole::Admin, to == CommunityRole::Admin) {
+            (true, false) => self.remove_admin(),
+            (false, true) => {
+                self.admin_count = self.admin_count.checked_add(1).ok_or(ProgramError::ArithmeticOverflow)?;
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn remove_admin(&mut self) -> Result<(), ProgramError> {
+        // An archived community has nothing left to administer
+        if self.admin_count <= 1 && self.status != CommunityStatus::Archived {
+            return Err(UnityVaultError::LastCommunityAdmin.into());
+        }
+        self.admin_count = self.admin_count.checked_sub(1).ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }
+}
+
+impl AccountState for Community {
+    const ACCOUNT_TYPE: AccountType = AccountType::Community;
+    const VERSION: u8 = 4;
+    const LEN: usize = Self::LEN;
+
+    fn validate_bounds(&self) -> Result<(), UnityVaultError> {
+        check_str_len("name", &self.name, MAX_COMMUNITY_NAME_LEN)?;
+        check_str_len("description", &self.description, MAX_COMMUNITY_DESCRIPTION_LEN)?;
+        check_str_len("rules", &self.rules, MAX_COMMUNITY_RULES_LEN)?;
+        Ok(())
+    }
+}
+
+/// `Community` layout version 1, and the legacy layout it matches field for
+/// field: no admin count or pending authority.
+#[derive(BorshDeserialize)]
+struct CommunityV1 {
+    is_initialized: bool,
+    authority: Pubkey,
+    name: String,
+    description: String,
+    rules: String,
+    is_private: bool,
+    status: CommunityStatus,
+    member_count: u32,
+    created_at: i64,
+    updated_at: i64,
+}
+
+/// `Community` layout version 2: no record of who suspended it.
+#[derive(BorshDeserialize)]
+struct CommunityV2 {
+    is_initialized: bool,
+    authority: Pubkey,
+    name: String,
+    description: String,
+    rules: String,
+    is_private: bool,
+    status: CommunityStatus,
+    member_count: u32,
+    admin_count: u32,
+    pending_authority: Option<Pubkey>,
+    created_at: i64,
+    updated_at: i64,
+}
+
+/// `Community` layout version 3: no dues or treasury realm.
+#[derive(BorshDeserialize)]
+struct CommunityV3 {
+    is_initialized: bool,
+    authority: Pubkey,
+    name: String,
+    description: String,
+    rules: String,
+    is_private: bool,
+    status: CommunityStatus,
+    member_count: u32,
+    admin_count: u32,
+    pending_authority: Option<Pubkey>,
+    suspended_by_platform: bool,
+    created_at: i64,
+    updated_at: i64,
+}
+
+impl From<CommunityV1> for CommunityV2 {
+    fn from(v1: CommunityV1) -> Self {
+        // Older communities have no membership accounts, so no counted admins;
+        // their authority still manages them
+        CommunityV2 {
+            is_initialized: v1.is_initialized,
+            authority: v1.authority,
+            name: v1.name,
+            description: v1.description,
+            rules: v1.rules,
+            is_private: v1.is_private,
+            status: v1.status,
+            member_count: v1.member_count,
+            admin_count: 0,
+            pending_authority: None,
+            created_at: v1.created_at,
+            updated_at: v1.updated_at,
+        }
+    }
+}
+
+impl From<CommunityV2> for CommunityV3 {
+    fn from(v2: CommunityV2) -> Self {
+        // Until now only the authority could suspend its community
+        CommunityV3 {
+            is_initialized: v2.is_initialized,
+            authority: v2.authority,
+            name: v2.name,
+            description: v2.description,
+            rules: v2.rules,
+            is_private: v2.is_private,
+            status: v2.status,
+            member_count: v2.member_count,
+            admin_count: v2.admin_count,
+            pending_authority: v2.pending_authority,
+            suspended_by_platform: false,
+            created_at: v2.created_at,
+            updated_at: v2.updated_at,
+        }
+    }
+}
+
+impl From<CommunityV3> for Community {
+    fn from(v3: CommunityV3) -> Self {
+        Community {
+            is_initialized: v3.is_initialized,
+            authority: v3.authority,
+            name: v3.name,
+            description: v3.description,
+            rules: v3.rules,
+            is_private: v3.is_private,
+            status: v3.status,
+            member_count: v3.member_count,
+            admin_count: v3.admin_count,
+            pending_authority: v3.pending_authority,
+            suspended_by_platform: v3.suspended_by_platform,
+            dues: None,
+            treasury_realm: None,
+            created_at: v3.created_at,
+            updated_at: v3.updated_at,
+        }
+    }
+}
+
+impl Migrate for Community {
+    const LEGACY_LEN: Option<usize> = Some(1667);
+
+    fn decode_version(version: u8, src: &[u8]) -> Result<Self, ProgramError> {
+        let legacy: Option<CommunityV1> = if version == LEGACY_LAYOUT_VERSION {
+            Some(decode_legacy(src)?)
+        } else {
+            None
+        };
+        let v1: Option<CommunityV1> = upgrade_or_decode(legacy, version, 1, src)?;
+        let v2: Option<CommunityV2> = upgrade_or_decode(v1, version, 2, src)?;
+        let v3: Option<CommunityV3> = upgrade_or_decode(v2, version, 3, src)?;
+        v3.map(Community::from).ok_or_else(|| UnityVaultError::UnsupportedAccountVersion.into())
+    }
+
+    fn migration_authority(&self) -> Pubkey {
+        self.authority
+    }
+}
+
+/// A member's place in a community, stored at the PDA
+/// `[MEMBERSHIP_SEED, community, member]`. Community-scoped governance
+/// checks it before letting the member propose or vote.
+///
+/// Public communities admit members as soon as they join. In private ones a
+/// membership starts as a request or an invite, and becomes active once the
+/// other side accepts.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct Membership {
+    pub is_initialized: bool,
+    pub community: Pubkey,
+    pub member: Pubkey,
+    pub role: CommunityRole,
+    pub status: MembershipStatus,
+    pub bump: u8,
+    /// End of the time paid for in a community charging dues.
+    pub dues_paid_until: i64,
+    pub joined_at: i64,
+    pub updated_at: i64,
+}
+
+impl Membership {
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + // account type and version
+        1 + // is_initialized
+        32 + // community
+        32 + // member
+        1 + // role
+        1 + // status
+        1 + // bump
+        8 + // dues_paid_until
+        8 + // joined_at
+        8; // updated_at
+
+    pub fn find_address(program_id: &Pubkey, community: &Pubkey, member: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[MEMBERSHIP_SEED, community.as_ref(), member.as_ref()], program_id)
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.status == MembershipStatus::Active
+    }
+
+    /// Status of a new membership when `member` joins on their own.
+    pub fn join_status(community: &Community) -> MembershipStatus {
+        if community.is_private {
+            MembershipStatus::Requested
+        } else {
+            MembershipStatus::Active
+        }
+    }
+
+    /// The member joins an existing membership, accepting an invite.
+    pub fn accept_invite(&mut self) -> Result<(), ProgramError> {
+        self.check_not_banned()?;
+        if self.status != MembershipStatus::Invited {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        self.status = MembershipStatus::Active;
+        Ok(())
+    }
+
+    /// The member joins an existing membership: accepting an invite, or
+    /// coming back to a lapsed one once the community stops charging dues.
+    pub fn rejoin(&mut self, community: &Community) -> Result<(), ProgramError> {
+        if self.status == MembershipStatus::Lapsed && community.dues.is_none() {
+            self.status = MembershipStatus::Active;
+            return Ok(());
+        }
+        self.accept_invite()
+    }
+
+    /// An admin or moderator invites an existing membership, approving a request.
+    pub fn approve_request(&mut self) -> Result<(), ProgramError> {
+        self.check_not_banned()?;
+        if self.status != MembershipStatus::Requested {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        self.status = MembershipStatus::Active;
+        Ok(())
+    }
+
+    pub fn check_not_banned(&self) -> Result<(), UnityVaultError> {
+        if self.status == MembershipStatus::Banned {
+            return Err(UnityVaultError::MemberBanned);
+        }
+        Ok(())
+    }
+
+    /// Whether the membership is active and paid up, or still within the
+    /// grace period, in `community`.
+    pub fn is_in_good_standing(&self, community: &Community, now: i64) -> bool {
+        self.is_active() && !self.dues_overdue(community, now)
+    }
+
+    /// Whether the dues of `community` have gone unpaid past the grace period.
+    pub fn dues_overdue(&self, community: &Community, now: i64) -> bool {
+        community
+            .dues
+            .is_some_and(|dues| now > self.dues_owed_from(&dues).saturating_add(dues.grace_period))
+    }
+
+    /// Whether the member has had time in `community` it hasn't paid dues
+    /// for yet, including time still within the grace period.
+    pub fn owes_dues(&self, community: &Community, now: i64) -> bool {
+        matches!(self.status, MembershipStatus::Active | MembershipStatus::Lapsed)
+            && community.dues.is_some_and(|dues| self.dues_owed_from(&dues) < now)
+    }
+
+    /// Checks that the member can leave `community`. Banned members can't,
+    /// which would lift their ban, and neither can members owing dues, who
+    /// would come back with a new membership and a fresh grace period. Once
+    /// the community is archived nobody can join again, so anyone can leave
+    /// and get their rent back.
+    pub fn check_can_leave(&self, community: &Community, now: i64) -> Result<(), UnityVaultError> {
+        if community.status == CommunityStatus::Archived {
+            return Ok(());
+        }
+        self.check_not_banned()?;
+        if self.owes_dues(community, now) {
+            return Err(UnityVaultError::DuesUnpaid);
+        }
+        Ok(())
+    }
+
+    /// Start of the time not yet paid for: members owe from when they joined
+    /// or the dues took effect, whichever is later.
+    fn dues_owed_from(&self, dues: &MembershipDues) -> i64 {
+        self.dues_paid_until.max(self.joined_at).max(dues.started_at)
+    }
+
+    /// Pays `periods` periods of dues and returns the amount owed for them.
+    /// An overdue membership pays from now rather than for the time it
+    /// missed, and a lapsed one becomes active again.
+    pub fn pay_dues(&mut self, community: &Community, periods: u32, now: i64) -> Result<u64, ProgramError> {
+        let dues = community.dues.ok_or(UnityVaultError::DuesNotCharged)?;
+        if periods == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if !matches!(self.status, MembershipStatus::Active | MembershipStatus::Lapsed) {
+            return Err(UnityVaultError::NotACommunityMember.into());
+        }
+        let amount = dues.amount.checked_mul(periods as u64).ok_or(ProgramError::ArithmeticOverflow)?;
+        let paid_from = if self.dues_overdue(community, now) { now } else { self.dues_owed_from(&dues) };
+        let paid_time = dues.period.checked_mul(periods as i64).ok_or(ProgramError::ArithmeticOverflow)?;
+        self.dues_paid_until = paid_from.checked_add(paid_time).ok_or(ProgramError::ArithmeticOverflow)?;
+        self.status = MembershipStatus::Active;
+        Ok(amount)
+    }
+
+    /// Lapses an active membership whose dues are overdue.
+    pub fn lapse(&mut self, community: &Community, now: i64) -> Result<(), UnityVaultError> {
+        if !self.is_active() || !self.dues_overdue(community, now) {
+            return Err(UnityVaultError::DuesNotOverdue);
+        }
+        self.status = MembershipStatus::Lapsed;
+        Ok(())
+    }
+}
+
+impl AccountState for Membership {
+    const ACCOUNT_TYPE: AccountType = AccountType::Membership;
+    const VERSION: u8 = 3;
+    const LEN: usize = Self::LEN;
+}
+
+/// `Membership` layout version 1: every membership was active.
+#[derive(BorshDeserialize)]
+struct MembershipV1 {
+    is_initialized: bool,
+    community: Pubkey,
+    member: Pubkey,
+    role: CommunityRole,
+    bump: u8,
+    joined_at: i64,
+    updated_at: i64,
+}
+
+/// `Membership` layout version 2: no dues paid.
+#[derive(BorshDeserialize)]
+struct MembershipV2 {
+    is_initialized: bool,
+    community: Pubkey,
+    member: Pubkey,
+    role: CommunityRole,
+    status: MembershipStatus,
+    bump: u8,
+    joined_at: i64,
+    updated_at: i64,
+}
+
+impl From<MembershipV1> for MembershipV2 {
+    fn from(v1: MembershipV1) -> Self {
+        MembershipV2 {
+            is_initialized: v1.is_initialized,
+            community: v1.community,
+            member: v1.member,
+            role: v1.role,
+            status: MembershipStatus::Active,
+            bump: v1.bump,
+            joined_at: v1.joined_at,
+            updated_at: v1.updated_at,
+        }
+    }
+}
+
+impl From<MembershipV2> for Membership {
+    fn from(v2: MembershipV2) -> Self {
+        Membership {
+            is_initialized: v2.is_initialized,
+            community: v2.community,
+            member: v2.member,
+            role: v2.role,
+            status: v2.status,
+            bump: v2.bump,
+            dues_paid_until: 0,
+            joined_at: v2.joined_at,
+            updated_at: v2.updated_at,
+        }
+    }
+}
+
+impl Migrate for Membership {
+    fn decode_version(version: u8, src: &[u8]) -> Result<Self, ProgramError> {
+        let v1: Option<MembershipV1> = upgrade_or_decode(None::<MembershipV1>, version, 1, src)?;
+        let v2: Option<MembershipV2> = upgrade_or_decode(v1, version, 2, src)?;
+        v2.map(Membership::from).ok_or_else(|| UnityVaultError::UnsupportedAccountVersion.into())
+    }
+
+    fn migration_authority(&self) -> Pubkey {
+        self.member
+    }
+}
+
+/// Where a community project stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum ProjectStatus {
+    /// Taking contributions until the goal is met or the funding deadline passes.
+    Funding,
+    /// Fully funded; milestones are being delivered and released.
+    Funded,
+    /// Every milestone has been released to the owner.
+    Completed,
+    /// Cancelled by its owner or the community authority; contributors can
+    /// claim back whatever has not been released.
+    Cancelled,
+    /// Missed its funding deadline or a milestone deadline; refundable like
+    /// a cancelled project.
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum MilestoneStatus {
+    Pending,
+    /// Submitted by the owner, with contributors voting on its release.
+    Voting,
+    Released,
+}
+
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct ProjectMilestoneParams {
+    pub title: String,
+    /// Tranche released to the owner when contributors approve the milestone.
+    pub amount: u64,
+    pub deadline: i64,
+}
+
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct ProjectParams {
+    pub title: String,
+    pub description: String,
+    pub funding_deadline: i64,
+    /// Milestones in delivery order. Their amounts add up to the funding goal.
+    pub milestones: Vec<ProjectMilestoneParams>,
+}
+
+impl ProjectParams {
+    /// Checks the layout budgets, and that each milestone is due after the
+    /// funding deadline and the one before it.
+    pub fn validate(&self, now: i64) -> Result<(), UnityVaultError> {
+        check_str_len("title", &self.title, MAX_PROJECT_TITLE_LEN)?;
+        check_str_len("description", &self.description, MAX_PROJECT_DESCRIPTION_LEN)?;
+        check_vec_len("milestones", &self.milestones, MAX_PROJECT_MILESTONES)?;
+        for milestone in &self.milestones {
+            check_str_len("milestone title", &milestone.title, MAX_MILESTONE_TITLE_LEN)?;
+        }
+        if self.milestones.is_empty() || self.funding_deadline <= now {
+            return Err(UnityVaultError::InvalidProjectParams);
+        }
+        let mut due_after = self.funding_deadline;
+        for milestone in &self.milestones {
+            if milestone.amount == 0 || milestone.deadline <= due_after {
+                return Err(UnityVaultError::InvalidProjectParams);
+            }
+            due_after = milestone.deadline;
+        }
+        self.funding_goal().map(|_| ())
+    }
+
+    pub fn funding_goal(&self) -> Result<u64, UnityVaultError> {
+        self.milestones
+            .iter()
+            .try_fold(0u64, |total, milestone| total.checked_add(milestone.amount))
+            .ok_or(UnityVaultError::InvalidProjectParams)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct ProjectMilestone {
+    pub title: String,
+    pub amount: u64,
+    pub deadline: i64,
+    pub status: MilestoneStatus,
+    /// Contributed amounts voting for and against the current submission.
+    pub approve_weight: u64,
+    pub reject_weight: u64,
+    pub voting_ends_at: i64,
+}
+
+impl ProjectMilestone {
+    pub const LEN: usize = 4 + MAX_MILESTONE_TITLE_LEN + // title
+        8 + // amount
+        8 + // deadline
+        1 + // status
+        8 + // approve_weight
+        8 + // reject_weight
+        8; // voting_ends_at
+}
+
+impl From<ProjectMilestoneParams> for ProjectMilestone {
+    fn from(params: ProjectMilestoneParams) -> Self {
+        ProjectMilestone {
+            title: params.title,
+            amount: params.amount,
+            deadline: params.deadline,
+            status: MilestoneStatus::Pending,
+            approve_weight: 0,
+            reject_weight: 0,
+            voting_ends_at: 0,
+        }
+    }
+}
+
+/// A crowdfunded project run by a community member.
+///
+/// Contributions are escrowed in the project's vault, a token account at the
+/// PDA `[PROJECT_VAULT_SEED, project]` that is its own authority. Once the
+/// goal is met the owner submits milestones one at a time, and each
+/// milestone's tranche is released when contributors holding a majority of
+/// the funding approve it. If the project is cancelled or misses a deadline,
+/// contributors claim back their share of what is still in escrow.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct CommunityProject {
+    pub is_initialized: bool,
+    pub community: Pubkey,
+    pub owner: Pubkey,
+    pub title: String,
+    pub description: String,
+    pub mint: Pubkey,
+    pub funding_goal: u64,
+    pub funding_deadline: i64,
+    pub total_contributed: u64,
+    pub total_released: u64,
+    pub contributor_count: u32,
+    pub status: ProjectStatus,
+    pub milestones: Vec<ProjectMilestone>,
+    /// Index of the next milestone to release.
+    pub current_milestone: u8,
+    /// Bumped on every milestone submission so contributors can vote again.
+    pub vote_round: u16,
+    pub vault_bump: u8,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl CommunityProject {
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + // account type and version
+        1 + // is_initialized
+        32 + // community
+        32 + // owner
+        4 + MAX_PROJECT_TITLE_LEN + // title
+        4 + MAX_PROJECT_DESCRIPTION_LEN + // description
+        32 + // mint
+        8 + // funding_goal
+        8 + // funding_deadline
+        8 + // total_contributed
+        8 + // total_released
+        4 + // contributor_count
+        1 + // status
+        4 + MAX_PROJECT_MILESTONES * ProjectMilestone::LEN + // milestones
+        1 + // current_milestone
+        2 + // vote_round
+        1 + // vault_bump
         8 + // created_at
         8; // updated_at
+
+    pub fn find_vault(program_id: &Pubkey, project: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[PROJECT_VAULT_SEED, project.as_ref()], program_id)
+    }
+
+    /// Books a contribution. Reaching the goal closes funding. The owner
+    /// can't back their own project, since contributions are what vote on
+    /// releasing its milestones.
+    pub fn contribute(&mut self, contributor: &Pubkey, amount: u64, now: i64) -> Result<(), ProgramError> {
+        if *contributor == self.owner {
+            return Err(UnityVaultError::OwnerCannotContribute.into());
+        }
+        if self.status != ProjectStatus::Funding || now >= self.funding_deadline {
+            return Err(UnityVaultError::ProjectNotActive.into());
+        }
+        if amount == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let total = self.total_contributed.checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)?;
+        if total > self.funding_goal {
+            return Err(UnityVaultError::FundingGoalExceeded.into());
+        }
+        self.total_contributed = total;
+        if total == self.funding_goal {
+            self.status = ProjectStatus::Funded;
+        }
+        Ok(())
+    }
+
+    fn current_milestone_mut(&mut self) -> Result<&mut ProjectMilestone, ProgramError> {
+        if self.status != ProjectStatus::Funded {
+            return Err(UnityVaultError::ProjectNotActive.into());
+        }
+        self.milestones
+            .get_mut(self.current_milestone as usize)
+            .ok_or(ProgramError::InvalidAccountData)
+    }
+
+    /// Opens a vote on the current milestone. A rejected milestone can be
+    /// submitted again until its deadline.
+    pub fn submit_milestone(&mut self, now: i64) -> Result<(), ProgramError> {
+        let milestone = self.current_milestone_mut()?;
+        if milestone.status != MilestoneStatus::Pending {
+            return Err(UnityVaultError::ProjectNotActive.into());
+        }
+        if now > milestone.deadline {
+            return Err(UnityVaultError::VotingClosed.into());
+        }
+        milestone.status = MilestoneStatus::Voting;
+        milestone.approve_weight = 0;
+        milestone.reject_weight = 0;
+        milestone.voting_ends_at = now.checked_add(MILESTONE_VOTING_PERIOD).ok_or(ProgramError::ArithmeticOverflow)?;
+        self.vote_round = self.vote_round.checked_add(1).ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Casts `contribution`'s amount for or against the current milestone.
+    pub fn vote_milestone(&mut self, contribution: &mut ProjectContribution, approve: bool, now: i64) -> Result<(), ProgramError> {
+        let vote_round = self.vote_round;
+        let milestone = self.current_milestone_mut()?;
+        if milestone.status != MilestoneStatus::Voting || now >= milestone.voting_ends_at {
+            return Err(UnityVaultError::VotingClosed.into());
+        }
+        if contribution.amount == 0 {
+            return Err(UnityVaultError::NoVotingWeight.into());
+        }
+        if contribution.voted_round == vote_round {
+            return Err(UnityVaultError::AlreadyVoted.into());
+        }
+        let weight = if approve { &mut milestone.approve_weight } else { &mut milestone.reject_weight };
+        *weight = weight.checked_add(contribution.amount).ok_or(ProgramError::ArithmeticOverflow)?;
+        contribution.voted_round = vote_round;
+        Ok(())
+    }
+
+    /// Settles the vote on the current milestone, early once either side
+    /// holds a majority of the funding. Returns the tranche to release when
+    /// approved; a rejected milestone goes back to pending.
+    pub fn finalize_milestone(&mut self, now: i64) -> Result<Option<u64>, ProgramError> {
+        let total_contributed = self.total_contributed as u128;
+        let milestone = self.current_milestone_mut()?;
+        if milestone.status != MilestoneStatus::Voting {
+            return Err(UnityVaultError::ProjectNotActive.into());
+        }
+        let approved = milestone.approve_weight as u128 * 2 > total_contributed;
+        let rejected = milestone.reject_weight as u128 * 2 >= total_contributed;
+        if !approved && !rejected && now < milestone.voting_ends_at {
+            return Err(UnityVaultError::VotingStillOpen.into());
+        }
+        if !approved {
+            milestone.status = MilestoneStatus::Pending;
+            return Ok(None);
+        }
+
+        milestone.status = MilestoneStatus::Released;
+        let amount = milestone.amount;
+        self.total_released = self.total_released.checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)?;
+        self.current_milestone += 1;
+        if self.current_milestone as usize == self.milestones.len() {
+            self.status = ProjectStatus::Completed;
+        }
+        Ok(Some(amount))
+    }
+
+    pub fn cancel(&mut self) -> Result<(), UnityVaultError> {
+        if !matches!(self.status, ProjectStatus::Funding | ProjectStatus::Funded) {
+            return Err(UnityVaultError::ProjectNotActive);
+        }
+        self.status = ProjectStatus::Cancelled;
+        Ok(())
+    }
+
+    /// Checks contributors can claim refunds, failing the project first if
+    /// it missed its funding deadline or the current milestone's deadline.
+    pub fn check_refundable(&mut self, now: i64) -> Result<(), UnityVaultError> {
+        let missed_deadline = match self.status {
+            ProjectStatus::Cancelled | ProjectStatus::Failed => return Ok(()),
+            ProjectStatus::Funding => now >= self.funding_deadline,
+            // A milestone under vote settles first
+            ProjectStatus::Funded => self
+                .milestones
+                .get(self.current_milestone as usize)
+                .is_some_and(|milestone| milestone.status == MilestoneStatus::Pending && now > milestone.deadline),
+            ProjectStatus::Completed => false,
+        };
+        if !missed_deadline {
+            return Err(UnityVaultError::ProjectNotRefundable);
+        }
+        self.status = ProjectStatus::Failed;
+        Ok(())
+    }
+
+    /// `contribution`'s share of the funds still in escrow.
+    pub fn refund_amount(&self, contribution: &ProjectContribution) -> u64 {
+        if self.total_contributed == 0 {
+            return 0;
+        }
+        let unreleased = self.total_contributed.saturating_sub(self.total_released) as u128;
+        (contribution.amount as u128 * unreleased / self.total_contributed as u128) as u64
+    }
 }
 
-impl Sealed for Community {}
+impl AccountState for CommunityProject {
+    const ACCOUNT_TYPE: AccountType = AccountType::CommunityProject;
+    const VERSION: u8 = 1;
+    const LEN: usize = Self::LEN;
 
-impl IsInitialized for Community {
-    fn is_initialized(&self) -> bool {
-        self.is_initialized
+    fn validate_bounds(&self) -> Result<(), UnityVaultError> {
+        check_str_len("title", &self.title, MAX_PROJECT_TITLE_LEN)?;
+        check_str_len("description", &self.description, MAX_PROJECT_DESCRIPTION_LEN)?;
+        check_vec_len("milestones", &self.milestones, MAX_PROJECT_MILESTONES)?;
+        for milestone in &self.milestones {
+            check_str_len("milestone title", &milestone.title, MAX_MILESTONE_TITLE_LEN)?;
+        }
+        Ok(())
     }
 }
 
-impl Pack for Community {
+/// What one contributor has put into a project, stored at the PDA
+/// `[CONTRIBUTION_SEED, project, contributor]`. The amount is also their
+/// weight in milestone votes.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct ProjectContribution {
+    pub is_initialized: bool,
+    pub project: Pubkey,
+    pub contributor: Pubkey,
+    pub amount: u64,
+    /// The project's `vote_round` this contributor last voted in.
+    pub voted_round: u16,
+    pub refunded: bool,
+    pub bump: u8,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl ProjectContribution {
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + // account type and version
+        1 + // is_initialized
+        32 + // project
+        32 + // contributor
+        8 + // amount
+        2 + // voted_round
+        1 + // refunded
+        1 + // bump
+        8 + // created_at
+        8; // updated_at
+
+    pub fn find_address(program_id: &Pubkey, project: &Pubkey, contributor: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[CONTRIBUTION_SEED, project.as_ref(), contributor.as_ref()], program_id)
+    }
+
+    pub fn add(&mut self, amount: u64) -> Result<(), ProgramError> {
+        self.amount = self.amount.checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    /// Marks the contribution refunded, once.
+    pub fn mark_refunded(&mut self) -> Result<(), UnityVaultError> {
+        if self.refunded || self.amount == 0 {
+            return Err(UnityVaultError::NothingToRefund);
+        }
+        self.refunded = true;
+        Ok(())
+    }
+}
+
+impl AccountState for ProjectContribution {
+    const ACCOUNT_TYPE: AccountType = AccountType::ProjectContribution;
+    const VERSION: u8 = 1;
     const LEN: usize = Self::LEN;
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::account::tests::assert_max_len_fits;
+
+    fn community_params(name_len: usize, description_len: usize, rules_len: usize) -> CommunityParams {
+        CommunityParams {
+            name: "n".repeat(name_len),
+            description: "d".repeat(description_len),
+            rules: "r".repeat(rules_len),
+            is_private: false,
+        }
+    }
+
+    #[test]
+    fn test_community_params_length_boundaries() {
+        assert_eq!(
+            community_params(MAX_COMMUNITY_NAME_LEN, MAX_COMMUNITY_DESCRIPTION_LEN, MAX_COMMUNITY_RULES_LEN).validate(),
+            Ok(())
+        );
+        assert_eq!(
+            community_params(MAX_COMMUNITY_NAME_LEN + 1, 0, 0).validate(),
+            Err(UnityVaultError::StringTooLong)
+        );
+        assert_eq!(
+            community_params(0, MAX_COMMUNITY_DESCRIPTION_LEN + 1, 0).validate(),
+            Err(UnityVaultError::StringTooLong)
+        );
+        assert_eq!(
+            community_params(0, 0, MAX_COMMUNITY_RULES_LEN + 1).validate(),
+            Err(UnityVaultError::StringTooLong)
+        );
+    }
+
+    #[test]
+    fn test_pack_max_length_fields_fits_len() {
+        let params = community_params(MAX_COMMUNITY_NAME_LEN, MAX_COMMUNITY_DESCRIPTION_LEN, MAX_COMMUNITY_RULES_LEN);
+        let community = Community {
+            is_initialized: true,
+            authority: Pubkey::new_unique(),
+            name: params.name.clone(),
+            description: params.description.clone(),
+            rules: params.rules.clone(),
+            is_private: params.is_private,
+            status: CommunityStatus::Active,
+            member_count: 1,
+            admin_count: 1,
+            pending_authority: Some(Pubkey::new_unique()),
+            suspended_by_platform: true,
+            dues: Some(MembershipDues {
+                mint: Pubkey::new_unique(),
+                amount: 1,
+                period: MIN_DUES_PERIOD,
+                grace_period: 0,
+                started_at: 0,
+            }),
+            treasury_realm: Some(Pubkey::new_unique()),
+            created_at: 0,
+            updated_at: 0,
+        };
+
+        assert_max_len_fits(&community);
+    }
+
+    pub(crate) fn test_community() -> Community {
+        Community {
+            is_initialized: true,
+            authority: Pubkey::new_unique(),
+            name: String::new(),
+            description: String::new(),
+            rules: String::new(),
+            is_private: false,
+            status: CommunityStatus::Active,
+            member_count: 1,
+            admin_count: 1,
+            pending_authority: None,
+            suspended_by_platform: false,
+            dues: None,
+            treasury_realm: None,
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_invite_and_request_flows() {
+        let mut community = test_community();
+        assert_eq!(Membership::join_status(&community), MembershipStatus::Active);
+        community.is_private = true;
+        assert_eq!(Membership::join_status(&community), MembershipStatus::Requested);
+
+        let mut membership = Membership {
+            is_initialized: true,
+            community: Pubkey::new_unique(),
+            member: Pubkey::new_unique(),
+            role: CommunityRole::Member,
+            status: MembershipStatus::Requested,
+            bump: 255,
+            dues_paid_until: 0,
+            joined_at: 0,
+            updated_at: 0,
+        };
+        // A request is approved by an invite, never by joining again
+        assert_eq!(membership.accept_invite(), Err(ProgramError::AccountAlreadyInitialized));
+        membership.approve_request().unwrap();
+        assert!(membership.is_active());
+        assert_eq!(membership.approve_request(), Err(ProgramError::AccountAlreadyInitialized));
 
-    fn pack_into_slice(&self, dst: &mut [u8]) {
-        let mut offset = 0;
-        
-        // Pack is_initialized
-        dst[offset] = self.is_initialized as u8;
-        offset += 1;
-        
-        // Pack authority
-        dst[offset..offset + 32].copy_from_slice(&self.authority.to_bytes());
-        offset += 32;
-        
-        // Pack name
-        let name_bytes = self.name.as_bytes();
-        dst[offset..offset + 4].copy_from_slice(&(name_bytes.len() as u32).to_le_bytes());
-        offset += 4;
-        dst[offset..offset + name_bytes.len()].copy_from_slice(name_bytes);
-        offset += name_bytes.len();
-        
-        // Pack description
-        let desc_bytes = self.description.as_bytes();
-        dst[offset..offset + 4].copy_from_slice(&(desc_bytes.len() as u32).to_le_bytes());
-        offset += 4;
-        dst[offset..offset + desc_bytes.len()].copy_from_slice(desc_bytes);
-        offset += desc_bytes.len();
-        
-        // Pack rules
-        let rules_bytes = self.rules.as_bytes();
-        dst[offset..offset + 4].copy_from_slice(&(rules_bytes.len() as u32).to_le_bytes());
-        offset += 4;
-        dst[offset..offset + rules_bytes.len()].copy_from_slice(rules_bytes);
-        offset += rules_bytes.len();
-        
-        // Pack is_private
-        dst[offset] = self.is_private as u8;
-        offset += 1;
-        
-        // Pack status
-        dst[offset] = self.status as u8;
-        offset += 1;
-        
-        // Pack member_count
-        dst[offset..offset + 4].copy_from_slice(&self.member_count.to_le_bytes());
-        offset += 4;
-        
-        // Pack timestamps
-        dst[offset..offset + 8].copy_from_slice(&self.created_at.to_le_bytes());
-        offset += 8;
-        dst[offset..offset + 8].copy_from_slice(&self.updated_at.to_le_bytes());
-    }
-
-    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let mut offset = 0;
-        
-        // Unpack is_initialized
-        let is_initialized = src[offset] != 0;
-        offset += 1;
-        
-        // Unpack authority
-        let authority_bytes: [u8; 32] = src[offset..offset + 32].try_into()
-            .map_err(|_| ProgramError::InvalidAccountData)?;
-        let authority = Pubkey::from(authority_bytes);
-        offset += 32;
-        
-        // Unpack name
-        let name_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
-        offset += 4;
-        let name = String::from_utf8(src[offset..offset + name_len].to_vec())
-            .map_err(|_| ProgramError::InvalidAccountData)?;
-        offset += name_len;
-        
-        // Unpack description
-        let desc_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
-        offset += 4;
-        let description = String::from_utf8(src[offset..offset + desc_len].to_vec())
-            .map_err(|_| ProgramError::InvalidAccountData)?;
-        offset += desc_len;
-        
-        // Unpack rules
-        let rules_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
-        offset += 4;
-        let rules = String::from_utf8(src[offset..offset + rules_len].to_vec())
-            .map_err(|_| ProgramError::InvalidAccountData)?;
-        offset += rules_len;
-        
-        // Unpack is_private
-        let is_private = src[offset] != 0;
-        offset += 1;
-        
-        // Unpack status
-        let status = match src[offset] {
-            0 => CommunityStatus::Active,
-            1 => CommunityStatus::Suspended,
-            2 => CommunityStatus::Archived,
-            _ => return Err(ProgramError::InvalidAccountData),
+        membership.status = MembershipStatus::Invited;
+        assert_eq!(membership.approve_request(), Err(ProgramError::AccountAlreadyInitialized));
+        membership.accept_invite().unwrap();
+        assert!(membership.is_active());
+        assert_eq!(membership.accept_invite(), Err(ProgramError::AccountAlreadyInitialized));
+
+        // A ban can't be joined or invited past
+        membership.status = MembershipStatus::Banned;
+        assert_eq!(membership.accept_invite(), Err(UnityVaultError::MemberBanned.into()));
+        assert_eq!(membership.approve_request(), Err(UnityVaultError::MemberBanned.into()));
+
+        let mut data = vec![0; Membership::LEN];
+        membership.pack(&mut data).unwrap();
+        assert_eq!(Membership::unpack(&data).unwrap(), membership);
+
+        assert!(CommunityRole::Admin.can_manage_members());
+        assert!(CommunityRole::Moderator.can_manage_members());
+        assert!(!CommunityRole::Member.can_manage_members());
+    }
+
+    #[test]
+    fn test_member_count_bounds() {
+        let mut community = test_community();
+        community.member_count = u32::MAX - 1;
+        community.add_member(CommunityRole::Member).unwrap();
+        assert_eq!(community.add_member(CommunityRole::Member), Err(ProgramError::ArithmeticOverflow));
+        community.member_count = 0;
+        assert_eq!(community.remove_member(CommunityRole::Member), Err(ProgramError::ArithmeticOverflow));
+    }
+
+    #[test]
+    fn test_last_admin_stays() {
+        let mut community = test_community();
+        assert_eq!(
+            community.change_role(CommunityRole::Admin, CommunityRole::Moderator),
+            Err(UnityVaultError::LastCommunityAdmin.into())
+        );
+        assert_eq!(
+            community.remove_member(CommunityRole::Admin),
+            Err(UnityVaultError::LastCommunityAdmin.into())
+        );
+
+        // A second admin lets the first step down
+        community.add_member(CommunityRole::Member).unwrap();
+        community.change_role(CommunityRole::Member, CommunityRole::Admin).unwrap();
+        assert_eq!(community.admin_count, 2);
+        community.change_role(CommunityRole::Admin, CommunityRole::Member).unwrap();
+        assert_eq!(community.admin_count, 1);
+        community.change_role(CommunityRole::Moderator, CommunityRole::Member).unwrap();
+        assert_eq!(community.admin_count, 1);
+
+        community.add_member(CommunityRole::Admin).unwrap();
+        community.remove_member(CommunityRole::Admin).unwrap();
+        assert_eq!((community.member_count, community.admin_count), (2, 1));
+
+        // Once archived the last admin can leave as well
+        community.status = CommunityStatus::Archived;
+        community.remove_member(CommunityRole::Admin).unwrap();
+        assert_eq!((community.member_count, community.admin_count), (1, 0));
+    }
+
+    #[test]
+    fn test_migrate_v1_adds_admin_fields() {
+        let authority = Pubkey::new_unique();
+        let mut data = vec![AccountType::Community as u8, 1];
+        data.extend(borsh::to_vec(&(
+            (true, authority, "n".to_string(), "d".to_string(), "r".to_string()),
+            (false, CommunityStatus::Active, 7u32, 10i64, 20i64),
+        )).unwrap());
+
+        let community = Community::decode_version(1, &data).unwrap();
+        assert_eq!(community.migration_authority(), authority);
+        assert_eq!(community.member_count, 7);
+        assert_eq!(community.admin_count, 0);
+        assert_eq!(community.pending_authority, None);
+        assert_eq!(community.updated_at, 20);
+        assert!(Community::decode_version(Community::VERSION, &data).is_err());
+    }
+
+    #[test]
+    fn test_migrate_v2_adds_suspension_origin() {
+        let mut data = vec![AccountType::Community as u8, 2];
+        data.extend(borsh::to_vec(&(
+            (true, Pubkey::new_unique(), "n".to_string(), "d".to_string(), "r".to_string()),
+            (false, CommunityStatus::Suspended, 7u32, 2u32, None::<Pubkey>, 10i64, 20i64),
+        )).unwrap());
+
+        let mut community = Community::decode_version(2, &data).unwrap();
+        assert_eq!(community.admin_count, 2);
+        assert!(!community.suspended_by_platform);
+        // The authority can lift a suspension it made before the upgrade
+        community.reinstate(false).unwrap();
+        assert_eq!(community.status, CommunityStatus::Active);
+    }
+
+    #[test]
+    fn test_suspend_reinstate_and_archive() {
+        let mut community = test_community();
+        assert_eq!(community.reinstate(true), Err(UnityVaultError::CommunityNotSuspended));
+
+        // The authority can't lift a platform suspension
+        community.suspend(true).unwrap();
+        assert_eq!(community.check_active(), Err(UnityVaultError::CommunityNotActive));
+        assert_eq!(community.suspend(false), Err(UnityVaultError::CommunityNotActive));
+        assert_eq!(community.reinstate(false), Err(UnityVaultError::NotAPlatformModerator));
+        community.reinstate(true).unwrap();
+        assert_eq!(community.check_active(), Ok(()));
+        assert!(!community.suspended_by_platform);
+
+        // Platform moderators can lift the authority's own
+        community.suspend(false).unwrap();
+        community.reinstate(true).unwrap();
+
+        // Archiving is for good
+        community.suspend(false).unwrap();
+        community.archive().unwrap();
+        assert_eq!(community.archive(), Err(UnityVaultError::CommunityNotActive));
+        assert_eq!(community.reinstate(true), Err(UnityVaultError::CommunityNotSuspended));
+        assert_eq!(community.suspend(true), Err(UnityVaultError::CommunityNotActive));
+    }
+
+    #[test]
+    fn test_role_ranks() {
+        assert!(CommunityRole::Admin.outranks(CommunityRole::Moderator));
+        assert!(CommunityRole::Moderator.outranks(CommunityRole::Member));
+        assert!(!CommunityRole::Moderator.outranks(CommunityRole::Moderator));
+        assert!(!CommunityRole::Admin.outranks(CommunityRole::Admin));
+        assert!(!CommunityRole::Member.outranks(CommunityRole::Moderator));
+    }
+
+    #[test]
+    fn test_dues_standing_and_lapse() {
+        const DAY: i64 = 24 * 60 * 60;
+        let mut community = test_community();
+        let mut membership = Membership {
+            is_initialized: true,
+            community: Pubkey::new_unique(),
+            member: Pubkey::new_unique(),
+            role: CommunityRole::Member,
+            status: MembershipStatus::Active,
+            bump: 255,
+            dues_paid_until: 0,
+            joined_at: 10 * DAY,
+            updated_at: 10 * DAY,
+        };
+        assert_eq!(membership.pay_dues(&community, 1, 0), Err(UnityVaultError::DuesNotCharged.into()));
+        assert!(membership.is_in_good_standing(&community, i64::MAX));
+
+        let params = MembershipDuesParams { mint: Pubkey::new_unique(), amount: 5, period: 30 * DAY, grace_period: 7 * DAY };
+        assert_eq!(params.validate(), Ok(()));
+        assert_eq!(MembershipDuesParams { period: DAY - 1, ..params.clone() }.validate(), Err(UnityVaultError::InvalidDuesConfig));
+        assert_eq!(MembershipDuesParams { amount: 0, ..params.clone() }.validate(), Err(UnityVaultError::InvalidDuesConfig));
+        assert_eq!(MembershipDuesParams { grace_period: -1, ..params.clone() }.validate(), Err(UnityVaultError::InvalidDuesConfig));
+        community.dues = Some(MembershipDues::new(params, 5 * DAY));
+
+        // Members owe from when they joined, after the dues took effect
+        assert!(membership.is_in_good_standing(&community, 17 * DAY));
+        assert!(!membership.is_in_good_standing(&community, 17 * DAY + 1));
+        assert_eq!(membership.lapse(&community, 17 * DAY), Err(UnityVaultError::DuesNotOverdue));
+
+        // Paying within the grace period covers the time since joining
+        assert_eq!(membership.pay_dues(&community, 2, 15 * DAY), Ok(10));
+        assert_eq!(membership.dues_paid_until, 70 * DAY);
+        assert!(membership.is_in_good_standing(&community, 77 * DAY));
+
+        membership.lapse(&community, 77 * DAY + 1).unwrap();
+        assert_eq!(membership.status, MembershipStatus::Lapsed);
+        assert!(!membership.is_in_good_standing(&community, 0));
+        assert_eq!(membership.lapse(&community, 77 * DAY + 1), Err(UnityVaultError::DuesNotOverdue));
+
+        // A lapsed membership pays from now, not for the time it missed
+        assert_eq!(membership.pay_dues(&community, 1, 100 * DAY), Ok(5));
+        assert_eq!(membership.status, MembershipStatus::Active);
+        assert_eq!(membership.dues_paid_until, 130 * DAY);
+
+        // Once dues stop, lapsed members can come back by joining
+        membership.status = MembershipStatus::Lapsed;
+        assert_eq!(membership.rejoin(&community), Err(ProgramError::AccountAlreadyInitialized));
+        community.dues = None;
+        membership.rejoin(&community).unwrap();
+        assert!(membership.is_active());
+
+        membership.status = MembershipStatus::Banned;
+        community.dues = Some(MembershipDues::new(MembershipDuesParams { mint: Pubkey::new_unique(), amount: 5, period: DAY, grace_period: 0 }, 0));
+        assert!(!membership.owes_dues(&community, DAY));
+        assert_eq!(membership.pay_dues(&community, 1, 0), Err(UnityVaultError::NotACommunityMember.into()));
+    }
+
+    #[test]
+    fn test_leaving_requires_dues_paid() {
+        const DAY: i64 = 24 * 60 * 60;
+        let mut community = test_community();
+        let mut membership = Membership {
+            is_initialized: true,
+            community: Pubkey::new_unique(),
+            member: Pubkey::new_unique(),
+            role: CommunityRole::Member,
+            status: MembershipStatus::Active,
+            bump: 255,
+            dues_paid_until: 0,
+            joined_at: 10 * DAY,
+            updated_at: 10 * DAY,
         };
-        offset += 1;
-        
-        // Unpack member_count
-        let member_count = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap());
-        offset += 4;
-        
-        // Unpack timestamps
-        let created_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
-        offset += 8;
-        let updated_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
-        
-        Ok(Community {
-            is_initialized,
-            authority,
-            name,
-            description,
-            rules,
-            is_private,
-            status,
-            member_count,
-            created_at,
-            updated_at,
-        })
-    }
-} 
\ No newline at end of file
+        assert_eq!(membership.check_can_leave(&community, 20 * DAY), Ok(()));
+
+        let params = MembershipDuesParams { mint: Pubkey::new_unique(), amount: 5, period: 30 * DAY, grace_period: 7 * DAY };
+        community.dues = Some(MembershipDues::new(params, 0));
+
+        // Leaving and joining again would restart the grace period, so time
+        // already spent in the community has to be paid for first
+        assert_eq!(membership.check_can_leave(&community, 10 * DAY), Ok(()));
+        assert_eq!(membership.check_can_leave(&community, 12 * DAY), Err(UnityVaultError::DuesUnpaid));
+        membership.pay_dues(&community, 1, 12 * DAY).unwrap();
+        assert_eq!(membership.check_can_leave(&community, 12 * DAY), Ok(()));
+
+        membership.lapse(&community, 48 * DAY).unwrap();
+        assert_eq!(membership.check_can_leave(&community, 48 * DAY), Err(UnityVaultError::DuesUnpaid));
+
+        // Nobody can rejoin an archived community, so bans and dues no longer
+        // keep members and their rent in it
+        community.status = CommunityStatus::Archived;
+        assert_eq!(membership.check_can_leave(&community, 48 * DAY), Ok(()));
+        membership.status = MembershipStatus::Banned;
+        assert_eq!(membership.check_can_leave(&community, 48 * DAY), Ok(()));
+        community.status = CommunityStatus::Suspended;
+        assert_eq!(membership.check_can_leave(&community, 48 * DAY), Err(UnityVaultError::MemberBanned));
+
+        membership.status = MembershipStatus::Invited;
+        community.status = CommunityStatus::Active;
+        assert_eq!(membership.check_can_leave(&community, 48 * DAY), Ok(()));
+    }
+
+    #[test]
+    fn test_migrate_v3_adds_dues() {
+        let mut data = vec![AccountType::Community as u8, 3];
+        data.extend(borsh::to_vec(&(
+            (true, Pubkey::new_unique(), "n".to_string(), "d".to_string(), "r".to_string()),
+            (false, CommunityStatus::Suspended, 7u32, 2u32, None::<Pubkey>, true, 10i64, 20i64),
+        )).unwrap());
+
+        let community = Community::decode_version(3, &data).unwrap();
+        assert!(community.suspended_by_platform);
+        assert_eq!(community.dues, None);
+        assert_eq!(community.treasury_realm, None);
+        assert_eq!(community.updated_at, 20);
+    }
+
+    #[test]
+    fn test_migrate_every_membership_layout() {
+        let member = Pubkey::new_unique();
+        let mut data = vec![AccountType::Membership as u8, 1];
+        data.extend(borsh::to_vec(&(
+            true, Pubkey::new_unique(), member, CommunityRole::Moderator, 254u8, 10i64, 20i64,
+        )).unwrap());
+
+        let membership = Membership::decode_version(1, &data).unwrap();
+        assert_eq!(membership.migration_authority(), member);
+        assert_eq!(membership.role, CommunityRole::Moderator);
+        assert_eq!(membership.status, MembershipStatus::Active);
+        assert_eq!(membership.dues_paid_until, 0);
+        assert_eq!(membership.joined_at, 10);
+        assert!(Membership::unpack(&data).is_err());
+
+        let mut data = vec![AccountType::Membership as u8, 2];
+        data.extend(borsh::to_vec(&(
+            true, Pubkey::new_unique(), member, CommunityRole::Member, MembershipStatus::Banned, 254u8, 10i64, 20i64,
+        )).unwrap());
+
+        let membership = Membership::decode_version(2, &data).unwrap();
+        assert_eq!(membership.migration_authority(), member);
+        assert_eq!(membership.status, MembershipStatus::Banned);
+        assert_eq!(membership.dues_paid_until, 0);
+        assert_eq!(membership.updated_at, 20);
+        assert!(Membership::decode_version(Membership::VERSION, &data).is_err());
+    }
+
+    fn project_params() -> ProjectParams {
+        let milestone = |amount, deadline| ProjectMilestoneParams { title: "m".to_string(), amount, deadline };
+        ProjectParams {
+            title: "Well".to_string(),
+            description: String::new(),
+            funding_deadline: 100,
+            milestones: vec![milestone(60, 1_000), milestone(40, 2_000)],
+        }
+    }
+
+    fn test_project() -> CommunityProject {
+        let params = project_params();
+        CommunityProject {
+            is_initialized: true,
+            community: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            title: params.title.clone(),
+            description: params.description.clone(),
+            mint: Pubkey::new_unique(),
+            funding_goal: params.funding_goal().unwrap(),
+            funding_deadline: params.funding_deadline,
+            total_contributed: 0,
+            total_released: 0,
+            contributor_count: 0,
+            status: ProjectStatus::Funding,
+            milestones: params.milestones.into_iter().map(ProjectMilestone::from).collect(),
+            current_milestone: 0,
+            vote_round: 0,
+            vault_bump: 255,
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    fn test_contribution(amount: u64) -> ProjectContribution {
+        ProjectContribution {
+            is_initialized: true,
+            project: Pubkey::new_unique(),
+            contributor: Pubkey::new_unique(),
+            amount,
+            voted_round: 0,
+            refunded: false,
+            bump: 255,
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_project_params_validation() {
+        let params = project_params();
+        assert_eq!(params.validate(0), Ok(()));
+        assert_eq!(params.funding_goal(), Ok(100));
+        assert_eq!(params.validate(100), Err(UnityVaultError::InvalidProjectParams));
+
+        // Milestones are due after funding closes, in order
+        let mut out_of_order = project_params();
+        out_of_order.milestones[1].deadline = 1_000;
+        assert_eq!(out_of_order.validate(0), Err(UnityVaultError::InvalidProjectParams));
+        let mut empty_tranche = project_params();
+        empty_tranche.milestones[0].amount = 0;
+        assert_eq!(empty_tranche.validate(0), Err(UnityVaultError::InvalidProjectParams));
+        let mut overflowing = project_params();
+        overflowing.milestones[0].amount = u64::MAX;
+        assert_eq!(overflowing.validate(0), Err(UnityVaultError::InvalidProjectParams));
+        let mut too_many = project_params();
+        too_many.milestones = (0..=MAX_PROJECT_MILESTONES as i64)
+            .map(|i| ProjectMilestoneParams { title: String::new(), amount: 1, deadline: 200 + i })
+            .collect();
+        assert_eq!(too_many.validate(0), Err(UnityVaultError::TooManyEntries));
+
+        // A project at its full budget fits its allocation
+        let mut project = test_project();
+        project.title = "t".repeat(MAX_PROJECT_TITLE_LEN);
+        project.description = "d".repeat(MAX_PROJECT_DESCRIPTION_LEN);
+        project.milestones = (0..MAX_PROJECT_MILESTONES)
+            .map(|_| ProjectMilestone::from(ProjectMilestoneParams {
+                title: "m".repeat(MAX_MILESTONE_TITLE_LEN),
+                amount: u64::MAX,
+                deadline: i64::MAX,
+            }))
+            .collect();
+        let mut data = vec![0; CommunityProject::LEN];
+        project.pack(&mut data).unwrap();
+        assert_eq!(CommunityProject::unpack(&data).unwrap().milestones, project.milestones);
+    }
+
+    #[test]
+    fn test_project_funding_and_milestone_release() {
+        let mut project = test_project();
+        let mut backer = test_contribution(0);
+        let mut other = test_contribution(0);
+
+        assert_eq!(project.submit_milestone(10), Err(UnityVaultError::ProjectNotActive.into()));
+        let owner = project.owner;
+        assert_eq!(project.contribute(&backer.contributor, 0, 10), Err(ProgramError::InvalidArgument));
+        assert_eq!(project.contribute(&backer.contributor, 101, 10), Err(UnityVaultError::FundingGoalExceeded.into()));
+        // The owner's own funding would outvote everyone on its milestones
+        assert_eq!(project.contribute(&owner, 60, 10), Err(UnityVaultError::OwnerCannotContribute.into()));
+        project.contribute(&backer.contributor, 60, 10).unwrap();
+        backer.add(60).unwrap();
+        assert_eq!(project.status, ProjectStatus::Funding);
+        project.contribute(&other.contributor, 40, 20).unwrap();
+        other.add(40).unwrap();
+        assert_eq!(project.status, ProjectStatus::Funded);
+        assert_eq!(project.contribute(&other.contributor, 1, 20), Err(UnityVaultError::ProjectNotActive.into()));
+
+        // Rejected by a majority of the funding, then resubmitted
+        project.submit_milestone(200).unwrap();
+        assert_eq!(project.submit_milestone(200), Err(UnityVaultError::ProjectNotActive.into()));
+        assert_eq!(project.finalize_milestone(200), Err(UnityVaultError::VotingStillOpen.into()));
+        project.vote_milestone(&mut backer, false, 200).unwrap();
+        assert_eq!(project.vote_milestone(&mut backer, true, 200), Err(UnityVaultError::AlreadyVoted.into()));
+        assert_eq!(project.finalize_milestone(200), Ok(None));
+        assert_eq!(project.milestones[0].status, MilestoneStatus::Pending);
+
+        project.submit_milestone(300).unwrap();
+        project.vote_milestone(&mut other, true, 300).unwrap();
+        let ends_at = project.milestones[0].voting_ends_at;
+        assert_eq!(project.vote_milestone(&mut backer, true, ends_at), Err(UnityVaultError::VotingClosed.into()));
+        // 40 of 100 in favour and nobody against isn't a majority
+        assert_eq!(project.finalize_milestone(ends_at), Ok(None));
+
+        project.submit_milestone(400).unwrap();
+        project.vote_milestone(&mut backer, true, 400).unwrap();
+        assert_eq!(project.finalize_milestone(400), Ok(Some(60)));
+        assert_eq!((project.current_milestone, project.total_released), (1, 60));
+
+        // The last tranche completes the project
+        project.submit_milestone(1_500).unwrap();
+        project.vote_milestone(&mut backer, true, 1_500).unwrap();
+        assert_eq!(project.finalize_milestone(1_500), Ok(Some(40)));
+        assert_eq!(project.status, ProjectStatus::Completed);
+        assert_eq!(project.cancel(), Err(UnityVaultError::ProjectNotActive));
+        assert_eq!(project.check_refundable(i64::MAX), Err(UnityVaultError::ProjectNotRefundable));
+    }
+
+    #[test]
+    fn test_project_refunds() {
+        // Funding deadline missed
+        let mut project = test_project();
+        project.contribute(&Pubkey::new_unique(), 30, 10).unwrap();
+        assert_eq!(project.check_refundable(99), Err(UnityVaultError::ProjectNotRefundable));
+        assert_eq!(project.check_refundable(100), Ok(()));
+        assert_eq!(project.status, ProjectStatus::Failed);
+        let mut contribution = test_contribution(30);
+        assert_eq!(project.refund_amount(&contribution), 30);
+        contribution.mark_refunded().unwrap();
+        assert_eq!(contribution.mark_refunded(), Err(UnityVaultError::NothingToRefund));
+
+        // Milestone deadline missed after a release: only the escrow is shared out
+        let mut project = test_project();
+        project.contribute(&Pubkey::new_unique(), 100, 10).unwrap();
+        project.submit_milestone(200).unwrap();
+        project.milestones[0].approve_weight = 100;
+        project.finalize_milestone(200).unwrap();
+        project.submit_milestone(1_500).unwrap();
+        // Not while the milestone is under vote
+        assert_eq!(project.check_refundable(2_001), Err(UnityVaultError::ProjectNotRefundable));
+        project.milestones[1].reject_weight = 100;
+        project.finalize_milestone(2_001).unwrap();
+        assert_eq!(project.check_refundable(2_001), Ok(()));
+        assert_eq!(project.refund_amount(&test_contribution(25)), 10);
+
+        let mut project = test_project();
+        project.contribute(&Pubkey::new_unique(), 100, 10).unwrap();
+        project.cancel().unwrap();
+        assert_eq!(project.check_refundable(10), Ok(()));
+        assert_eq!(project.status, ProjectStatus::Cancelled);
+    }
+}
@@ -0,0 +1,120 @@
+use solana_program::{program_error::ProgramError, pubkey::Pubkey};
+use spl_associated_token_account::get_associated_token_address;
+use crate::governance::state::{find_governance_authority, ProposalAccountMeta, ProposalInstruction};
+use crate::governance::treasury::TreasuryAsset;
+use crate::{CommunityInstruction, Instruction};
+
+pub const COMMUNITY_TREASURY_SEED: &[u8] = b"community_treasury";
+
+/// System-owned PDA holding the SOL of `community`. SPL tokens, membership
+/// dues among them, are held in its associated token accounts. Funds can
+/// only leave through the community's `TreasuryTransfer`, which the
+/// governance authority of `Community::treasury_realm` must sign, so an
+/// executed proposal in that realm is the only spending path.
+pub fn find_community_treasury(program_id: &Pubkey, community: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[COMMUNITY_TREASURY_SEED, community.as_ref()], program_id)
+}
+
+/// Associated token account of the community treasury for `mint`.
+pub fn find_community_treasury_token_account(program_id: &Pubkey, community: &Pubkey, mint: &Pubkey) -> Pubkey {
+    let (treasury, _) = find_community_treasury(program_id, community);
+    get_associated_token_address(&treasury, mint)
+}
+
+/// Builds the instruction a proposal in `realm` carries to pay `amount` of
+/// `asset` from the community treasury to `destination`. For tokens,
+/// `destination` is a token account.
+pub fn community_treasury_transfer_instruction(
+    program_id: &Pubkey,
+    community: &Pubkey,
+    realm: &Pubkey,
+    asset: TreasuryAsset,
+    destination: &Pubkey,
+    amount: u64,
+) -> Result<ProposalInstruction, ProgramError> {
+    let (governance_authority, _) = find_governance_authority(program_id, realm);
+    let (treasury, _) = find_community_treasury(program_id, community);
+
+    let meta = |pubkey: Pubkey, is_signer: bool, is_writable: bool| ProposalAccountMeta {
+        pubkey,
+        is_signer,
+        is_writable,
+    };
+    let mut accounts = vec![
+        meta(governance_authority, true, false),
+        meta(*realm, false, false),
+        meta(*community, false, false),
+    ];
+    match asset {
+        TreasuryAsset::Sol => accounts.extend([
+            meta(treasury, false, true),
+            meta(*destination, false, true),
+            meta(solana_program::system_program::id(), false, false),
+        ]),
+        TreasuryAsset::Token { mint } => accounts.extend([
+            meta(treasury, false, false),
+            meta(get_associated_token_address(&treasury, &mint), false, true),
+            meta(*destination, false, true),
+            meta(spl_token::id(), false, false),
+        ]),
+    }
+
+    let data = borsh::to_vec(&Instruction::Community(CommunityInstruction::TreasuryTransfer { asset, amount }))
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    Ok(ProposalInstruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshDeserialize;
+
+    #[test]
+    fn test_transfer_instruction_targets_community_treasury() {
+        let program_id = Pubkey::new_unique();
+        let community = Pubkey::new_unique();
+        let realm = Pubkey::new_unique();
+        let destination = Pubkey::new_unique();
+        let (treasury, _) = find_community_treasury(&program_id, &community);
+        let (governance_authority, _) = find_governance_authority(&program_id, &realm);
+
+        let instruction = community_treasury_transfer_instruction(
+            &program_id,
+            &community,
+            &realm,
+            TreasuryAsset::Sol,
+            &destination,
+            5,
+        )
+        .unwrap();
+        assert_eq!(instruction.accounts[0], ProposalAccountMeta { pubkey: governance_authority, is_signer: true, is_writable: false });
+        assert_eq!(instruction.accounts[2].pubkey, community);
+        assert_eq!(instruction.accounts[3].pubkey, treasury);
+        assert!(instruction.validate().is_ok());
+        match Instruction::try_from_slice(&instruction.data).unwrap() {
+            Instruction::Community(CommunityInstruction::TreasuryTransfer { asset, amount }) => {
+                assert_eq!(asset, TreasuryAsset::Sol);
+                assert_eq!(amount, 5);
+            }
+            _ => panic!("expected a community treasury transfer"),
+        }
+
+        let mint = Pubkey::new_unique();
+        let instruction = community_treasury_transfer_instruction(
+            &program_id,
+            &community,
+            &realm,
+            TreasuryAsset::Token { mint },
+            &destination,
+            5,
+        )
+        .unwrap();
+        assert_eq!(instruction.accounts[4].pubkey, find_community_treasury_token_account(&program_id, &community, &mint));
+        assert_eq!(instruction.accounts[5].pubkey, destination);
+    }
+}
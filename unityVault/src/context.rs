@@ -0,0 +1,143 @@
+use solana_program::{
+    account_info::AccountInfo,
+    bpf_loader_upgradeable,
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use crate::account::AccountState;
+use crate::error::UnityVaultError;
+
+/// Accounts an instruction expects, in order. Every handler starts by
+/// calling `try_accounts`, which takes its accounts from the front of the
+/// instruction's account list and runs the checks that only depend on the
+/// accounts themselves: signers, owners, program ids and initialization.
+pub trait Accounts<'a>: Sized {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError>;
+}
+
+pub fn check_signer(account: &AccountInfo) -> ProgramResult {
+    if !account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    Ok(())
+}
+
+/// Checks `account` is owned by this program.
+pub fn check_program_owned(program_id: &Pubkey, account: &AccountInfo) -> ProgramResult {
+    if account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+/// Checks `account` is owned by this program and not yet written, so
+/// initializing it can't overwrite an existing account.
+pub fn check_uninitialized<T: AccountState>(program_id: &Pubkey, account: &AccountInfo) -> ProgramResult {
+    check_program_owned(program_id, account)?;
+    if T::is_initialized_account(&account.data.borrow()) {
+        return Err(ProgramError::AccountAlreadyInitialized);
+    }
+    Ok(())
+}
+
+pub fn check_system_program(account: &AccountInfo) -> ProgramResult {
+    if account.key != &solana_program::system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+pub fn check_token_program(account: &AccountInfo) -> ProgramResult {
+    if account.key != &spl_token::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    Ok(())
+}
+
+/// Checks `authority` is the upgrade authority recorded in `program_data`,
+/// this program's data account under the upgradeable loader. That key
+/// deploys the program, so it is the one platform-wide authority.
+pub fn check_upgrade_authority(program_id: &Pubkey, program_data: &AccountInfo, authority: &AccountInfo) -> ProgramResult {
+    let (program_data_key, _) = Pubkey::find_program_address(&[program_id.as_ref()], &bpf_loader_upgradeable::id());
+    if program_data.key != &program_data_key || program_data.owner != &bpf_loader_upgradeable::id() {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // `UpgradeableLoaderState::ProgramData`: a u32 tag, the deployment slot
+    // and an optional upgrade authority
+    let data = program_data.data.borrow();
+    let upgrade_authority = match data.get(..45) {
+        Some([3, 0, 0, 0, _, _, _, _, _, _, _, _, 1, key @ ..]) => Pubkey::try_from(key).ok(),
+        _ => None,
+    };
+    if upgrade_authority != Some(*authority.key) || !authority.is_signer {
+        return Err(UnityVaultError::NotPlatformAuthority.into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::community::state::Community;
+    use solana_program::clock::Epoch;
+
+    #[test]
+    fn test_check_uninitialized() {
+        let program_id = Pubkey::new_unique();
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mut data = vec![0; Community::LEN];
+        let account = AccountInfo::new(&key, false, true, &mut lamports, &mut data, &program_id, false, Epoch::default());
+
+        assert_eq!(check_uninitialized::<Community>(&program_id, &account), Ok(()));
+        assert_eq!(
+            check_uninitialized::<Community>(&Pubkey::new_unique(), &account),
+            Err(ProgramError::IncorrectProgramId)
+        );
+        account.data.borrow_mut()[0] = 1;
+        assert_eq!(
+            check_uninitialized::<Community>(&program_id, &account),
+            Err(ProgramError::AccountAlreadyInitialized)
+        );
+        assert_eq!(check_signer(&account), Err(ProgramError::MissingRequiredSignature));
+        assert_eq!(check_system_program(&account), Err(ProgramError::IncorrectProgramId));
+    }
+
+    #[test]
+    fn test_check_upgrade_authority() {
+        let program_id = Pubkey::new_unique();
+        let loader = bpf_loader_upgradeable::id();
+        let (program_data_key, _) = Pubkey::find_program_address(&[program_id.as_ref()], &loader);
+        let upgrade_authority = Pubkey::new_unique();
+        let mut data = vec![3, 0, 0, 0, 7, 0, 0, 0, 0, 0, 0, 0, 1];
+        data.extend_from_slice(upgrade_authority.as_ref());
+        let mut lamports = 0;
+        let program_data = AccountInfo::new(&program_data_key, false, false, &mut lamports, &mut data, &loader, false, Epoch::default());
+
+        let mut signer_lamports = 0;
+        let signer = AccountInfo::new(&upgrade_authority, true, false, &mut signer_lamports, &mut [], &program_id, false, Epoch::default());
+        assert_eq!(check_upgrade_authority(&program_id, &program_data, &signer), Ok(()));
+
+        let other_key = Pubkey::new_unique();
+        let mut other_lamports = 0;
+        let other = AccountInfo::new(&other_key, true, false, &mut other_lamports, &mut [], &program_id, false, Epoch::default());
+        assert_eq!(
+            check_upgrade_authority(&program_id, &program_data, &other),
+            Err(UnityVaultError::NotPlatformAuthority.into())
+        );
+
+        // An immutable program has no upgrade authority left to act
+        program_data.data.borrow_mut()[12] = 0;
+        assert_eq!(
+            check_upgrade_authority(&program_id, &program_data, &signer),
+            Err(UnityVaultError::NotPlatformAuthority.into())
+        );
+
+        assert_eq!(
+            check_upgrade_authority(&Pubkey::new_unique(), &program_data, &signer),
+            Err(ProgramError::InvalidAccountData)
+        );
+    }
+}
@@ -0,0 +1,74 @@
+use solana_program::program_error::ProgramError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnityVaultError {
+    // Two-factor authentication
+    TwoFactorNotEnabled,
+    InvalidBackupCode,
+    GuardianSignatureRequired,
+    // Authority recovery
+    InvalidRecoveryConfig,
+    NotARecoveryGuardian,
+    NoPendingRecovery,
+    RecoveryThresholdNotMet,
+    RecoveryTimelockActive,
+    // Input validation
+    StringTooLong,
+    TooManyEntries,
+    // Account layout
+    AccountTypeMismatch,
+    UnsupportedAccountVersion,
+    // Governance
+    AlreadyVoted,
+    VotingClosed,
+    VotingStillOpen,
+    NoVotingWeight,
+    GoverningMintMismatch,
+    GoverningTokensLocked,
+    InvalidProposalParams,
+    ProposalNotExecutable,
+    TransactionAlreadyExecuted,
+    InsufficientGoverningTokens,
+    InvalidVoteChoice,
+    InvalidGovernanceConfig,
+    NotARealmMember,
+    InvalidLockDuration,
+    NoActiveLock,
+    InvalidVetoCouncil,
+    VetoThresholdNotMet,
+    VetoWindowClosed,
+    GovernanceGuardRail,
+    // Community
+    NotACommunityMember,
+    InsufficientCommunityRole,
+    LastCommunityAdmin,
+    MemberBanned,
+    CommunityNotActive,
+    CommunityNotSuspended,
+    NotAPlatformModerator,
+    // Community projects
+    InvalidProjectParams,
+    ProjectNotActive,
+    FundingGoalExceeded,
+    ProjectNotRefundable,
+    NothingToRefund,
+    // Community treasury and dues
+    InvalidDuesConfig,
+    DuesNotCharged,
+    DuesNotOverdue,
+    TreasuryRealmMismatch,
+    // Two-factor authentication, appended to keep existing error codes stable
+    TwoFactorAlreadyEnabled,
+    // Platform administration
+    NotPlatformAuthority,
+    // Community dues, appended to keep existing error codes stable
+    DuesUnpaid,
+    // Community projects
+    OwnerCannotContribute,
+}
+
+impl From<UnityVaultError> for ProgramError {
+    fn from(e: UnityVaultError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
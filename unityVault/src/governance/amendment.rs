@@ -0,0 +1,131 @@
+use solana_program::{
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use crate::error::UnityVaultError;
+use crate::governance::state::{find_governance_authority, GovernanceConfig, ProposalAccountMeta, ProposalInstruction, Realm};
+use crate::{GovernanceInstruction, Instruction};
+
+/// Rules set by governance need at least a simple majority.
+pub const MIN_AMENDED_APPROVAL_PERCENTAGE: u8 = 50;
+/// Rules set by governance leave at least a day to veto or react to a
+/// passed proposal before it executes.
+pub const MIN_AMENDED_EXECUTION_DELAY: i64 = 24 * 60 * 60;
+
+/// Guard rails on the rules a proposal can give its own realm. Beyond the
+/// usual bounds, quorum can not drop below the floor the realm was created
+/// with, approval can not drop below a simple majority and the execution
+/// delay can not drop below `MIN_AMENDED_EXECUTION_DELAY`.
+pub fn check_amendment(realm: &Realm, config: &GovernanceConfig) -> Result<(), UnityVaultError> {
+    config.validate()?;
+    if config.quorum < realm.quorum_floor
+        || config.min_approval_percentage < MIN_AMENDED_APPROVAL_PERCENTAGE
+        || config.execution_delay < MIN_AMENDED_EXECUTION_DELAY
+    {
+        return Err(UnityVaultError::GovernanceGuardRail);
+    }
+    Ok(())
+}
+
+/// Builds the instruction a proposal carries to replace the voting rules of
+/// `realm` once executed. Proposals already created keep the rules they
+/// were created with.
+pub fn set_governance_config_instruction(
+    program_id: &Pubkey,
+    realm: &Pubkey,
+    config: GovernanceConfig,
+) -> Result<ProposalInstruction, ProgramError> {
+    let (governance_authority, _) = find_governance_authority(program_id, realm);
+
+    let accounts = vec![
+        ProposalAccountMeta { pubkey: *realm, is_signer: false, is_writable: true },
+        ProposalAccountMeta { pubkey: governance_authority, is_signer: true, is_writable: false },
+    ];
+
+    let data = borsh::to_vec(&Instruction::Governance(GovernanceInstruction::SetGovernanceConfig(config)))
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    Ok(ProposalInstruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshDeserialize;
+    use crate::governance::state::{RealmScope, MAX_EXECUTION_DELAY};
+
+    fn realm(quorum_floor: u64) -> Realm {
+        Realm {
+            is_initialized: true,
+            scope: RealmScope::Platform,
+            governing_mint: Pubkey::new_unique(),
+            config: GovernanceConfig {
+                quorum: quorum_floor,
+                min_approval_percentage: 60,
+                voting_duration: 86_400,
+                execution_delay: 3_600,
+            },
+            quorum_floor,
+            bump: 255,
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_amendment_guard_rails() {
+        let realm = realm(100);
+        let amended = GovernanceConfig {
+            quorum: 100,
+            min_approval_percentage: MIN_AMENDED_APPROVAL_PERCENTAGE,
+            voting_duration: 86_400,
+            execution_delay: MIN_AMENDED_EXECUTION_DELAY,
+        };
+        assert_eq!(check_amendment(&realm, &amended), Ok(()));
+
+        let rejected = [
+            GovernanceConfig { quorum: 99, ..amended },
+            GovernanceConfig { quorum: 0, ..amended },
+            GovernanceConfig { min_approval_percentage: MIN_AMENDED_APPROVAL_PERCENTAGE - 1, ..amended },
+            GovernanceConfig { execution_delay: MIN_AMENDED_EXECUTION_DELAY - 1, ..amended },
+        ];
+        for config in rejected {
+            assert_eq!(check_amendment(&realm, &config), Err(UnityVaultError::GovernanceGuardRail));
+        }
+
+        // The usual bounds still apply
+        let out_of_bounds = GovernanceConfig { execution_delay: MAX_EXECUTION_DELAY + 1, ..amended };
+        assert_eq!(check_amendment(&realm, &out_of_bounds), Err(UnityVaultError::InvalidGovernanceConfig));
+
+        // Raising the rules is always allowed
+        let stricter = GovernanceConfig { quorum: u64::MAX, min_approval_percentage: 100, ..amended };
+        assert_eq!(check_amendment(&realm, &stricter), Ok(()));
+    }
+
+    #[test]
+    fn test_config_instruction_is_signed_by_governance() {
+        let program_id = Pubkey::new_unique();
+        let realm = Pubkey::new_unique();
+        let (governance_authority, _) = find_governance_authority(&program_id, &realm);
+        let config = GovernanceConfig {
+            quorum: 500,
+            min_approval_percentage: 66,
+            voting_duration: 86_400,
+            execution_delay: MIN_AMENDED_EXECUTION_DELAY,
+        };
+
+        let instruction = set_governance_config_instruction(&program_id, &realm, config).unwrap();
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts[0], ProposalAccountMeta { pubkey: realm, is_signer: false, is_writable: true });
+        assert_eq!(instruction.accounts[1], ProposalAccountMeta { pubkey: governance_authority, is_signer: true, is_writable: false });
+        assert!(instruction.validate().is_ok());
+        match Instruction::try_from_slice(&instruction.data).unwrap() {
+            Instruction::Governance(GovernanceInstruction::SetGovernanceConfig(decoded)) => assert_eq!(decoded, config),
+            _ => panic!("expected a governance config update"),
+        }
+    }
+}
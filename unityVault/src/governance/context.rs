@@ -4,99 +4,543 @@ use solana_program::{
     program_error::ProgramError,
     pubkey::Pubkey,
 };
-use crate::governance::state::Proposal;
-use std::convert::TryFrom;
+use crate::account::AccountState;
+use crate::context::{
+    check_program_owned, check_signer, check_system_program, check_token_program, check_uninitialized, Accounts,
+};
+use crate::governance::state::{find_governance_authority, Proposal, Realm, TokenOwnerRecord};
+use crate::governance::treasury::find_treasury_address;
 
 pub struct CreateProposalContext<'a> {
     pub proposal: &'a AccountInfo<'a>,
     pub authority: &'a AccountInfo<'a>,
     pub system_program: &'a AccountInfo<'a>,
+    pub realm: &'a AccountInfo<'a>,
+    pub deposit_config: &'a AccountInfo<'a>,
+    pub token_owner_record: &'a AccountInfo<'a>,
+    /// The proposer's membership and the community, for community realms
+    pub membership: Option<&'a AccountInfo<'a>>,
+    pub community: Option<&'a AccountInfo<'a>>,
 }
 
-impl<'a> TryFrom<&'a [AccountInfo<'a>]> for CreateProposalContext<'a> {
-    type Error = ProgramError;
-
-    fn try_from(accounts: &'a [AccountInfo<'a>]) -> Result<Self, Self::Error> {
-        let account_info_iter = &mut accounts.iter();
-        let proposal = next_account_info(account_info_iter)?;
-        let authority = next_account_info(account_info_iter)?;
-        let system_program = next_account_info(account_info_iter)?;
+/// A proposal and its signing authority, for the handlers only the proposer can call.
+pub struct UpdateProposalContext<'a> {
+    pub proposal: &'a AccountInfo<'a>,
+    pub authority: &'a AccountInfo<'a>,
+}
 
-        Ok(CreateProposalContext {
-            proposal,
-            authority,
-            system_program,
-        })
-    }
+pub struct FinalizeProposalContext<'a> {
+    pub proposal: &'a AccountInfo<'a>,
 }
 
-pub struct UpdateProposalContext<'a> {
+pub struct InsertTransactionContext<'a> {
     pub proposal: &'a AccountInfo<'a>,
+    pub proposal_transaction: &'a AccountInfo<'a>,
     pub authority: &'a AccountInfo<'a>,
+    pub system_program: &'a AccountInfo<'a>,
+}
+
+pub struct ExecuteProposalContext<'a> {
+    pub proposal: &'a AccountInfo<'a>,
+    pub proposal_transaction: &'a AccountInfo<'a>,
+    pub governance_authority: &'a AccountInfo<'a>,
+}
+
+/// The treasury of a realm, paying out under its governance authority's
+/// signature. The asset being moved decides which accounts follow in
+/// `remaining`: `TreasurySolAccounts` or `TreasuryTokenAccounts`.
+pub struct TreasuryTransferContext<'a> {
+    pub governance_authority: &'a AccountInfo<'a>,
+    pub realm: &'a AccountInfo<'a>,
+    pub treasury: &'a AccountInfo<'a>,
+    pub remaining: &'a [AccountInfo<'a>],
+}
+
+pub struct TreasurySolAccounts<'a> {
+    pub destination: &'a AccountInfo<'a>,
+    pub system_program: &'a AccountInfo<'a>,
+}
+
+pub struct TreasuryTokenAccounts<'a> {
+    pub treasury_token_account: &'a AccountInfo<'a>,
+    pub destination_token_account: &'a AccountInfo<'a>,
+    pub token_program: &'a AccountInfo<'a>,
+}
+
+/// Accounts moving governing tokens into the vault, for deposits and locks.
+pub struct DepositGoverningTokensContext<'a> {
+    pub token_owner_record: &'a AccountInfo<'a>,
+    pub governing_token_vault: &'a AccountInfo<'a>,
+    pub governing_mint: &'a AccountInfo<'a>,
+    pub source_token_account: &'a AccountInfo<'a>,
+    pub owner: &'a AccountInfo<'a>,
+    pub token_program: &'a AccountInfo<'a>,
+    pub system_program: &'a AccountInfo<'a>,
+}
+
+/// Accounts moving governing tokens out of the vault back to their owner.
+pub struct WithdrawGoverningTokensContext<'a> {
+    pub token_owner_record: &'a AccountInfo<'a>,
+    pub governing_token_vault: &'a AccountInfo<'a>,
+    pub destination_token_account: &'a AccountInfo<'a>,
+    pub owner: &'a AccountInfo<'a>,
+    pub token_program: &'a AccountInfo<'a>,
+}
+
+/// A token owner record and its signing owner.
+pub struct TokenOwnerRecordContext<'a> {
+    pub token_owner_record: &'a AccountInfo<'a>,
+    pub owner: &'a AccountInfo<'a>,
 }
 
 pub struct VoteProposalContext<'a> {
     pub proposal: &'a AccountInfo<'a>,
+    pub vote_record: &'a AccountInfo<'a>,
     pub voter: &'a AccountInfo<'a>,
+    pub token_owner_record: &'a AccountInfo<'a>,
+    pub system_program: &'a AccountInfo<'a>,
+    /// The owner's membership and the community, for community realms
+    pub membership: Option<&'a AccountInfo<'a>>,
+    pub community: Option<&'a AccountInfo<'a>>,
+}
+
+pub struct ChangeVoteContext<'a> {
+    pub proposal: &'a AccountInfo<'a>,
+    pub vote_record: &'a AccountInfo<'a>,
+    pub voter: &'a AccountInfo<'a>,
+    pub token_owner_record: &'a AccountInfo<'a>,
+    /// The owner's membership and the community, for community realms
+    pub membership: Option<&'a AccountInfo<'a>>,
+    pub community: Option<&'a AccountInfo<'a>>,
+}
+
+pub struct RelinquishVoteContext<'a> {
+    pub proposal: &'a AccountInfo<'a>,
+    pub vote_record: &'a AccountInfo<'a>,
+    pub voter: &'a AccountInfo<'a>,
+    pub token_owner_record: &'a AccountInfo<'a>,
+}
+
+/// A realm-level account set by the realm's governance authority, created
+/// on first use with rent paid by the realm's treasury.
+pub struct RealmSettingContext<'a> {
+    pub setting: &'a AccountInfo<'a>,
+    pub governance_authority: &'a AccountInfo<'a>,
+    pub realm: &'a AccountInfo<'a>,
+    pub treasury: &'a AccountInfo<'a>,
+    pub system_program: &'a AccountInfo<'a>,
+}
+
+pub struct VetoProposalContext<'a> {
+    pub proposal: &'a AccountInfo<'a>,
+    pub veto_council: &'a AccountInfo<'a>,
+    pub council_members: &'a [AccountInfo<'a>],
+}
+
+pub struct ReleaseProposalDepositContext<'a> {
+    pub proposal: &'a AccountInfo<'a>,
+    pub token_owner_record: &'a AccountInfo<'a>,
+    pub governing_token_vault: &'a AccountInfo<'a>,
+    pub treasury_token_account: &'a AccountInfo<'a>,
+    pub token_program: &'a AccountInfo<'a>,
+}
+
+pub struct CreateRealmContext<'a> {
+    pub realm: &'a AccountInfo<'a>,
+    pub authority: &'a AccountInfo<'a>,
+    pub governing_mint: &'a AccountInfo<'a>,
+    pub system_program: &'a AccountInfo<'a>,
+    /// The community, for community realms
+    pub community: Option<&'a AccountInfo<'a>>,
+}
+
+pub struct SetGovernanceConfigContext<'a> {
+    pub realm: &'a AccountInfo<'a>,
+    pub governance_authority: &'a AccountInfo<'a>,
+}
+
+/// Checks `governance_authority` is the signing governance authority PDA of `realm`.
+pub fn check_governance_authority(program_id: &Pubkey, realm: &AccountInfo, governance_authority: &AccountInfo) -> ProgramResult {
+    let (governance_authority_key, _) = find_governance_authority(program_id, realm.key);
+    if governance_authority.key != &governance_authority_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    check_signer(governance_authority)
+}
+
+/// Checks `owner` signs for a token owner record they own.
+fn check_record_owner(program_id: &Pubkey, token_owner_record: &AccountInfo, owner: &AccountInfo) -> ProgramResult {
+    check_signer(owner)?;
+    check_program_owned(program_id, token_owner_record)?;
+    let token_owner_record_data = TokenOwnerRecord::unpack(&token_owner_record.data.borrow())?;
+    if token_owner_record_data.owner != *owner.key {
+        return Err(ProgramError::IllegalOwner);
+    }
+    Ok(())
 }
 
 impl<'a> CreateProposalContext<'a> {
     pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
         // Verify proposal account is not initialized
-        let proposal_data = Proposal::unpack_unchecked(&self.proposal.data.borrow())?;
-        if proposal_data.is_initialized {
-            return Err(ProgramError::AccountAlreadyInitialized);
-        }
-        
+        check_uninitialized::<Proposal>(program_id, self.proposal)?;
+
         // Verify authority is signer
-        if !self.authority.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
-        
+        check_signer(self.authority)?;
+
         // Verify system program
-        if self.system_program.key != &solana_program::system_program::id() {
-            return Err(ProgramError::IncorrectProgramId);
-        }
-        
-        Ok(())
+        check_system_program(self.system_program)?;
+
+        // Verify realm is owned by this program
+        check_program_owned(program_id, self.realm)
+    }
+}
+
+impl<'a> Accounts<'a> for CreateProposalContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = CreateProposalContext {
+            proposal: next_account_info(account_info_iter)?,
+            authority: next_account_info(account_info_iter)?,
+            system_program: next_account_info(account_info_iter)?,
+            realm: next_account_info(account_info_iter)?,
+            deposit_config: next_account_info(account_info_iter)?,
+            token_owner_record: next_account_info(account_info_iter)?,
+            membership: account_info_iter.next(),
+            community: account_info_iter.next(),
+        };
+        context.validate(program_id)?;
+        Ok(context)
     }
 }
 
 impl<'a> UpdateProposalContext<'a> {
     pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
         // Verify proposal account is initialized
-        let proposal_data = Proposal::unpack_unchecked(&self.proposal.data.borrow())?;
+        check_program_owned(program_id, self.proposal)?;
+        let proposal_data = Proposal::unpack(&self.proposal.data.borrow())?;
         if !proposal_data.is_initialized {
             return Err(ProgramError::UninitializedAccount);
         }
-        
+
         // Verify authority is signer
-        if !self.authority.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
-        
+        check_signer(self.authority)?;
+
         // Verify authority matches
         if proposal_data.authority != *self.authority.key {
             return Err(ProgramError::IllegalOwner);
         }
-        
+
         Ok(())
     }
 }
 
+impl<'a> Accounts<'a> for UpdateProposalContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = UpdateProposalContext {
+            proposal: next_account_info(account_info_iter)?,
+            authority: next_account_info(account_info_iter)?,
+        };
+        context.validate(program_id)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for FinalizeProposalContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = FinalizeProposalContext {
+            proposal: next_account_info(account_info_iter)?,
+        };
+        check_program_owned(program_id, context.proposal)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for InsertTransactionContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = InsertTransactionContext {
+            proposal: next_account_info(account_info_iter)?,
+            proposal_transaction: next_account_info(account_info_iter)?,
+            authority: next_account_info(account_info_iter)?,
+            system_program: next_account_info(account_info_iter)?,
+        };
+        UpdateProposalContext { proposal: context.proposal, authority: context.authority }.validate(program_id)?;
+        check_system_program(context.system_program)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for ExecuteProposalContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = ExecuteProposalContext {
+            proposal: next_account_info(account_info_iter)?,
+            proposal_transaction: next_account_info(account_info_iter)?,
+            governance_authority: next_account_info(account_info_iter)?,
+        };
+        check_program_owned(program_id, context.proposal)?;
+        check_program_owned(program_id, context.proposal_transaction)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for TreasuryTransferContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let governance_authority = next_account_info(account_info_iter)?;
+        let realm = next_account_info(account_info_iter)?;
+        let treasury = next_account_info(account_info_iter)?;
+        let context = TreasuryTransferContext {
+            governance_authority,
+            realm,
+            treasury,
+            remaining: account_info_iter.as_slice(),
+        };
+
+        // Verify governance authority signed
+        check_governance_authority(program_id, context.realm, context.governance_authority)?;
+
+        // Verify treasury address
+        let (treasury_key, _) = find_treasury_address(program_id, context.realm.key);
+        if context.treasury.key != &treasury_key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for TreasurySolAccounts<'a> {
+    fn try_accounts(_program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = TreasurySolAccounts {
+            destination: next_account_info(account_info_iter)?,
+            system_program: next_account_info(account_info_iter)?,
+        };
+        check_system_program(context.system_program)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for TreasuryTokenAccounts<'a> {
+    fn try_accounts(_program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = TreasuryTokenAccounts {
+            treasury_token_account: next_account_info(account_info_iter)?,
+            destination_token_account: next_account_info(account_info_iter)?,
+            token_program: next_account_info(account_info_iter)?,
+        };
+        check_token_program(context.token_program)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for DepositGoverningTokensContext<'a> {
+    fn try_accounts(_program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = DepositGoverningTokensContext {
+            token_owner_record: next_account_info(account_info_iter)?,
+            governing_token_vault: next_account_info(account_info_iter)?,
+            governing_mint: next_account_info(account_info_iter)?,
+            source_token_account: next_account_info(account_info_iter)?,
+            owner: next_account_info(account_info_iter)?,
+            token_program: next_account_info(account_info_iter)?,
+            system_program: next_account_info(account_info_iter)?,
+        };
+        check_signer(context.owner)?;
+        check_token_program(context.token_program)?;
+        check_system_program(context.system_program)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for WithdrawGoverningTokensContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = WithdrawGoverningTokensContext {
+            token_owner_record: next_account_info(account_info_iter)?,
+            governing_token_vault: next_account_info(account_info_iter)?,
+            destination_token_account: next_account_info(account_info_iter)?,
+            owner: next_account_info(account_info_iter)?,
+            token_program: next_account_info(account_info_iter)?,
+        };
+        check_token_program(context.token_program)?;
+        check_record_owner(program_id, context.token_owner_record, context.owner)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for TokenOwnerRecordContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = TokenOwnerRecordContext {
+            token_owner_record: next_account_info(account_info_iter)?,
+            owner: next_account_info(account_info_iter)?,
+        };
+        check_record_owner(program_id, context.token_owner_record, context.owner)?;
+        Ok(context)
+    }
+}
+
 impl<'a> VoteProposalContext<'a> {
     pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
         // Verify proposal account is initialized
-        let proposal_data = Proposal::unpack_unchecked(&self.proposal.data.borrow())?;
+        check_program_owned(program_id, self.proposal)?;
+        let proposal_data = Proposal::unpack(&self.proposal.data.borrow())?;
         if !proposal_data.is_initialized {
             return Err(ProgramError::UninitializedAccount);
         }
-        
+
         // Verify voter is signer
-        if !self.voter.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
+        check_signer(self.voter)?;
+
+        // Verify system program
+        check_system_program(self.system_program)
+    }
+}
+
+impl<'a> Accounts<'a> for VoteProposalContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = VoteProposalContext {
+            proposal: next_account_info(account_info_iter)?,
+            vote_record: next_account_info(account_info_iter)?,
+            voter: next_account_info(account_info_iter)?,
+            token_owner_record: next_account_info(account_info_iter)?,
+            system_program: next_account_info(account_info_iter)?,
+            membership: account_info_iter.next(),
+            community: account_info_iter.next(),
+        };
+        context.validate(program_id)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for ChangeVoteContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = ChangeVoteContext {
+            proposal: next_account_info(account_info_iter)?,
+            vote_record: next_account_info(account_info_iter)?,
+            voter: next_account_info(account_info_iter)?,
+            token_owner_record: next_account_info(account_info_iter)?,
+            membership: account_info_iter.next(),
+            community: account_info_iter.next(),
+        };
+        check_signer(context.voter)?;
+        check_program_owned(program_id, context.proposal)?;
+        check_program_owned(program_id, context.vote_record)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for RelinquishVoteContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = RelinquishVoteContext {
+            proposal: next_account_info(account_info_iter)?,
+            vote_record: next_account_info(account_info_iter)?,
+            voter: next_account_info(account_info_iter)?,
+            token_owner_record: next_account_info(account_info_iter)?,
+        };
+        check_signer(context.voter)?;
+        check_program_owned(program_id, context.proposal)?;
+        check_program_owned(program_id, context.vote_record)?;
+        check_program_owned(program_id, context.token_owner_record)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for RealmSettingContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = RealmSettingContext {
+            setting: next_account_info(account_info_iter)?,
+            governance_authority: next_account_info(account_info_iter)?,
+            realm: next_account_info(account_info_iter)?,
+            treasury: next_account_info(account_info_iter)?,
+            system_program: next_account_info(account_info_iter)?,
+        };
+        check_governance_authority(program_id, context.realm, context.governance_authority)?;
+        check_system_program(context.system_program)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for VetoProposalContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let proposal = next_account_info(account_info_iter)?;
+        let veto_council = next_account_info(account_info_iter)?;
+        check_program_owned(program_id, proposal)?;
+        Ok(VetoProposalContext {
+            proposal,
+            veto_council,
+            council_members: account_info_iter.as_slice(),
+        })
+    }
+}
+
+impl<'a> Accounts<'a> for ReleaseProposalDepositContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = ReleaseProposalDepositContext {
+            proposal: next_account_info(account_info_iter)?,
+            token_owner_record: next_account_info(account_info_iter)?,
+            governing_token_vault: next_account_info(account_info_iter)?,
+            treasury_token_account: next_account_info(account_info_iter)?,
+            token_program: next_account_info(account_info_iter)?,
+        };
+        check_program_owned(program_id, context.proposal)?;
+        check_token_program(context.token_program)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for CreateRealmContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = CreateRealmContext {
+            realm: next_account_info(account_info_iter)?,
+            authority: next_account_info(account_info_iter)?,
+            governing_mint: next_account_info(account_info_iter)?,
+            system_program: next_account_info(account_info_iter)?,
+            community: account_info_iter.next(),
+        };
+
+        // Verify realm is not created yet; the handler creates it at its PDA
+        if context.realm.owner == program_id || Realm::is_initialized_account(&context.realm.data.borrow()) {
+            return Err(ProgramError::AccountAlreadyInitialized);
         }
-        
-        Ok(())
+
+        // Verify the community scoping the realm, if any, is owned by this program
+        if let Some(community) = context.community {
+            check_program_owned(program_id, community)?;
+        }
+
+        check_signer(context.authority)?;
+        check_system_program(context.system_program)?;
+
+        // Verify governing mint is an SPL token mint
+        if context.governing_mint.owner != &spl_token::id() {
+            return Err(ProgramError::IncorrectProgramId);
+        }
+
+        Ok(context)
     }
-} 
\ No newline at end of file
+}
+
+impl<'a> Accounts<'a> for SetGovernanceConfigContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = SetGovernanceConfigContext {
+            realm: next_account_info(account_info_iter)?,
+            governance_authority: next_account_info(account_info_iter)?,
+        };
+        check_program_owned(program_id, context.realm)?;
+        check_governance_authority(program_id, context.realm, context.governance_authority)?;
+        Ok(context)
+    }
+}
@@ -0,0 +1,218 @@
+use borsh::{BorshSerialize, BorshDeserialize};
+use solana_program::program_error::ProgramError;
+use crate::error::UnityVaultError;
+use crate::governance::tally::BASIS_POINTS;
+
+pub const MIN_LOCK_DURATION: i64 = 7 * 24 * 60 * 60;
+pub const MAX_LOCK_DURATION: i64 = 4 * 365 * 24 * 60 * 60;
+/// Extra voting power of a lock with `MAX_LOCK_DURATION` left: 4x in total.
+pub const MAX_LOCK_BOOST_BPS: u64 = 30_000;
+
+/// Tokens locked until `lock_end`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct Lock {
+    pub amount: u64,
+    pub lock_end: i64,
+}
+
+impl Lock {
+    pub const LEN: usize = 8 + 8;
+
+    /// The locked amount plus a boost proportional to the time left, so
+    /// power decays linearly to the plain amount as the lock runs out.
+    pub fn voting_power(&self, at: i64) -> u64 {
+        let remaining = self.lock_end.saturating_sub(at).clamp(0, MAX_LOCK_DURATION) as u128;
+        let amount = self.amount as u128;
+        let boost = amount * MAX_LOCK_BOOST_BPS as u128 * remaining / (MAX_LOCK_DURATION as u128 * BASIS_POINTS as u128);
+        u64::try_from(amount + boost).unwrap_or(u64::MAX)
+    }
+}
+
+/// A holder's time-locked governing tokens.
+///
+/// Votes read power at a proposal's snapshot time, so the lock as it stood
+/// before its latest change is kept too. A lock changed twice since a
+/// snapshot has no power for that proposal, and power never exceeds what is
+/// still locked, so withdrawn tokens can not vote again elsewhere.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct VoteEscrow {
+    pub lock: Lock,
+    pub updated_at: i64,
+    pub previous_lock: Lock,
+    pub previous_updated_at: i64,
+}
+
+impl VoteEscrow {
+    pub const LEN: usize = Lock::LEN + 8 + Lock::LEN + 8;
+
+    pub fn is_locked(&self, now: i64) -> bool {
+        self.lock.amount > 0 && now < self.lock.lock_end
+    }
+
+    /// Voting power at `snapshot`, given the lock's history up to now.
+    pub fn voting_power_at(&self, snapshot: i64) -> u64 {
+        let lock = if self.updated_at <= snapshot {
+            self.lock
+        } else if self.previous_updated_at <= snapshot {
+            self.previous_lock
+        } else {
+            return 0;
+        };
+        Lock {
+            amount: lock.amount.min(self.lock.amount),
+            lock_end: lock.lock_end,
+        }
+        .voting_power(snapshot)
+    }
+
+    /// Keeps the lock as it stood before the first change at `now`.
+    fn checkpoint(&mut self, now: i64) {
+        if self.updated_at < now {
+            self.previous_lock = self.lock;
+            self.previous_updated_at = self.updated_at;
+        }
+        self.updated_at = now;
+    }
+
+    pub fn lock(&mut self, amount: u64, duration: i64, now: i64) -> Result<(), ProgramError> {
+        if self.lock.amount > 0 {
+            return Err(UnityVaultError::GoverningTokensLocked.into());
+        }
+        if amount == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        if !(MIN_LOCK_DURATION..=MAX_LOCK_DURATION).contains(&duration) {
+            return Err(UnityVaultError::InvalidLockDuration.into());
+        }
+        self.checkpoint(now);
+        self.lock = Lock { amount, lock_end: now + duration };
+        Ok(())
+    }
+
+    /// Adds tokens to a running lock without changing its end.
+    pub fn increase(&mut self, amount: u64, now: i64) -> Result<(), ProgramError> {
+        if !self.is_locked(now) {
+            return Err(UnityVaultError::NoActiveLock.into());
+        }
+        if amount == 0 {
+            return Err(ProgramError::InvalidArgument);
+        }
+        let total = self.lock.amount.checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)?;
+        self.checkpoint(now);
+        self.lock.amount = total;
+        Ok(())
+    }
+
+    /// Moves the end of a running lock to `duration` from now, which must be later.
+    pub fn extend(&mut self, duration: i64, now: i64) -> Result<(), ProgramError> {
+        if !self.is_locked(now) {
+            return Err(UnityVaultError::NoActiveLock.into());
+        }
+        let lock_end = now.saturating_add(duration);
+        if duration > MAX_LOCK_DURATION || lock_end <= self.lock.lock_end {
+            return Err(UnityVaultError::InvalidLockDuration.into());
+        }
+        self.checkpoint(now);
+        self.lock.lock_end = lock_end;
+        Ok(())
+    }
+
+    /// Releases an expired lock, returning the amount to pay out.
+    pub fn withdraw(&mut self, now: i64) -> Result<u64, ProgramError> {
+        if self.is_locked(now) {
+            return Err(UnityVaultError::GoverningTokensLocked.into());
+        }
+        let amount = self.lock.amount;
+        if amount == 0 {
+            return Err(UnityVaultError::NoActiveLock.into());
+        }
+        self.checkpoint(now);
+        self.lock = Lock::default();
+        Ok(amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    #[test]
+    fn test_power_decays_to_locked_amount() {
+        let lock = Lock { amount: 1_000, lock_end: MAX_LOCK_DURATION };
+        assert_eq!(lock.voting_power(0), 4_000);
+        assert_eq!(lock.voting_power(MAX_LOCK_DURATION / 2), 2_500);
+        assert_eq!(lock.voting_power(MAX_LOCK_DURATION), 1_000);
+        assert_eq!(lock.voting_power(MAX_LOCK_DURATION + 1), 1_000);
+
+        // Power never increases as time passes
+        let mut last = u64::MAX;
+        for at in (0..=MAX_LOCK_DURATION).step_by(DAY as usize * 10) {
+            let power = lock.voting_power(at);
+            assert!(power <= last);
+            last = power;
+        }
+
+        assert_eq!(Lock { amount: u64::MAX, lock_end: MAX_LOCK_DURATION }.voting_power(0), u64::MAX);
+    }
+
+    #[test]
+    fn test_lock_lifecycle() {
+        let mut escrow = VoteEscrow::default();
+        assert_eq!(escrow.lock(100, MIN_LOCK_DURATION - 1, 0), Err(UnityVaultError::InvalidLockDuration.into()));
+        assert_eq!(escrow.lock(100, MAX_LOCK_DURATION + 1, 0), Err(UnityVaultError::InvalidLockDuration.into()));
+        assert_eq!(escrow.increase(1, 0), Err(UnityVaultError::NoActiveLock.into()));
+
+        escrow.lock(100, 30 * DAY, 0).unwrap();
+        assert_eq!(escrow.lock(100, 30 * DAY, 0), Err(UnityVaultError::GoverningTokensLocked.into()));
+        assert_eq!(escrow.withdraw(DAY), Err(UnityVaultError::GoverningTokensLocked.into()));
+
+        escrow.increase(50, DAY).unwrap();
+        assert_eq!(escrow.lock, Lock { amount: 150, lock_end: 30 * DAY });
+
+        // Extending must push the end further out
+        assert_eq!(escrow.extend(28 * DAY, 2 * DAY), Err(UnityVaultError::InvalidLockDuration.into()));
+        escrow.extend(60 * DAY, 2 * DAY).unwrap();
+        assert_eq!(escrow.lock.lock_end, 62 * DAY);
+
+        assert_eq!(escrow.withdraw(62 * DAY), Ok(150));
+        assert_eq!(escrow.lock, Lock::default());
+        assert_eq!(escrow.withdraw(62 * DAY), Err(UnityVaultError::NoActiveLock.into()));
+
+        // An expired lock must be withdrawn before locking again
+        escrow.lock(10, 30 * DAY, 63 * DAY).unwrap();
+        assert_eq!(escrow.extend(MIN_LOCK_DURATION, 93 * DAY), Err(UnityVaultError::NoActiveLock.into()));
+    }
+
+    #[test]
+    fn test_power_at_snapshot_ignores_later_changes() {
+        let mut escrow = VoteEscrow::default();
+        escrow.lock(1_000, MAX_LOCK_DURATION, 100).unwrap();
+        assert_eq!(escrow.voting_power_at(99), 0);
+        assert_eq!(escrow.voting_power_at(100), 4_000);
+
+        // Increasing after the snapshot does not add power to it
+        escrow.increase(1_000, 200).unwrap();
+        assert_eq!(escrow.voting_power_at(150), Lock { amount: 1_000, lock_end: MAX_LOCK_DURATION + 100 }.voting_power(150));
+        assert_eq!(escrow.voting_power_at(200), Lock { amount: 2_000, lock_end: MAX_LOCK_DURATION + 100 }.voting_power(200));
+
+        // Several changes in one transaction keep the same checkpoint
+        escrow.extend(MAX_LOCK_DURATION, 300).unwrap();
+        escrow.increase(1, 300).unwrap();
+        assert_eq!(escrow.previous_updated_at, 200);
+        assert_eq!(escrow.previous_lock.amount, 2_000);
+
+        // A lock changed twice since the snapshot has no power for it
+        assert_eq!(escrow.voting_power_at(150), 0);
+    }
+
+    #[test]
+    fn test_withdrawn_tokens_lose_power() {
+        let mut escrow = VoteEscrow::default();
+        escrow.lock(1_000, MIN_LOCK_DURATION, 0).unwrap();
+        escrow.withdraw(MIN_LOCK_DURATION).unwrap();
+        // The lock was running at the snapshot, but the tokens are gone
+        assert_eq!(escrow.voting_power_at(MIN_LOCK_DURATION - 1), 0);
+    }
+}
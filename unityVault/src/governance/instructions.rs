@@ -1,142 +1,1206 @@
 use solana_program::{
-    account_info::{next_account_info, AccountInfo},
+    account_info::AccountInfo,
     clock::Clock,
     entrypoint::ProgramResult,
+    program::{invoke, invoke_signed},
+    system_instruction,
     program_error::ProgramError,
     program_pack::Pack,
     pubkey::Pubkey,
+    rent::Rent,
     sysvar::Sysvar,
 };
-use crate::governance::state::{Proposal, ProposalParams, ProposalStatus, VoteType};
-use crate::governance::context::{CreateProposalContext, UpdateProposalContext, VoteProposalContext};
+use spl_token::instruction as token_instruction;
+use crate::account::{create_pda_account, AccountState};
+use crate::error::UnityVaultError;
+use crate::governance::amendment::check_amendment;
+use crate::governance::escrow::VoteEscrow;
+use crate::governance::treasury::{find_treasury_address, find_treasury_token_account, TreasuryAsset, TREASURY_SEED};
+use crate::governance::state::{
+    find_governance_authority, GovernanceConfig, Proposal, ProposalDepositConfig, ProposalInstruction,
+    ProposalParams, ProposalStatus, ProposalTransaction, Realm, RealmScope, TokenOwnerRecord, VetoCouncil,
+    VoteMode, VoteRecord, VoteType, GOVERNANCE_AUTHORITY_SEED, GOVERNING_VAULT_SEED, PROPOSAL_DEPOSIT_CONFIG_SEED,
+    PROPOSAL_TRANSACTION_SEED, REALM_SEED, TOKEN_OWNER_RECORD_SEED, VETO_COUNCIL_SEED, VOTE_RECORD_SEED,
+};
+use crate::community::state::{Community, Membership};
+use crate::governance::context::{
+    ChangeVoteContext, CreateProposalContext, CreateRealmContext, DepositGoverningTokensContext, ExecuteProposalContext,
+    FinalizeProposalContext, InsertTransactionContext, RealmSettingContext, RelinquishVoteContext,
+    ReleaseProposalDepositContext, SetGovernanceConfigContext, TokenOwnerRecordContext, TreasurySolAccounts,
+    TreasuryTokenAccounts, TreasuryTransferContext, UpdateProposalContext, VetoProposalContext, VoteProposalContext,
+    WithdrawGoverningTokensContext,
+};
+use crate::context::Accounts;
 
-pub fn create_proposal(
-    _program_id: &Pubkey,
-    accounts: &[AccountInfo],
+pub fn create_proposal<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
     params: ProposalParams,
 ) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
+    params.validate()?;
+    
+    let CreateProposalContext { proposal, authority, realm, deposit_config, token_owner_record, membership, community, .. } =
+        CreateProposalContext::try_accounts(program_id, accounts)?;
     
-    let proposal = next_account_info(account_info_iter)?;
-    let authority = next_account_info(account_info_iter)?;
-    let system_program = next_account_info(account_info_iter)?;
+    let realm_data = Realm::unpack(&realm.data.borrow())?;
+    let governing_mint = realm_data.governing_mint;
     
-    // Verify authority is signer
-    if !authority.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
+    // Community realms only take proposals from members
+    check_realm_member(program_id, &realm_data.scope, membership, community, authority.key)?;
+    
+    // Verify deposit config address; it is only an account once governance has set it
+    let (deposit_config_key, _) = ProposalDepositConfig::find_address(program_id, realm.key);
+    if deposit_config.key != &deposit_config_key {
+        return Err(ProgramError::InvalidSeeds);
     }
+    let deposit_config_data = if deposit_config.owner == program_id {
+        ProposalDepositConfig::unpack(&deposit_config.data.borrow())?
+    } else {
+        ProposalDepositConfig::default()
+    };
     
-    // Verify system program
-    if system_program.key != &solana_program::system_program::id() {
-        return Err(ProgramError::IncorrectProgramId);
+    // Take the proposer's deposit from their deposited governing tokens
+    let (token_owner_record_key, _) = TokenOwnerRecord::find_address(program_id, &governing_mint, authority.key);
+    if token_owner_record.key != &token_owner_record_key {
+        return Err(ProgramError::InvalidSeeds);
     }
+    let deposit_amount = if token_owner_record.owner == program_id {
+        let mut token_owner_record_data = TokenOwnerRecord::unpack(&token_owner_record.data.borrow())?;
+        let deposit_amount = deposit_config_data.charge_proposer(&mut token_owner_record_data)?;
+        token_owner_record_data.pack(&mut token_owner_record.data.borrow_mut())?;
+        deposit_amount
+    } else {
+        // Without deposited tokens only an unset config lets the proposer through
+        deposit_config_data.charge_proposer(&mut TokenOwnerRecord::default())?
+    };
     
-    // Create and initialize proposal
+    // Create and initialize proposal with the realm's voting rules
     let mut proposal_data = Proposal {
         is_initialized: true,
         authority: *authority.key,
+        realm: *realm.key,
+        scope: realm_data.scope,
+        governing_mint,
         title: params.title,
         description: params.description,
         status: ProposalStatus::Draft,
-        voting_duration: params.voting_duration,
-        min_votes: params.min_votes,
-        min_approval_percentage: params.min_approval_percentage,
+        voting_duration: 0,
+        min_votes: 0,
+        min_approval_percentage: 0,
         yes_votes: 0,
         no_votes: 0,
         abstain_votes: 0,
+        vote_mode: params.vote_mode,
+        option_votes: vec![0; params.options.len()],
+        options: params.options,
+        option_voter_weight: 0,
+        winning_option: None,
+        voting_started_at: 0,
+        voting_completed_at: 0,
+        execution_delay: 0,
+        transaction_count: 0,
+        executed_transaction_count: 0,
+        transactions_hash: [0; 32],
+        content_hash: [0; 32],
+        deposit_amount,
+        vetoed_by: Vec::new(),
         created_at: Clock::get()?.unix_timestamp,
         updated_at: Clock::get()?.unix_timestamp,
         executed_at: 0,
     };
+    proposal_data.apply_config(&realm_data.config);
     
     // Pack the data into the account
-    proposal_data.pack_into_slice(&mut proposal.data.borrow_mut());
+    proposal_data.pack(&mut proposal.data.borrow_mut())?;
     
     Ok(())
 }
 
-pub fn update_proposal(
-    _program_id: &Pubkey,
-    accounts: &[AccountInfo],
+pub fn update_proposal<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
     params: ProposalParams,
 ) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
+    params.validate()?;
+    
+    let UpdateProposalContext { proposal, .. } = UpdateProposalContext::try_accounts(program_id, accounts)?;
     
-    let proposal = next_account_info(account_info_iter)?;
-    let authority = next_account_info(account_info_iter)?;
+    let mut proposal_data = Proposal::unpack(&proposal.data.borrow())?;
     
-    // Verify authority is signer
-    if !authority.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
+    // Parameters are locked once voting starts
+    if proposal_data.status != ProposalStatus::Draft {
+        return Err(ProgramError::InvalidAccountData);
     }
     
-    // Verify authority matches
-    let mut proposal_data = Proposal::unpack_from_slice(&proposal.data.borrow())?;
-    if proposal_data.authority != *authority.key {
-        return Err(ProgramError::IllegalOwner);
+    // A draft with transactions attached can't become a poll
+    if params.vote_mode != VoteMode::YesNo && proposal_data.transaction_count > 0 {
+        return Err(UnityVaultError::InvalidProposalParams.into());
     }
     
     // Update proposal
     proposal_data.title = params.title;
     proposal_data.description = params.description;
-    proposal_data.voting_duration = params.voting_duration;
-    proposal_data.min_votes = params.min_votes;
-    proposal_data.min_approval_percentage = params.min_approval_percentage;
+    proposal_data.vote_mode = params.vote_mode;
+    proposal_data.option_votes = vec![0; params.options.len()];
+    proposal_data.options = params.options;
     proposal_data.updated_at = Clock::get()?.unix_timestamp;
     
     // Pack the updated data
-    proposal_data.pack_into_slice(&mut proposal.data.borrow_mut());
+    proposal_data.pack(&mut proposal.data.borrow_mut())?;
     
     Ok(())
 }
 
-pub fn vote_proposal(
-    _program_id: &Pubkey,
-    accounts: &[AccountInfo],
-    vote_type: VoteType,
+pub fn activate_proposal<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
 ) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
+    let UpdateProposalContext { proposal, .. } = UpdateProposalContext::try_accounts(program_id, accounts)?;
     
-    let proposal = next_account_info(account_info_iter)?;
-    let voter = next_account_info(account_info_iter)?;
+    let mut proposal_data = Proposal::unpack(&proposal.data.borrow())?;
     
-    // Verify voter is signer
-    if !voter.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
+    // Only drafts can be opened for voting
+    if proposal_data.status != ProposalStatus::Draft {
+        return Err(ProgramError::InvalidAccountData);
     }
     
-    // Get proposal data
-    let mut proposal_data = Proposal::unpack_from_slice(&proposal.data.borrow())?;
+    let current_time = Clock::get()?.unix_timestamp;
+    proposal_data.status = ProposalStatus::Active;
+    proposal_data.voting_started_at = current_time;
+    proposal_data.content_hash = proposal_data.compute_content_hash();
+    proposal_data.updated_at = current_time;
+    
+    proposal_data.pack(&mut proposal.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+pub fn cancel_proposal<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let UpdateProposalContext { proposal, .. } = UpdateProposalContext::try_accounts(program_id, accounts)?;
     
-    // Verify proposal is active
+    let mut proposal_data = Proposal::unpack(&proposal.data.borrow())?;
+    
+    // Decided proposals can no longer be cancelled
+    if !matches!(proposal_data.status, ProposalStatus::Draft | ProposalStatus::Active) {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    
+    proposal_data.status = ProposalStatus::Cancelled;
+    proposal_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    proposal_data.pack(&mut proposal.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+pub fn finalize_proposal<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let FinalizeProposalContext { proposal } = FinalizeProposalContext::try_accounts(program_id, accounts)?;
+    
+    let mut proposal_data = Proposal::unpack(&proposal.data.borrow())?;
     if proposal_data.status != ProposalStatus::Active {
         return Err(ProgramError::InvalidAccountData);
     }
     
-    // Update vote counts
-    match vote_type {
-        VoteType::Yes => proposal_data.yes_votes += 1,
-        VoteType::No => proposal_data.no_votes += 1,
-        VoteType::Abstain => proposal_data.abstain_votes += 1,
+    // Anyone can finalize, but only once the voting window has closed
+    let current_time = Clock::get()?.unix_timestamp;
+    if current_time < proposal_data.voting_ends_at() {
+        return Err(UnityVaultError::VotingStillOpen.into());
+    }
+    
+    let result = proposal_data.tally();
+    proposal_data.status = if result.passed {
+        proposal_data.winning_option = result.winning_option;
+        ProposalStatus::Passed
+    } else {
+        ProposalStatus::Rejected
+    };
+    proposal_data.voting_completed_at = current_time;
+    proposal_data.updated_at = current_time;
+    
+    proposal_data.pack(&mut proposal.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+pub fn insert_transaction<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    instruction: ProposalInstruction,
+) -> ProgramResult {
+    instruction.validate()?;
+    
+    let InsertTransactionContext { proposal, proposal_transaction, authority, system_program } =
+        InsertTransactionContext::try_accounts(program_id, accounts)?;
+    
+    let mut proposal_data = Proposal::unpack(&proposal.data.borrow())?;
+    
+    // Instructions are fixed before voting starts
+    if proposal_data.status != ProposalStatus::Draft {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    
+    // Multiple-choice proposals are polls and execute nothing
+    if proposal_data.vote_mode != VoteMode::YesNo {
+        return Err(UnityVaultError::InvalidProposalParams.into());
+    }
+    
+    // Verify transaction address for the next index
+    let index = proposal_data.transaction_count;
+    let (proposal_transaction_key, bump) = ProposalTransaction::find_address(program_id, proposal.key, index);
+    if proposal_transaction.key != &proposal_transaction_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    create_pda_account(
+        program_id,
+        authority,
+        proposal_transaction,
+        system_program,
+        ProposalTransaction::LEN,
+        &[PROPOSAL_TRANSACTION_SEED, proposal.key.as_ref(), &index.to_le_bytes(), &[bump]],
+    )?;
+    
+    let proposal_transaction_data = ProposalTransaction {
+        is_initialized: true,
+        proposal: *proposal.key,
+        index,
+        instruction,
+        bump,
+        executed_at: 0,
+    };
+    
+    proposal_data.record_transaction(&proposal_transaction_data.instruction)?;
+    proposal_data.transaction_count = index.checked_add(1).ok_or(ProgramError::ArithmeticOverflow)?;
+    proposal_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    proposal_transaction_data.pack(&mut proposal_transaction.data.borrow_mut())?;
+    proposal_data.pack(&mut proposal.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Executes one transaction of a passed proposal once its timelock has
+/// elapsed, signed by the governance authority of the proposal's mint.
+///
+/// Accounts expected:
+/// 0. `[writable]` The proposal
+/// 1. `[writable]` The proposal transaction
+/// 2. `[]` The governance authority PDA
+/// 3. ..  Every account the transaction's instruction references
+pub fn execute_proposal<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let ExecuteProposalContext { proposal, proposal_transaction, governance_authority } =
+        ExecuteProposalContext::try_accounts(program_id, accounts)?;
+    
+    let mut proposal_data = Proposal::unpack(&proposal.data.borrow())?;
+    let mut proposal_transaction_data = ProposalTransaction::unpack(&proposal_transaction.data.borrow())?;
+    
+    // Verify the transaction belongs to the proposal
+    if proposal_transaction_data.proposal != *proposal.key {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if proposal_transaction_data.is_executed() {
+        return Err(UnityVaultError::TransactionAlreadyExecuted.into());
     }
     
-    // Update proposal status if voting period has ended
+    // Verify the proposal passed and its timelock has elapsed
     let current_time = Clock::get()?.unix_timestamp;
-    if current_time >= proposal_data.created_at + proposal_data.voting_duration {
-        let total_votes = proposal_data.yes_votes + proposal_data.no_votes + proposal_data.abstain_votes;
-        let approval_percentage = (proposal_data.yes_votes as f64 / total_votes as f64) * 100.0;
-        
-        if total_votes >= proposal_data.min_votes && approval_percentage >= proposal_data.min_approval_percentage as f64 {
-            proposal_data.status = ProposalStatus::Passed;
-        } else {
-            proposal_data.status = ProposalStatus::Rejected;
+    if !proposal_data.is_executable(current_time) {
+        return Err(UnityVaultError::ProposalNotExecutable.into());
+    }
+    
+    // Verify governance authority address
+    let realm = proposal_data.realm;
+    let (governance_authority_key, bump) = find_governance_authority(program_id, &realm);
+    if governance_authority.key != &governance_authority_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    // Record execution before invoking so the transaction can not run twice
+    proposal_transaction_data.executed_at = current_time;
+    proposal_data.executed_transaction_count += 1;
+    if proposal_data.executed_transaction_count == proposal_data.transaction_count {
+        proposal_data.status = ProposalStatus::Executed;
+        proposal_data.executed_at = current_time;
+    }
+    proposal_data.updated_at = current_time;
+    
+    proposal_transaction_data.pack(&mut proposal_transaction.data.borrow_mut())?;
+    proposal_data.pack(&mut proposal.data.borrow_mut())?;
+    
+    invoke_signed(
+        &proposal_transaction_data.instruction.to_instruction(),
+        accounts,
+        &[&[GOVERNANCE_AUTHORITY_SEED, realm.as_ref(), &[bump]]],
+    )?;
+    
+    Ok(())
+}
+
+/// Pays out of the governance treasury. The governance authority must sign,
+/// which only `execute_proposal` can do.
+///
+/// Accounts expected:
+/// 0. `[signer]` The governance authority PDA
+/// 1. `[]` The realm
+/// 2. `[writable]` The treasury PDA (read-only for tokens)
+///
+/// For `TreasuryAsset::Sol`:
+/// 3. `[writable]` The destination
+/// 4. `[]` System program
+///
+/// For `TreasuryAsset::Token`:
+/// 3. `[writable]` The treasury's token account
+/// 4. `[writable]` The destination token account
+/// 5. `[]` Token program
+pub fn treasury_transfer<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    asset: TreasuryAsset,
+    amount: u64,
+) -> ProgramResult {
+    let TreasuryTransferContext { realm, treasury, remaining, .. } = TreasuryTransferContext::try_accounts(program_id, accounts)?;
+    
+    let (treasury_key, treasury_bump) = find_treasury_address(program_id, realm.key);
+    let treasury_seeds: &[&[u8]] = &[TREASURY_SEED, realm.key.as_ref(), &[treasury_bump]];
+    
+    match asset {
+        TreasuryAsset::Sol => {
+            let TreasurySolAccounts { destination, system_program } = TreasurySolAccounts::try_accounts(program_id, remaining)?;
+            
+            invoke_signed(
+                &system_instruction::transfer(&treasury_key, destination.key, amount),
+                &[treasury.clone(), destination.clone(), system_program.clone()],
+                &[treasury_seeds],
+            )?;
+        }
+        TreasuryAsset::Token { mint } => {
+            let TreasuryTokenAccounts { treasury_token_account, destination_token_account, token_program } =
+                TreasuryTokenAccounts::try_accounts(program_id, remaining)?;
+            
+            // Verify the source holds the requested mint for the treasury
+            let source = spl_token::state::Account::unpack(&treasury_token_account.data.borrow())?;
+            if source.owner != treasury_key || source.mint != mint {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            
+            invoke_signed(
+                &token_instruction::transfer(
+                    token_program.key,
+                    treasury_token_account.key,
+                    destination_token_account.key,
+                    &treasury_key,
+                    &[],
+                    amount,
+                )?,
+                &[
+                    treasury_token_account.clone(),
+                    destination_token_account.clone(),
+                    treasury.clone(),
+                    token_program.clone(),
+                ],
+                &[treasury_seeds],
+            )?;
+        }
+    }
+    
+    Ok(())
+}
+
+pub fn deposit_governing_tokens<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    amount: u64,
+) -> ProgramResult {
+    receive_governing_tokens(program_id, accounts, amount, |token_owner_record_data, _| {
+        token_owner_record_data.deposit(amount)
+    })
+}
+
+/// Locks `amount` governing tokens in the vote escrow for `duration` seconds.
+/// Takes the same accounts as `deposit_governing_tokens`.
+pub fn lock_governing_tokens<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    amount: u64,
+    duration: i64,
+) -> ProgramResult {
+    receive_governing_tokens(program_id, accounts, amount, |token_owner_record_data, current_time| {
+        token_owner_record_data.vote_escrow.lock(amount, duration, current_time)
+    })
+}
+
+/// Adds `amount` governing tokens to a running lock. Takes the same accounts
+/// as `deposit_governing_tokens`.
+pub fn increase_lock<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    amount: u64,
+) -> ProgramResult {
+    receive_governing_tokens(program_id, accounts, amount, |token_owner_record_data, current_time| {
+        token_owner_record_data.vote_escrow.increase(amount, current_time)
+    })
+}
+
+/// Moves `amount` governing tokens from the holder into the vault, creating
+/// the vault and their record on first use, and books them with `update`.
+fn receive_governing_tokens<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    amount: u64,
+    update: impl FnOnce(&mut TokenOwnerRecord, i64) -> ProgramResult,
+) -> ProgramResult {
+    let DepositGoverningTokensContext {
+        token_owner_record,
+        governing_token_vault,
+        governing_mint,
+        source_token_account,
+        owner,
+        token_program,
+        system_program,
+    } = DepositGoverningTokensContext::try_accounts(program_id, accounts)?;
+    
+    if amount == 0 {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    // Verify derived addresses
+    let (vault_key, vault_bump) = TokenOwnerRecord::find_governing_vault(program_id, governing_mint.key);
+    if governing_token_vault.key != &vault_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let (record_key, record_bump) = TokenOwnerRecord::find_address(program_id, governing_mint.key, owner.key);
+    if token_owner_record.key != &record_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    // The first deposit of a mint creates its vault
+    if governing_token_vault.data_is_empty() {
+        create_pda_account(
+            &spl_token::id(),
+            owner,
+            governing_token_vault,
+            system_program,
+            spl_token::state::Account::LEN,
+            &[GOVERNING_VAULT_SEED, governing_mint.key.as_ref(), &[vault_bump]],
+        )?;
+        invoke(
+            &token_instruction::initialize_account3(token_program.key, &vault_key, governing_mint.key, &vault_key)?,
+            &[governing_token_vault.clone(), governing_mint.clone(), token_program.clone()],
+        )?;
+    }
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    
+    // The first deposit of a holder creates their record
+    let mut token_owner_record_data = if TokenOwnerRecord::is_initialized_account(&token_owner_record.data.borrow()) {
+        TokenOwnerRecord::unpack(&token_owner_record.data.borrow())?
+    } else {
+        create_pda_account(
+            program_id,
+            owner,
+            token_owner_record,
+            system_program,
+            TokenOwnerRecord::LEN,
+            &[TOKEN_OWNER_RECORD_SEED, governing_mint.key.as_ref(), owner.key.as_ref(), &[record_bump]],
+        )?;
+        TokenOwnerRecord {
+            is_initialized: true,
+            governing_mint: *governing_mint.key,
+            owner: *owner.key,
+            delegate: Pubkey::default(),
+            deposited_amount: 0,
+            active_votes: 0,
+            vote_escrow: VoteEscrow::default(),
+            bump: record_bump,
+            created_at: current_time,
+            updated_at: current_time,
         }
+    };
+    
+    update(&mut token_owner_record_data, current_time)?;
+    token_owner_record_data.updated_at = current_time;
+    
+    // Move the tokens into the vault
+    invoke(
+        &token_instruction::transfer(token_program.key, source_token_account.key, &vault_key, owner.key, &[], amount)?,
+        &[source_token_account.clone(), governing_token_vault.clone(), owner.clone(), token_program.clone()],
+    )?;
+    
+    token_owner_record_data.pack(&mut token_owner_record.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+pub fn withdraw_governing_tokens<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    amount: u64,
+) -> ProgramResult {
+    let WithdrawGoverningTokensContext { token_owner_record, governing_token_vault, destination_token_account, token_program, .. } =
+        WithdrawGoverningTokensContext::try_accounts(program_id, accounts)?;
+    
+    let mut token_owner_record_data = TokenOwnerRecord::unpack(&token_owner_record.data.borrow())?;
+    
+    // Verify vault address
+    let governing_mint = token_owner_record_data.governing_mint;
+    let (vault_key, vault_bump) = TokenOwnerRecord::find_governing_vault(program_id, &governing_mint);
+    if governing_token_vault.key != &vault_key {
+        return Err(ProgramError::InvalidSeeds);
     }
     
+    // Fails while the deposit is backing votes on open proposals
+    token_owner_record_data.withdraw(amount)?;
+    token_owner_record_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    invoke_signed(
+        &token_instruction::transfer(token_program.key, &vault_key, destination_token_account.key, &vault_key, &[], amount)?,
+        &[governing_token_vault.clone(), destination_token_account.clone(), token_program.clone()],
+        &[&[GOVERNING_VAULT_SEED, governing_mint.as_ref(), &[vault_bump]]],
+    )?;
+    
+    token_owner_record_data.pack(&mut token_owner_record.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Pushes the end of a running lock out to `duration` seconds from now.
+///
+/// 0. `[writable]` The token owner record
+/// 1. `[signer]` The owner
+pub fn extend_lock<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    duration: i64,
+) -> ProgramResult {
+    let TokenOwnerRecordContext { token_owner_record, .. } = TokenOwnerRecordContext::try_accounts(program_id, accounts)?;
+    
+    let mut token_owner_record_data = TokenOwnerRecord::unpack(&token_owner_record.data.borrow())?;
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    token_owner_record_data.vote_escrow.extend(duration, current_time)?;
+    token_owner_record_data.updated_at = current_time;
+    
+    token_owner_record_data.pack(&mut token_owner_record.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Pays out an expired lock in full. Takes the same accounts as
+/// `withdraw_governing_tokens`.
+pub fn withdraw_locked_tokens<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let WithdrawGoverningTokensContext { token_owner_record, governing_token_vault, destination_token_account, token_program, .. } =
+        WithdrawGoverningTokensContext::try_accounts(program_id, accounts)?;
+    
+    let mut token_owner_record_data = TokenOwnerRecord::unpack(&token_owner_record.data.borrow())?;
+    
+    // Verify vault address
+    let governing_mint = token_owner_record_data.governing_mint;
+    let (vault_key, vault_bump) = TokenOwnerRecord::find_governing_vault(program_id, &governing_mint);
+    if governing_token_vault.key != &vault_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    // Fails before the lock ends or while it is backing votes on open proposals
+    let current_time = Clock::get()?.unix_timestamp;
+    let amount = token_owner_record_data.withdraw_locked(current_time)?;
+    token_owner_record_data.updated_at = current_time;
+    
+    invoke_signed(
+        &token_instruction::transfer(token_program.key, &vault_key, destination_token_account.key, &vault_key, &[], amount)?,
+        &[governing_token_vault.clone(), destination_token_account.clone(), token_program.clone()],
+        &[&[GOVERNING_VAULT_SEED, governing_mint.as_ref(), &[vault_bump]]],
+    )?;
+    
+    token_owner_record_data.pack(&mut token_owner_record.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Checks `member` is an active member of the community `scope` restricts
+/// governance to, and that the community is active. Platform realms are open
+/// to every token holder.
+fn check_realm_member(
+    program_id: &Pubkey,
+    scope: &RealmScope,
+    membership: Option<&AccountInfo>,
+    community_account: Option<&AccountInfo>,
+    member: &Pubkey,
+) -> ProgramResult {
+    let community = match scope {
+        RealmScope::Platform => return Ok(()),
+        RealmScope::Community(community) => community,
+    };
+    
+    // Suspended and archived communities can't govern
+    let community_account = community_account.ok_or(ProgramError::NotEnoughAccountKeys)?;
+    if community_account.key != community || community_account.owner != program_id {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let community_data = Community::unpack(&community_account.data.borrow())?;
+    community_data.check_active()?;
+    
+    let membership = membership.ok_or(UnityVaultError::NotARealmMember)?;
+    let (membership_key, _) = Membership::find_address(program_id, community, member);
+    if membership.key != &membership_key || membership.owner != program_id {
+        return Err(UnityVaultError::NotARealmMember.into());
+    }
+    let membership_data = Membership::unpack(&membership.data.borrow())?;
+    if membership_data.community != *community
+        || membership_data.member != *member
+        || !membership_data.is_in_good_standing(&community_data, Clock::get()?.unix_timestamp)
+    {
+        return Err(UnityVaultError::NotARealmMember.into());
+    }
+    Ok(())
+}
+
+/// Loads the deposit record a vote is cast with and checks that `voter`,
+/// its owner or delegate, can vote it on `proposal_data`. In community
+/// realms the owner must also be a member of the active community.
+fn load_voter_token_owner_record(
+    program_id: &Pubkey,
+    token_owner_record: &AccountInfo,
+    membership: Option<&AccountInfo>,
+    community: Option<&AccountInfo>,
+    voter: &Pubkey,
+    proposal_data: &Proposal,
+) -> Result<TokenOwnerRecord, ProgramError> {
+    if token_owner_record.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let token_owner_record_data = TokenOwnerRecord::unpack(&token_owner_record.data.borrow())?;
+    if !token_owner_record_data.is_owner_or_delegate(voter) {
+        return Err(ProgramError::IllegalOwner);
+    }
+    if token_owner_record_data.governing_mint != proposal_data.governing_mint {
+        return Err(UnityVaultError::GoverningMintMismatch.into());
+    }
+    if token_owner_record_data.voting_weight(proposal_data.voting_started_at) == 0 {
+        return Err(UnityVaultError::NoVotingWeight.into());
+    }
+    check_realm_member(program_id, &proposal_data.scope, membership, community, &token_owner_record_data.owner)?;
+    Ok(token_owner_record_data)
+}
+
+pub fn vote_proposal<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    vote_type: VoteType,
+) -> ProgramResult {
+    let VoteProposalContext { proposal, vote_record, voter, token_owner_record, system_program, membership, community } =
+        VoteProposalContext::try_accounts(program_id, accounts)?;
+    
+    // Get proposal data
+    let mut proposal_data = Proposal::unpack(&proposal.data.borrow())?;
+    
+    // Verify voting is open
+    let current_time = Clock::get()?.unix_timestamp;
+    if !proposal_data.is_voting_open(current_time) {
+        return Err(UnityVaultError::VotingClosed.into());
+    }
+    
+    let mut token_owner_record_data =
+        load_voter_token_owner_record(program_id, token_owner_record, membership, community, voter.key, &proposal_data)?;
+    let token_owner = token_owner_record_data.owner;
+    
+    // Verify vote record address, which is per token owner even when a delegate votes
+    let (vote_record_key, bump) = VoteRecord::find_address(program_id, proposal.key, &token_owner);
+    if vote_record.key != &vote_record_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    // The first vote creates the owner's record; later ones must use ChangeVote
+    if VoteRecord::is_initialized_account(&vote_record.data.borrow()) {
+        return Err(UnityVaultError::AlreadyVoted.into());
+    }
+    
+    create_pda_account(
+        program_id,
+        voter,
+        vote_record,
+        system_program,
+        VoteRecord::LEN,
+        &[VOTE_RECORD_SEED, proposal.key.as_ref(), token_owner.as_ref(), &[bump]],
+    )?;
+    
+    let vote_record_data = VoteRecord {
+        is_initialized: true,
+        proposal: *proposal.key,
+        voter: token_owner,
+        cast_by: *voter.key,
+        vote_type,
+        weight: token_owner_record_data.voting_weight(proposal_data.voting_started_at),
+        is_relinquished: false,
+        bump,
+        created_at: current_time,
+        updated_at: current_time,
+    };
+    
+    // Update vote counts
+    proposal_data.add_vote(vote_type, vote_record_data.weight)?;
+    
+    // Lock the deposit until the vote is relinquished
+    token_owner_record_data.active_votes = token_owner_record_data.active_votes
+        .checked_add(1)
+        .ok_or(ProgramError::ArithmeticOverflow)?;
+    token_owner_record_data.updated_at = current_time;
+    
     proposal_data.updated_at = current_time;
     
     // Pack the updated data
-    proposal_data.pack_into_slice(&mut proposal.data.borrow_mut());
+    vote_record_data.pack(&mut vote_record.data.borrow_mut())?;
+    token_owner_record_data.pack(&mut token_owner_record.data.borrow_mut())?;
+    proposal_data.pack(&mut proposal.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+pub fn change_vote<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    vote_type: VoteType,
+) -> ProgramResult {
+    let ChangeVoteContext { proposal, vote_record, voter, token_owner_record, membership, community } =
+        ChangeVoteContext::try_accounts(program_id, accounts)?;
+    
+    let mut proposal_data = Proposal::unpack(&proposal.data.borrow())?;
+    let mut vote_record_data = VoteRecord::unpack(&vote_record.data.borrow())?;
+    
+    // Votes can only change while voting is open
+    let current_time = Clock::get()?.unix_timestamp;
+    if !proposal_data.is_voting_open(current_time) {
+        return Err(UnityVaultError::VotingClosed.into());
+    }
+    
+    let token_owner_record_data =
+        load_voter_token_owner_record(program_id, token_owner_record, membership, community, voter.key, &proposal_data)?;
+    
+    // Verify the record is the owner's ballot on this proposal and the signer may change it
+    if vote_record_data.proposal != *proposal.key || vote_record_data.voter != token_owner_record_data.owner {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if !vote_record_data.can_be_changed_by(voter.key, &token_owner_record_data) {
+        return Err(ProgramError::IllegalOwner);
+    }
+    
+    // Move the weight to the new choice, picking up any deposits made since
+    proposal_data.remove_vote(vote_record_data.vote_type, vote_record_data.weight)?;
+    proposal_data.add_vote(vote_type, token_owner_record_data.voting_weight(proposal_data.voting_started_at))?;
+    proposal_data.updated_at = current_time;
+    
+    vote_record_data.vote_type = vote_type;
+    vote_record_data.weight = token_owner_record_data.voting_weight(proposal_data.voting_started_at);
+    vote_record_data.cast_by = *voter.key;
+    vote_record_data.updated_at = current_time;
+    
+    // Pack the updated data
+    vote_record_data.pack(&mut vote_record.data.borrow_mut())?;
+    proposal_data.pack(&mut proposal.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+pub fn relinquish_vote<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let RelinquishVoteContext { proposal, vote_record, voter, token_owner_record } =
+        RelinquishVoteContext::try_accounts(program_id, accounts)?;
+    
+    let proposal_data = Proposal::unpack(&proposal.data.borrow())?;
+    let mut vote_record_data = VoteRecord::unpack(&vote_record.data.borrow())?;
+    let mut token_owner_record_data = TokenOwnerRecord::unpack(&token_owner_record.data.borrow())?;
+    
+    // Verify the records belong to this proposal and the signer's vote
+    if vote_record_data.proposal != *proposal.key || vote_record_data.voter != token_owner_record_data.owner {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if token_owner_record_data.governing_mint != proposal_data.governing_mint {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    if !token_owner_record_data.is_owner_or_delegate(voter.key) {
+        return Err(ProgramError::IllegalOwner);
+    }
+    if vote_record_data.is_relinquished {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    
+    // The deposit stays locked until the vote can no longer change
+    let current_time = Clock::get()?.unix_timestamp;
+    if proposal_data.is_voting_open(current_time) {
+        return Err(UnityVaultError::VotingStillOpen.into());
+    }
+    
+    vote_record_data.is_relinquished = true;
+    vote_record_data.updated_at = current_time;
+    token_owner_record_data.active_votes = token_owner_record_data.active_votes.saturating_sub(1);
+    token_owner_record_data.updated_at = current_time;
+    
+    vote_record_data.pack(&mut vote_record.data.borrow_mut())?;
+    token_owner_record_data.pack(&mut token_owner_record.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Sets or clears (with `Pubkey::default()`) the key allowed to vote a deposit.
+/// Votes a delegate already cast stay in place; the owner can still change them.
+pub fn set_delegate<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    delegate: Pubkey,
+) -> ProgramResult {
+    let TokenOwnerRecordContext { token_owner_record, owner } = TokenOwnerRecordContext::try_accounts(program_id, accounts)?;
+    
+    let mut token_owner_record_data = TokenOwnerRecord::unpack(&token_owner_record.data.borrow())?;
+    
+    if delegate == *owner.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    token_owner_record_data.delegate = delegate;
+    token_owner_record_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    token_owner_record_data.pack(&mut token_owner_record.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Sets the proposal deposit and minimum holding for a realm, creating
+/// the config on first use with rent paid by the realm's treasury.
+///
+/// 0. `[writable]` The deposit config PDA
+/// 1. `[signer]` The governance authority PDA
+/// 2. `[]` The realm
+/// 3. `[writable]` The treasury PDA
+/// 4. `[]` The system program
+pub fn set_proposal_deposit_config<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    deposit_amount: u64,
+    min_tokens_to_propose: u64,
+) -> ProgramResult {
+    let RealmSettingContext { setting: deposit_config, realm, treasury, system_program, .. } =
+        RealmSettingContext::try_accounts(program_id, accounts)?;
+    
+    // Verify config and treasury addresses
+    let (deposit_config_key, bump) = ProposalDepositConfig::find_address(program_id, realm.key);
+    if deposit_config.key != &deposit_config_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let (treasury_key, treasury_bump) = find_treasury_address(program_id, realm.key);
+    if treasury.key != &treasury_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    if deposit_config.owner != program_id {
+        create_treasury_funded_account(
+            program_id,
+            treasury,
+            &[TREASURY_SEED, realm.key.as_ref(), &[treasury_bump]],
+            deposit_config,
+            system_program,
+            ProposalDepositConfig::LEN,
+            &[PROPOSAL_DEPOSIT_CONFIG_SEED, realm.key.as_ref(), &[bump]],
+        )?;
+    }
+    
+    let deposit_config_data = ProposalDepositConfig {
+        is_initialized: true,
+        realm: *realm.key,
+        deposit_amount,
+        min_tokens_to_propose,
+        bump,
+        updated_at: Clock::get()?.unix_timestamp,
+    };
+    
+    deposit_config_data.pack(&mut deposit_config.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Creates a realm-level account at the PDA `seeds`, with rent paid by the
+/// realm's treasury.
+fn create_treasury_funded_account<'a>(
+    program_id: &Pubkey,
+    treasury: &AccountInfo<'a>,
+    treasury_seeds: &[&[u8]],
+    account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    space: usize,
+    seeds: &[&[u8]],
+) -> ProgramResult {
+    invoke_signed(
+        &system_instruction::create_account(
+            treasury.key,
+            account.key,
+            Rent::get()?.minimum_balance(space),
+            space as u64,
+            program_id,
+        ),
+        &[treasury.clone(), account.clone(), system_program.clone()],
+        &[treasury_seeds, seeds],
+    )
+}
+
+/// Sets the veto council of a realm, creating it on first use with rent
+/// paid by the realm's treasury. An empty member list disables vetoes.
+///
+/// 0. `[writable]` The veto council PDA
+/// 1. `[signer]` The governance authority PDA
+/// 2. `[]` The realm
+/// 3. `[writable]` The realm treasury
+/// 4. `[]` The system program
+pub fn set_veto_council<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    members: Vec<Pubkey>,
+    threshold: u8,
+) -> ProgramResult {
+    VetoCouncil::validate_members(&members, threshold)?;
+    
+    let RealmSettingContext { setting: veto_council, realm, treasury, system_program, .. } =
+        RealmSettingContext::try_accounts(program_id, accounts)?;
+    
+    // Verify council and treasury addresses
+    let (veto_council_key, bump) = VetoCouncil::find_address(program_id, realm.key);
+    if veto_council.key != &veto_council_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let (treasury_key, treasury_bump) = find_treasury_address(program_id, realm.key);
+    if treasury.key != &treasury_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    if veto_council.owner != program_id {
+        create_treasury_funded_account(
+            program_id,
+            treasury,
+            &[TREASURY_SEED, realm.key.as_ref(), &[treasury_bump]],
+            veto_council,
+            system_program,
+            VetoCouncil::LEN,
+            &[VETO_COUNCIL_SEED, realm.key.as_ref(), &[bump]],
+        )?;
+    }
+    
+    let veto_council_data = VetoCouncil {
+        is_initialized: true,
+        realm: *realm.key,
+        members,
+        threshold,
+        bump,
+        updated_at: Clock::get()?.unix_timestamp,
+    };
+    
+    veto_council_data.pack(&mut veto_council.data.borrow_mut())?;
     
     Ok(())
-} 
\ No newline at end of file
+}
+
+/// Vetoes a passed proposal before its execution timelock ends. The council
+/// members sign the transaction together and are recorded on the proposal.
+///
+/// 0. `[writable]` The proposal
+/// 1. `[]` The realm's veto council
+/// 2. `[signer]` Council members, at least the threshold
+pub fn veto_proposal<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let VetoProposalContext { proposal, veto_council, council_members } = VetoProposalContext::try_accounts(program_id, accounts)?;
+    let signers: Vec<Pubkey> = council_members
+        .iter()
+        .filter(|account| account.is_signer)
+        .map(|account| *account.key)
+        .collect();
+    
+    let mut proposal_data = Proposal::unpack(&proposal.data.borrow())?;
+    
+    // Verify the council belongs to the proposal's realm
+    let (veto_council_key, _) = VetoCouncil::find_address(program_id, &proposal_data.realm);
+    if veto_council.key != &veto_council_key || veto_council.owner != program_id {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let veto_council_data = VetoCouncil::unpack(&veto_council.data.borrow())?;
+    
+    let vetoed_by = veto_council_data.approving_members(&signers)?;
+    let current_time = Clock::get()?.unix_timestamp;
+    proposal_data.veto(vetoed_by, current_time)?;
+    proposal_data.updated_at = current_time;
+    
+    proposal_data.pack(&mut proposal.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Settles a finished proposal's deposit. Anyone can call it: the deposit
+/// returns to the proposer's record if the vote reached quorum and is paid
+/// from the vault into the treasury otherwise.
+///
+/// 0. `[writable]` The proposal
+/// 1. `[writable]` The proposer's token owner record
+/// 2. `[writable]` The governing token vault
+/// 3. `[writable]` The realm treasury's token account for the governing mint
+/// 4. `[]` The token program
+pub fn release_proposal_deposit<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let ReleaseProposalDepositContext { proposal, token_owner_record, governing_token_vault, treasury_token_account, token_program } =
+        ReleaseProposalDepositContext::try_accounts(program_id, accounts)?;
+    
+    let mut proposal_data = Proposal::unpack(&proposal.data.borrow())?;
+    let refundable = proposal_data.deposit_refundable().ok_or(ProgramError::InvalidAccountData)?;
+    let amount = proposal_data.deposit_amount;
+    if amount == 0 {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let governing_mint = proposal_data.governing_mint;
+    
+    if refundable {
+        // Verify the record is the proposer's
+        let (token_owner_record_key, _) = TokenOwnerRecord::find_address(program_id, &governing_mint, &proposal_data.authority);
+        if token_owner_record.key != &token_owner_record_key || token_owner_record.owner != program_id {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        let mut token_owner_record_data = TokenOwnerRecord::unpack(&token_owner_record.data.borrow())?;
+        token_owner_record_data.deposit(amount)?;
+        token_owner_record_data.updated_at = Clock::get()?.unix_timestamp;
+        token_owner_record_data.pack(&mut token_owner_record.data.borrow_mut())?;
+    } else {
+        // Verify vault and treasury token account addresses
+        let (vault_key, vault_bump) = TokenOwnerRecord::find_governing_vault(program_id, &governing_mint);
+        if governing_token_vault.key != &vault_key {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        if treasury_token_account.key != &find_treasury_token_account(program_id, &proposal_data.realm, &governing_mint) {
+            return Err(ProgramError::InvalidSeeds);
+        }
+        
+        invoke_signed(
+            &token_instruction::transfer(token_program.key, &vault_key, treasury_token_account.key, &vault_key, &[], amount)?,
+            &[governing_token_vault.clone(), treasury_token_account.clone(), token_program.clone()],
+            &[&[GOVERNING_VAULT_SEED, governing_mint.as_ref(), &[vault_bump]]],
+        )?;
+    }
+    
+    proposal_data.deposit_amount = 0;
+    proposal_data.updated_at = Clock::get()?.unix_timestamp;
+    proposal_data.pack(&mut proposal.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Creates the realm for `scope` and the governing mint. A community realm
+/// is created by the community's authority; the platform realm by the
+/// governing mint's mint authority.
+///
+/// 0. `[writable]` The realm PDA
+/// 1. `[writable, signer]` The community authority or mint authority, paying rent
+/// 2. `[]` The governing mint
+/// 3. `[]` The system program
+/// 4. `[]` The community, for `RealmScope::Community`
+pub fn create_realm<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    scope: RealmScope,
+    config: GovernanceConfig,
+    quorum_floor: u64,
+) -> ProgramResult {
+    config.validate()?;
+    if quorum_floor == 0 || config.quorum < quorum_floor {
+        return Err(UnityVaultError::InvalidGovernanceConfig.into());
+    }
+    
+    let CreateRealmContext { realm, authority, governing_mint, system_program, community } =
+        CreateRealmContext::try_accounts(program_id, accounts)?;
+    
+    let mint_data = spl_token::state::Mint::unpack(&governing_mint.data.borrow())?;
+    
+    // Verify the signer controls the scope
+    match scope {
+        RealmScope::Platform => {
+            if mint_data.mint_authority != Some(*authority.key).into() {
+                return Err(ProgramError::IllegalOwner);
+            }
+        }
+        RealmScope::Community(community_key) => {
+            let community = community.ok_or(ProgramError::NotEnoughAccountKeys)?;
+            if community.key != &community_key {
+                return Err(ProgramError::InvalidAccountData);
+            }
+            let community_data = Community::unpack(&community.data.borrow())?;
+            if community_data.authority != *authority.key {
+                return Err(ProgramError::IllegalOwner);
+            }
+            community_data.check_active()?;
+        }
+    }
+    
+    // Verify realm address
+    let (realm_key, bump) = Realm::find_address(program_id, &scope, governing_mint.key);
+    if realm.key != &realm_key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    
+    create_pda_account(
+        program_id,
+        authority,
+        realm,
+        system_program,
+        Realm::LEN,
+        &[REALM_SEED, scope.seed_key().as_ref(), governing_mint.key.as_ref(), &[bump]],
+    )?;
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    let realm_data = Realm {
+        is_initialized: true,
+        scope,
+        governing_mint: *governing_mint.key,
+        config,
+        quorum_floor,
+        bump,
+        created_at: current_time,
+        updated_at: current_time,
+    };
+    
+    realm_data.pack(&mut realm.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+/// Replaces a realm's voting rules. Only the realm's governance authority
+/// can sign, so this runs only as an executed proposal built with
+/// `amendment::set_governance_config_instruction`.
+///
+/// 0. `[writable]` The realm
+/// 1. `[signer]` The governance authority PDA
+pub fn set_governance_config<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    config: GovernanceConfig,
+) -> ProgramResult {
+    let SetGovernanceConfigContext { realm, .. } = SetGovernanceConfigContext::try_accounts(program_id, accounts)?;
+    
+    let mut realm_data = Realm::unpack(&realm.data.borrow())?;
+    check_amendment(&realm_data, &config)?;
+    
+    realm_data.config = config;
+    realm_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    realm_data.pack(&mut realm.data.borrow_mut())?;
+    
+    Ok(())
+}
@@ -1,6 +1,10 @@
 pub mod state;
 pub mod context;
 pub mod instructions;
+pub mod amendment;
+pub mod escrow;
+pub mod tally;
+pub mod treasury;
 
 pub use state::*;
 pub use context::*;
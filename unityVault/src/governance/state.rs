@@ -1,10 +1,36 @@
 use solana_program::{
-    account_info::AccountInfo,
-    program_error::ProgramError,
-    program_pack::{IsInitialized, Pack, Sealed},
+    hash::hashv,
+    instruction::{AccountMeta, Instruction},
     pubkey::Pubkey,
 };
 use borsh::{BorshSerialize, BorshDeserialize};
+use crate::account::{AccountState, AccountType, ACCOUNT_HEADER_LEN};
+use solana_program::program_error::ProgramError;
+use crate::error::UnityVaultError;
+use crate::governance::escrow::VoteEscrow;
+use crate::governance::tally::{tally, tally_options, TallyResult, TallyRules};
+use crate::migration::{decode_legacy, upgrade_or_decode, Migrate, LEGACY_LAYOUT_VERSION};
+use crate::validation::{check_str_len, check_vec_len};
+
+pub const MAX_PROPOSAL_TITLE_LEN: usize = 100;
+pub const MAX_PROPOSAL_DESCRIPTION_LEN: usize = 1000;
+pub const MIN_VOTING_DURATION: i64 = 60 * 60;
+pub const MAX_VOTING_DURATION: i64 = 30 * 24 * 60 * 60;
+pub const MAX_EXECUTION_DELAY: i64 = 30 * 24 * 60 * 60;
+pub const VOTE_RECORD_SEED: &[u8] = b"vote";
+pub const TOKEN_OWNER_RECORD_SEED: &[u8] = b"token_owner_record";
+pub const GOVERNING_VAULT_SEED: &[u8] = b"governing_vault";
+pub const GOVERNANCE_AUTHORITY_SEED: &[u8] = b"governance";
+pub const PROPOSAL_TRANSACTION_SEED: &[u8] = b"proposal_transaction";
+pub const PROPOSAL_DEPOSIT_CONFIG_SEED: &[u8] = b"proposal_deposit_config";
+pub const REALM_SEED: &[u8] = b"realm";
+pub const VETO_COUNCIL_SEED: &[u8] = b"veto_council";
+pub const MAX_VETO_COUNCIL_MEMBERS: usize = 10;
+pub const MAX_INSTRUCTION_ACCOUNTS: usize = 16;
+pub const MAX_INSTRUCTION_DATA_LEN: usize = 512;
+/// Options are chosen with a `u16` bitmask, so there can be at most 16.
+pub const MAX_PROPOSAL_OPTIONS: usize = 16;
+pub const MAX_PROPOSAL_OPTION_LEN: usize = 50;
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ProposalStatus {
@@ -13,6 +39,9 @@ pub enum ProposalStatus {
     Passed,
     Rejected,
     Executed,
+    Cancelled,
+    /// Stopped by the realm's veto council during the execution timelock.
+    Vetoed,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
@@ -20,206 +49,1742 @@ pub enum VoteType {
     Yes,
     No,
     Abstain,
+    /// Options picked on a multiple-choice proposal, as a bitmask of their indexes.
+    Options(u16),
+}
+
+/// How a proposal is voted on. Multiple-choice proposals are polls: they
+/// pick a winning option but cannot carry executable transactions.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoteMode {
+    YesNo,
+    /// Each voter picks exactly one option.
+    SingleChoice,
+    /// Each voter approves any number of options, each getting their full weight.
+    Approval,
 }
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub struct ProposalParams {
     pub title: String,
     pub description: String,
-    pub voting_duration: i64,
-    pub min_votes: u32,
-    pub min_approval_percentage: u8,
+    pub vote_mode: VoteMode,
+    /// Option labels for multiple-choice proposals; empty for yes/no.
+    pub options: Vec<String>,
+}
+
+impl ProposalParams {
+    pub fn validate(&self) -> Result<(), UnityVaultError> {
+        check_str_len("title", &self.title, MAX_PROPOSAL_TITLE_LEN)?;
+        check_str_len("description", &self.description, MAX_PROPOSAL_DESCRIPTION_LEN)?;
+        check_vec_len("options", &self.options, MAX_PROPOSAL_OPTIONS)?;
+        for option in &self.options {
+            check_str_len("option", option, MAX_PROPOSAL_OPTION_LEN)?;
+        }
+        let options_valid = match self.vote_mode {
+            VoteMode::YesNo => self.options.is_empty(),
+            VoteMode::SingleChoice | VoteMode::Approval => {
+                self.options.len() >= 2 && self.options.iter().all(|option| !option.is_empty())
+            }
+        };
+        if !options_valid {
+            return Err(UnityVaultError::InvalidProposalParams);
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
 pub struct Proposal {
     pub is_initialized: bool,
     pub authority: Pubkey,
+    pub realm: Pubkey,
+    pub scope: RealmScope,
+    pub governing_mint: Pubkey,
     pub title: String,
     pub description: String,
     pub status: ProposalStatus,
     pub voting_duration: i64,
-    pub min_votes: u32,
+    pub min_votes: u64,
     pub min_approval_percentage: u8,
-    pub yes_votes: u32,
-    pub no_votes: u32,
-    pub abstain_votes: u32,
+    pub yes_votes: u64,
+    pub no_votes: u64,
+    pub abstain_votes: u64,
+    pub vote_mode: VoteMode,
+    pub options: Vec<String>,
+    pub option_votes: Vec<u64>,
+    /// Weight of the voters who picked options, counted once per voter.
+    pub option_voter_weight: u64,
+    pub winning_option: Option<u8>,
+    pub voting_started_at: i64,
+    pub voting_completed_at: i64,
+    pub execution_delay: i64,
+    pub transaction_count: u16,
+    pub executed_transaction_count: u16,
+    pub transactions_hash: [u8; 32],
+    pub content_hash: [u8; 32],
+    pub deposit_amount: u64,
+    /// Council members who vetoed the proposal; empty unless `Vetoed`.
+    pub vetoed_by: Vec<Pubkey>,
     pub created_at: i64,
     pub updated_at: i64,
     pub executed_at: i64,
 }
 
 impl Proposal {
-    pub const LEN: usize = 1 + // is_initialized
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + // account type and version
+        1 + // is_initialized
         32 + // authority
-        4 + 100 + // title (max 100 chars)
-        4 + 1000 + // description (max 1000 chars)
+        32 + // realm
+        RealmScope::LEN + // scope
+        32 + // governing_mint
+        4 + MAX_PROPOSAL_TITLE_LEN + // title
+        4 + MAX_PROPOSAL_DESCRIPTION_LEN + // description
         1 + // status
         8 + // voting_duration
-        4 + // min_votes
+        8 + // min_votes
         1 + // min_approval_percentage
-        4 + // yes_votes
-        4 + // no_votes
-        4 + // abstain_votes
+        8 + // yes_votes
+        8 + // no_votes
+        8 + // abstain_votes
+        1 + // vote_mode
+        4 + MAX_PROPOSAL_OPTIONS * (4 + MAX_PROPOSAL_OPTION_LEN) + // options
+        4 + MAX_PROPOSAL_OPTIONS * 8 + // option_votes
+        8 + // option_voter_weight
+        1 + 1 + // winning_option
+        8 + // voting_started_at
+        8 + // voting_completed_at
+        8 + // execution_delay
+        2 + // transaction_count
+        2 + // executed_transaction_count
+        32 + // transactions_hash
+        32 + // content_hash
+        8 + // deposit_amount
+        4 + MAX_VETO_COUNCIL_MEMBERS * 32 + // vetoed_by
         8 + // created_at
         8 + // updated_at
         8; // executed_at
+
+    /// Copies the realm's voting rules, which the proposal keeps from then on.
+    pub fn apply_config(&mut self, config: &GovernanceConfig) {
+        self.min_votes = config.quorum;
+        self.min_approval_percentage = config.min_approval_percentage;
+        self.voting_duration = config.voting_duration;
+        self.execution_delay = config.execution_delay;
+    }
+
+    /// End of the voting window, which opens on activation.
+    pub fn voting_ends_at(&self) -> i64 {
+        self.voting_started_at.saturating_add(self.voting_duration)
+    }
+
+    /// Whether votes can still be cast or changed at `now`.
+    pub fn is_voting_open(&self, now: i64) -> bool {
+        self.status == ProposalStatus::Active && now < self.voting_ends_at()
+    }
+
+    /// Folds an attached instruction into `transactions_hash`, in insertion order.
+    pub fn record_transaction(&mut self, instruction: &ProposalInstruction) -> Result<(), ProgramError> {
+        let encoded = borsh::to_vec(instruction).map_err(|_| ProgramError::InvalidAccountData)?;
+        self.transactions_hash = hashv(&[&self.transactions_hash, &encoded]).to_bytes();
+        Ok(())
+    }
+
+    /// Hash of everything voters decide on: the text, the voting rules and
+    /// the attached instructions. Stored on activation so voters can check
+    /// it against what they were shown.
+    pub fn compute_content_hash(&self) -> [u8; 32] {
+        let mut options = Vec::new();
+        for option in &self.options {
+            options.extend_from_slice(option.as_bytes());
+            options.push(0);
+        }
+        hashv(&[
+            self.realm.as_ref(),
+            self.governing_mint.as_ref(),
+            self.title.as_bytes(),
+            &[0],
+            self.description.as_bytes(),
+            &[0],
+            &self.voting_duration.to_le_bytes(),
+            &self.min_votes.to_le_bytes(),
+            &[self.min_approval_percentage],
+            &self.execution_delay.to_le_bytes(),
+            &[self.vote_mode as u8],
+            &options,
+            &self.transaction_count.to_le_bytes(),
+            &self.transactions_hash,
+        ])
+        .to_bytes()
+    }
+
+    /// Earliest time a passed proposal's transactions can be executed.
+    pub fn executable_at(&self) -> i64 {
+        self.voting_completed_at.saturating_add(self.execution_delay)
+    }
+
+    /// Whether a passed proposal's timelock has elapsed. Proposals migrated
+    /// from before realms existed have no governance authority to sign for
+    /// them and are never executable.
+    pub fn is_executable(&self, now: i64) -> bool {
+        self.status == ProposalStatus::Passed && self.realm != Pubkey::default() && now >= self.executable_at()
+    }
+
+    /// Marks a passed proposal as vetoed by `signers`, which must be before
+    /// its transactions become executable.
+    pub fn veto(&mut self, signers: Vec<Pubkey>, now: i64) -> Result<(), UnityVaultError> {
+        if self.status != ProposalStatus::Passed || now >= self.executable_at() {
+            return Err(UnityVaultError::VetoWindowClosed);
+        }
+        self.status = ProposalStatus::Vetoed;
+        self.vetoed_by = signers;
+        Ok(())
+    }
+
+    pub fn tally_rules(&self) -> TallyRules {
+        TallyRules {
+            quorum: self.min_votes,
+            approval_threshold_bps: u16::from(self.min_approval_percentage) * 100,
+        }
+    }
+
+    /// Result of the vote as it stands. Finalization records the outcome of
+    /// this once the window has closed; clients can call it to preview.
+    pub fn tally(&self) -> TallyResult {
+        match self.vote_mode {
+            VoteMode::YesNo => tally(self.yes_votes, self.no_votes, self.abstain_votes, &self.tally_rules()),
+            VoteMode::SingleChoice | VoteMode::Approval => tally_options(
+                &self.option_votes,
+                self.option_voter_weight,
+                self.abstain_votes,
+                &self.tally_rules(),
+            ),
+        }
+    }
+
+    /// Whether the proposer's deposit goes back to them once the proposal is
+    /// settled: only if the vote reached quorum. `None` while it is undecided.
+    pub fn deposit_refundable(&self) -> Option<bool> {
+        match self.status {
+            ProposalStatus::Passed | ProposalStatus::Rejected | ProposalStatus::Executed => {
+                Some(self.tally().quorum_reached)
+            }
+            ProposalStatus::Cancelled | ProposalStatus::Vetoed => Some(false),
+            ProposalStatus::Draft | ProposalStatus::Active => None,
+        }
+    }
+
+    /// Checks `vote_type` is a valid ballot for this proposal's vote mode.
+    /// Abstaining is always allowed.
+    pub fn check_vote(&self, vote_type: VoteType) -> Result<(), UnityVaultError> {
+        let valid = match (self.vote_mode, vote_type) {
+            (_, VoteType::Abstain) => true,
+            (VoteMode::YesNo, VoteType::Yes | VoteType::No) => true,
+            (VoteMode::SingleChoice, VoteType::Options(mask)) => {
+                mask.count_ones() == 1 && self.is_option_mask(mask)
+            }
+            (VoteMode::Approval, VoteType::Options(mask)) => mask != 0 && self.is_option_mask(mask),
+            _ => false,
+        };
+        if !valid {
+            return Err(UnityVaultError::InvalidVoteChoice);
+        }
+        Ok(())
+    }
+
+    fn is_option_mask(&self, mask: u16) -> bool {
+        u32::from(mask) >> self.options.len() == 0
+    }
+
+    fn apply_vote(&mut self, vote_type: VoteType, weight: u64, add: bool) -> Result<(), ProgramError> {
+        self.check_vote(vote_type)?;
+        let apply = |tally: &mut u64| -> Result<(), ProgramError> {
+            let updated = if add { tally.checked_add(weight) } else { tally.checked_sub(weight) };
+            *tally = updated.ok_or(ProgramError::ArithmeticOverflow)?;
+            Ok(())
+        };
+        match vote_type {
+            VoteType::Yes => apply(&mut self.yes_votes),
+            VoteType::No => apply(&mut self.no_votes),
+            VoteType::Abstain => apply(&mut self.abstain_votes),
+            VoteType::Options(mask) => {
+                for (index, tally) in self.option_votes.iter_mut().enumerate() {
+                    if mask & (1 << index) != 0 {
+                        apply(tally)?;
+                    }
+                }
+                apply(&mut self.option_voter_weight)
+            }
+        }
+    }
+
+    pub fn add_vote(&mut self, vote_type: VoteType, weight: u64) -> Result<(), ProgramError> {
+        self.apply_vote(vote_type, weight, true)
+    }
+
+    pub fn remove_vote(&mut self, vote_type: VoteType, weight: u64) -> Result<(), ProgramError> {
+        self.apply_vote(vote_type, weight, false)
+    }
 }
 
-impl Sealed for Proposal {}
+impl AccountState for Proposal {
+    const ACCOUNT_TYPE: AccountType = AccountType::Proposal;
+    const VERSION: u8 = 2;
+    const LEN: usize = Self::LEN;
+
+    fn validate_bounds(&self) -> Result<(), UnityVaultError> {
+        check_str_len("title", &self.title, MAX_PROPOSAL_TITLE_LEN)?;
+        check_str_len("description", &self.description, MAX_PROPOSAL_DESCRIPTION_LEN)?;
+        check_vec_len("options", &self.options, MAX_PROPOSAL_OPTIONS)?;
+        check_vec_len("option_votes", &self.option_votes, MAX_PROPOSAL_OPTIONS)?;
+        check_vec_len("vetoed_by", &self.vetoed_by, MAX_VETO_COUNCIL_MEMBERS)?;
+        for option in &self.options {
+            check_str_len("option", option, MAX_PROPOSAL_OPTION_LEN)?;
+        }
+        Ok(())
+    }
+}
+
+/// `Proposal` layout version 1, and the legacy layout it matches field for
+/// field: one-per-signer `u32` tallies and no governing mint.
+#[derive(BorshDeserialize)]
+#[cfg_attr(test, derive(BorshSerialize))]
+struct ProposalV1 {
+    is_initialized: bool,
+    authority: Pubkey,
+    title: String,
+    description: String,
+    status: ProposalStatus,
+    voting_duration: i64,
+    min_votes: u32,
+    min_approval_percentage: u8,
+    yes_votes: u32,
+    no_votes: u32,
+    abstain_votes: u32,
+    created_at: i64,
+    updated_at: i64,
+    executed_at: i64,
+}
+
+impl From<ProposalV1> for Proposal {
+    fn from(v1: ProposalV1) -> Self {
+        // Proposals without a realm or governing mint can no longer be voted on or executed
+        Proposal {
+            is_initialized: v1.is_initialized,
+            authority: v1.authority,
+            realm: Pubkey::default(),
+            scope: RealmScope::Platform,
+            governing_mint: Pubkey::default(),
+            title: v1.title,
+            description: v1.description,
+            status: v1.status,
+            voting_duration: v1.voting_duration,
+            min_votes: v1.min_votes.into(),
+            min_approval_percentage: v1.min_approval_percentage,
+            yes_votes: v1.yes_votes.into(),
+            no_votes: v1.no_votes.into(),
+            abstain_votes: v1.abstain_votes.into(),
+            vote_mode: VoteMode::YesNo,
+            options: Vec::new(),
+            option_votes: Vec::new(),
+            option_voter_weight: 0,
+            winning_option: None,
+            // Voting used to open on creation
+            voting_started_at: v1.created_at,
+            voting_completed_at: 0,
+            execution_delay: 0,
+            transaction_count: 0,
+            executed_transaction_count: 0,
+            transactions_hash: [0; 32],
+            content_hash: [0; 32],
+            deposit_amount: 0,
+            vetoed_by: Vec::new(),
+            created_at: v1.created_at,
+            updated_at: v1.updated_at,
+            executed_at: v1.executed_at,
+        }
+    }
+}
+
+impl Migrate for Proposal {
+    const LEGACY_LEN: Option<usize> = Some(1191);
+
+    fn decode_version(version: u8, src: &[u8]) -> Result<Self, ProgramError> {
+        let v1: ProposalV1 = match version {
+            LEGACY_LAYOUT_VERSION => decode_legacy(src)?,
+            1 => decode_legacy(src.get(ACCOUNT_HEADER_LEN..).ok_or(ProgramError::AccountDataTooSmall)?)?,
+            _ => return Err(UnityVaultError::UnsupportedAccountVersion.into()),
+        };
+        Ok(Proposal::from(v1))
+    }
+
+    fn migration_authority(&self) -> Pubkey {
+        self.authority
+    }
+}
+
+/// One token owner's ballot on one proposal, stored at the PDA
+/// `[VOTE_RECORD_SEED, proposal, voter]` so each owner can only hold one.
+/// `cast_by` is the owner, or their delegate if the delegate voted for them.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct VoteRecord {
+    pub is_initialized: bool,
+    pub proposal: Pubkey,
+    pub voter: Pubkey,
+    pub cast_by: Pubkey,
+    pub vote_type: VoteType,
+    pub weight: u64,
+    pub is_relinquished: bool,
+    pub bump: u8,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
 
-impl IsInitialized for Proposal {
-    fn is_initialized(&self) -> bool {
-        self.is_initialized
+impl VoteRecord {
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + // account type and version
+        1 + // is_initialized
+        32 + // proposal
+        32 + // voter
+        32 + // cast_by
+        1 + 2 + // vote_type
+        8 + // weight
+        1 + // is_relinquished
+        1 + // bump
+        8 + // created_at
+        8; // updated_at
+
+    pub fn find_address(program_id: &Pubkey, proposal: &Pubkey, voter: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[VOTE_RECORD_SEED, proposal.as_ref(), voter.as_ref()], program_id)
+    }
+
+    /// The owner can always change their vote, overriding their delegate.
+    /// The delegate can only change a vote they cast and are still delegated.
+    pub fn can_be_changed_by(&self, signer: &Pubkey, token_owner_record: &TokenOwnerRecord) -> bool {
+        *signer == self.voter || (*signer == self.cast_by && token_owner_record.delegate == *signer)
     }
 }
 
-impl Pack for Proposal {
+impl AccountState for VoteRecord {
+    const ACCOUNT_TYPE: AccountType = AccountType::VoteRecord;
+    const VERSION: u8 = 3;
     const LEN: usize = Self::LEN;
+}
 
-    fn pack_into_slice(&self, dst: &mut [u8]) {
-        let mut offset = 0;
-        
-        // Pack is_initialized
-        dst[offset] = self.is_initialized as u8;
-        offset += 1;
-        
-        // Pack authority
-        dst[offset..offset + 32].copy_from_slice(&self.authority.to_bytes());
-        offset += 32;
-        
-        // Pack title
-        let title_bytes = self.title.as_bytes();
-        dst[offset..offset + 4].copy_from_slice(&(title_bytes.len() as u32).to_le_bytes());
-        offset += 4;
-        dst[offset..offset + title_bytes.len()].copy_from_slice(title_bytes);
-        offset += title_bytes.len();
-        
-        // Pack description
-        let desc_bytes = self.description.as_bytes();
-        dst[offset..offset + 4].copy_from_slice(&(desc_bytes.len() as u32).to_le_bytes());
-        offset += 4;
-        dst[offset..offset + desc_bytes.len()].copy_from_slice(desc_bytes);
-        offset += desc_bytes.len();
-        
-        // Pack status
-        dst[offset] = self.status as u8;
-        offset += 1;
-        
-        // Pack voting_duration
-        dst[offset..offset + 8].copy_from_slice(&self.voting_duration.to_le_bytes());
-        offset += 8;
-        
-        // Pack min_votes
-        dst[offset..offset + 4].copy_from_slice(&self.min_votes.to_le_bytes());
-        offset += 4;
-        
-        // Pack min_approval_percentage
-        dst[offset] = self.min_approval_percentage;
-        offset += 1;
-        
-        // Pack votes
-        dst[offset..offset + 4].copy_from_slice(&self.yes_votes.to_le_bytes());
-        offset += 4;
-        dst[offset..offset + 4].copy_from_slice(&self.no_votes.to_le_bytes());
-        offset += 4;
-        dst[offset..offset + 4].copy_from_slice(&self.abstain_votes.to_le_bytes());
-        offset += 4;
-        
-        // Pack timestamps
-        dst[offset..offset + 8].copy_from_slice(&self.created_at.to_le_bytes());
-        offset += 8;
-        dst[offset..offset + 8].copy_from_slice(&self.updated_at.to_le_bytes());
-        offset += 8;
-        dst[offset..offset + 8].copy_from_slice(&self.executed_at.to_le_bytes());
-    }
-
-    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let mut offset = 0;
-        
-        // Unpack is_initialized
-        let is_initialized = src[offset] != 0;
-        offset += 1;
-        
-        // Unpack authority
-        let authority_bytes: [u8; 32] = src[offset..offset + 32].try_into()
-            .map_err(|_| ProgramError::InvalidAccountData)?;
-        let authority = Pubkey::from(authority_bytes);
-        offset += 32;
-        
-        // Unpack title
-        let title_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
-        offset += 4;
-        let title = String::from_utf8(src[offset..offset + title_len].to_vec())
-            .map_err(|_| ProgramError::InvalidAccountData)?;
-        offset += title_len;
-        
-        // Unpack description
-        let desc_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
-        offset += 4;
-        let description = String::from_utf8(src[offset..offset + desc_len].to_vec())
-            .map_err(|_| ProgramError::InvalidAccountData)?;
-        offset += desc_len;
-        
-        // Unpack status
-        let status = match src[offset] {
-            0 => ProposalStatus::Draft,
-            1 => ProposalStatus::Active,
-            2 => ProposalStatus::Passed,
-            3 => ProposalStatus::Rejected,
-            4 => ProposalStatus::Executed,
-            _ => return Err(ProgramError::InvalidAccountData),
-        };
-        offset += 1;
-        
-        // Unpack voting_duration
-        let voting_duration = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
-        offset += 8;
-        
-        // Unpack min_votes
-        let min_votes = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap());
-        offset += 4;
-        
-        // Unpack min_approval_percentage
-        let min_approval_percentage = src[offset];
-        offset += 1;
-        
-        // Unpack votes
-        let yes_votes = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap());
-        offset += 4;
-        let no_votes = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap());
-        offset += 4;
-        let abstain_votes = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap());
-        offset += 4;
-        
-        // Unpack timestamps
-        let created_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
-        offset += 8;
-        let updated_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
-        offset += 8;
-        let executed_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
-        
-        Ok(Proposal {
-            is_initialized,
-            authority,
-            title,
-            description,
-            status,
-            voting_duration,
-            min_votes,
-            min_approval_percentage,
-            yes_votes,
-            no_votes,
-            abstain_votes,
-            created_at,
-            updated_at,
-            executed_at,
-        })
+/// `VoteRecord` layout version 1: votes could not be relinquished.
+#[derive(BorshDeserialize)]
+#[cfg_attr(test, derive(BorshSerialize))]
+struct VoteRecordV1 {
+    is_initialized: bool,
+    proposal: Pubkey,
+    voter: Pubkey,
+    vote_type: VoteType,
+    weight: u64,
+    bump: u8,
+    created_at: i64,
+    updated_at: i64,
+}
+
+/// `VoteRecord` layout version 2: votes were always cast by the voter.
+#[derive(BorshDeserialize)]
+#[cfg_attr(test, derive(BorshSerialize))]
+struct VoteRecordV2 {
+    is_initialized: bool,
+    proposal: Pubkey,
+    voter: Pubkey,
+    vote_type: VoteType,
+    weight: u64,
+    is_relinquished: bool,
+    bump: u8,
+    created_at: i64,
+    updated_at: i64,
+}
+
+impl From<VoteRecordV1> for VoteRecordV2 {
+    fn from(v1: VoteRecordV1) -> Self {
+        VoteRecordV2 {
+            is_initialized: v1.is_initialized,
+            proposal: v1.proposal,
+            voter: v1.voter,
+            vote_type: v1.vote_type,
+            weight: v1.weight,
+            is_relinquished: false,
+            bump: v1.bump,
+            created_at: v1.created_at,
+            updated_at: v1.updated_at,
+        }
     }
 }
 
-impl Proposal {
-    pub fn unpack_unchecked(data: &[u8]) -> Result<Self, ProgramError> {
-        Self::unpack_from_slice(data)
+impl From<VoteRecordV2> for VoteRecord {
+    fn from(v2: VoteRecordV2) -> Self {
+        VoteRecord {
+            is_initialized: v2.is_initialized,
+            proposal: v2.proposal,
+            voter: v2.voter,
+            cast_by: v2.voter,
+            vote_type: v2.vote_type,
+            weight: v2.weight,
+            is_relinquished: v2.is_relinquished,
+            bump: v2.bump,
+            created_at: v2.created_at,
+            updated_at: v2.updated_at,
+        }
+    }
+}
+
+impl Migrate for VoteRecord {
+    fn decode_version(version: u8, src: &[u8]) -> Result<Self, ProgramError> {
+        let v1: Option<VoteRecordV1> = upgrade_or_decode(None::<VoteRecordV1>, version, 1, src)?;
+        let v2: Option<VoteRecordV2> = upgrade_or_decode(v1, version, 2, src)?;
+        v2.map(VoteRecord::from).ok_or_else(|| UnityVaultError::UnsupportedAccountVersion.into())
+    }
+
+    fn migration_authority(&self) -> Pubkey {
+        self.voter
+    }
+}
+
+/// PDA that signs the instructions of executed proposals in `realm`.
+/// Assets the realm controls are owned by this key.
+pub fn find_governance_authority(program_id: &Pubkey, realm: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[GOVERNANCE_AUTHORITY_SEED, realm.as_ref()], program_id)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct ProposalAccountMeta {
+    pub pubkey: Pubkey,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+/// A serialized instruction a proposal executes once passed.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct ProposalInstruction {
+    pub program_id: Pubkey,
+    pub accounts: Vec<ProposalAccountMeta>,
+    pub data: Vec<u8>,
+}
+
+impl ProposalInstruction {
+    pub const LEN: usize = 32 + // program_id
+        4 + MAX_INSTRUCTION_ACCOUNTS * (32 + 1 + 1) + // accounts
+        4 + MAX_INSTRUCTION_DATA_LEN; // data
+
+    pub fn validate(&self) -> Result<(), UnityVaultError> {
+        check_vec_len("accounts", &self.accounts, MAX_INSTRUCTION_ACCOUNTS)?;
+        check_vec_len("data", &self.data, MAX_INSTRUCTION_DATA_LEN)?;
+        Ok(())
+    }
+
+    pub fn to_instruction(&self) -> Instruction {
+        Instruction {
+            program_id: self.program_id,
+            accounts: self.accounts
+                .iter()
+                .map(|meta| AccountMeta {
+                    pubkey: meta.pubkey,
+                    is_signer: meta.is_signer,
+                    is_writable: meta.is_writable,
+                })
+                .collect(),
+            data: self.data.clone(),
+        }
+    }
+}
+
+/// One instruction attached to a proposal, stored at the PDA
+/// `[PROPOSAL_TRANSACTION_SEED, proposal, index]`. Indexes are assigned in
+/// order from `Proposal::transaction_count`.
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct ProposalTransaction {
+    pub is_initialized: bool,
+    pub proposal: Pubkey,
+    pub index: u16,
+    pub instruction: ProposalInstruction,
+    pub bump: u8,
+    pub executed_at: i64,
+}
+
+impl ProposalTransaction {
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + // account type and version
+        1 + // is_initialized
+        32 + // proposal
+        2 + // index
+        ProposalInstruction::LEN + // instruction
+        1 + // bump
+        8; // executed_at
+
+    pub fn find_address(program_id: &Pubkey, proposal: &Pubkey, index: u16) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[PROPOSAL_TRANSACTION_SEED, proposal.as_ref(), &index.to_le_bytes()], program_id)
+    }
+
+    pub fn is_executed(&self) -> bool {
+        self.executed_at != 0
+    }
+}
+
+impl AccountState for ProposalTransaction {
+    const ACCOUNT_TYPE: AccountType = AccountType::ProposalTransaction;
+    const VERSION: u8 = 1;
+    const LEN: usize = Self::LEN;
+
+    fn validate_bounds(&self) -> Result<(), UnityVaultError> {
+        self.instruction.validate()
+    }
+}
+
+/// Governing tokens a holder has deposited into the program vault for
+/// `governing_mint`, stored at the PDA `[TOKEN_OWNER_RECORD_SEED, mint, owner]`.
+/// `delegate`, when set, may vote the deposit on the owner's behalf.
+///
+/// Voting weight is the deposited amount rather than a wallet balance, plus
+/// the boosted power of any tokens in `vote_escrow`. Deposits stay locked
+/// while `active_votes` is non-zero, so the same tokens can not be moved
+/// between wallets to vote twice or borrowed for one vote.
+#[derive(Debug, Clone, Default, BorshSerialize, BorshDeserialize)]
+pub struct TokenOwnerRecord {
+    pub is_initialized: bool,
+    pub governing_mint: Pubkey,
+    pub owner: Pubkey,
+    pub delegate: Pubkey,
+    pub deposited_amount: u64,
+    pub active_votes: u32,
+    pub vote_escrow: VoteEscrow,
+    pub bump: u8,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl TokenOwnerRecord {
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + // account type and version
+        1 + // is_initialized
+        32 + // governing_mint
+        32 + // owner
+        32 + // delegate
+        8 + // deposited_amount
+        4 + // active_votes
+        VoteEscrow::LEN + // vote_escrow
+        1 + // bump
+        8 + // created_at
+        8; // updated_at
+
+    pub fn find_address(program_id: &Pubkey, governing_mint: &Pubkey, owner: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[TOKEN_OWNER_RECORD_SEED, governing_mint.as_ref(), owner.as_ref()], program_id)
+    }
+
+    /// Token account holding all deposits of `governing_mint`. It is its own token authority.
+    pub fn find_governing_vault(program_id: &Pubkey, governing_mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[GOVERNING_VAULT_SEED, governing_mint.as_ref()], program_id)
+    }
+
+    /// Weight the record votes with on a proposal whose voting started at `snapshot`.
+    pub fn voting_weight(&self, snapshot: i64) -> u64 {
+        self.deposited_amount.saturating_add(self.vote_escrow.voting_power_at(snapshot))
+    }
+
+    pub fn has_delegate(&self) -> bool {
+        self.delegate != Pubkey::default()
+    }
+
+    /// Whether `signer` can cast this record's weight.
+    pub fn is_owner_or_delegate(&self, signer: &Pubkey) -> bool {
+        *signer == self.owner || (self.has_delegate() && *signer == self.delegate)
+    }
+
+    pub fn deposit(&mut self, amount: u64) -> Result<(), ProgramError> {
+        self.deposited_amount = self.deposited_amount.checked_add(amount).ok_or(ProgramError::ArithmeticOverflow)?;
+        Ok(())
+    }
+
+    pub fn withdraw(&mut self, amount: u64) -> Result<(), ProgramError> {
+        if self.active_votes > 0 {
+            return Err(UnityVaultError::GoverningTokensLocked.into());
+        }
+        self.deposited_amount = self.deposited_amount.checked_sub(amount).ok_or(ProgramError::InsufficientFunds)?;
+        Ok(())
+    }
+
+    /// Releases an expired lock. Like deposits, it stays put while backing votes.
+    pub fn withdraw_locked(&mut self, now: i64) -> Result<u64, ProgramError> {
+        if self.active_votes > 0 {
+            return Err(UnityVaultError::GoverningTokensLocked.into());
+        }
+        self.vote_escrow.withdraw(now)
+    }
+
+    /// Moves `amount` of the deposit into a proposal's escrow. The tokens stay
+    /// in the vault but no longer count as voting weight or can be withdrawn.
+    /// Votes already cast keep their recorded weight, so this ignores the lock.
+    pub fn escrow_proposal_deposit(&mut self, amount: u64) -> Result<(), ProgramError> {
+        self.deposited_amount = self.deposited_amount.checked_sub(amount).ok_or(ProgramError::InsufficientFunds)?;
+        Ok(())
+    }
+}
+
+impl AccountState for TokenOwnerRecord {
+    const ACCOUNT_TYPE: AccountType = AccountType::TokenOwnerRecord;
+    const VERSION: u8 = 3;
+    const LEN: usize = Self::LEN;
+}
+
+/// `TokenOwnerRecord` layout version 1: no delegate.
+#[derive(BorshDeserialize)]
+#[cfg_attr(test, derive(BorshSerialize))]
+struct TokenOwnerRecordV1 {
+    is_initialized: bool,
+    governing_mint: Pubkey,
+    owner: Pubkey,
+    deposited_amount: u64,
+    active_votes: u32,
+    bump: u8,
+    created_at: i64,
+    updated_at: i64,
+}
+
+/// `TokenOwnerRecord` layout version 2: no vote escrow.
+#[derive(BorshDeserialize)]
+#[cfg_attr(test, derive(BorshSerialize))]
+struct TokenOwnerRecordV2 {
+    is_initialized: bool,
+    governing_mint: Pubkey,
+    owner: Pubkey,
+    delegate: Pubkey,
+    deposited_amount: u64,
+    active_votes: u32,
+    bump: u8,
+    created_at: i64,
+    updated_at: i64,
+}
+
+impl From<TokenOwnerRecordV1> for TokenOwnerRecordV2 {
+    fn from(v1: TokenOwnerRecordV1) -> Self {
+        TokenOwnerRecordV2 {
+            is_initialized: v1.is_initialized,
+            governing_mint: v1.governing_mint,
+            owner: v1.owner,
+            delegate: Pubkey::default(),
+            deposited_amount: v1.deposited_amount,
+            active_votes: v1.active_votes,
+            bump: v1.bump,
+            created_at: v1.created_at,
+            updated_at: v1.updated_at,
+        }
+    }
+}
+
+impl From<TokenOwnerRecordV2> for TokenOwnerRecord {
+    fn from(v2: TokenOwnerRecordV2) -> Self {
+        TokenOwnerRecord {
+            is_initialized: v2.is_initialized,
+            governing_mint: v2.governing_mint,
+            owner: v2.owner,
+            delegate: v2.delegate,
+            deposited_amount: v2.deposited_amount,
+            active_votes: v2.active_votes,
+            vote_escrow: VoteEscrow::default(),
+            bump: v2.bump,
+            created_at: v2.created_at,
+            updated_at: v2.updated_at,
+        }
+    }
+}
+
+impl Migrate for TokenOwnerRecord {
+    fn decode_version(version: u8, src: &[u8]) -> Result<Self, ProgramError> {
+        let v1: Option<TokenOwnerRecordV1> = upgrade_or_decode(None::<TokenOwnerRecordV1>, version, 1, src)?;
+        let v2: Option<TokenOwnerRecordV2> = upgrade_or_decode(v1, version, 2, src)?;
+        v2.map(TokenOwnerRecord::from).ok_or_else(|| UnityVaultError::UnsupportedAccountVersion.into())
+    }
+
+    fn migration_authority(&self) -> Pubkey {
+        self.owner
+    }
+}
+
+/// Who a realm governs: the whole platform, or one community whose
+/// members are the only ones who can propose and vote.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RealmScope {
+    Platform,
+    Community(Pubkey),
+}
+
+impl RealmScope {
+    pub const LEN: usize = 1 + 32;
+
+    /// Key the realm address is derived from; the default key for the platform.
+    pub fn seed_key(&self) -> Pubkey {
+        match self {
+            RealmScope::Platform => Pubkey::default(),
+            RealmScope::Community(community) => *community,
+        }
+    }
+}
+
+/// Voting rules proposals in a realm inherit when they are created.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GovernanceConfig {
+    /// Minimum total vote weight for a vote to count.
+    pub quorum: u64,
+    pub min_approval_percentage: u8,
+    pub voting_duration: i64,
+    /// Time between a proposal passing and its transactions becoming executable.
+    pub execution_delay: i64,
+}
+
+impl GovernanceConfig {
+    pub fn validate(&self) -> Result<(), UnityVaultError> {
+        if self.min_approval_percentage > 100
+            || !(MIN_VOTING_DURATION..=MAX_VOTING_DURATION).contains(&self.voting_duration)
+            || !(0..=MAX_EXECUTION_DELAY).contains(&self.execution_delay)
+        {
+            return Err(UnityVaultError::InvalidGovernanceConfig);
+        }
+        Ok(())
+    }
+}
+
+/// A governance scope with its own rules, treasury and governance authority,
+/// stored at the PDA `[REALM_SEED, scope key, governing_mint]`.
+///
+/// `config` changes only through executed proposals, within the guard rails
+/// of `amendment::check_amendment`. `quorum_floor` is fixed at creation.
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct Realm {
+    pub is_initialized: bool,
+    pub scope: RealmScope,
+    pub governing_mint: Pubkey,
+    pub config: GovernanceConfig,
+    /// Lowest quorum governance can set.
+    pub quorum_floor: u64,
+    pub bump: u8,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl Realm {
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + // account type and version
+        1 + // is_initialized
+        RealmScope::LEN + // scope
+        32 + // governing_mint
+        8 + 1 + 8 + 8 + // config
+        8 + // quorum_floor
+        1 + // bump
+        8 + // created_at
+        8; // updated_at
+
+    pub fn find_address(program_id: &Pubkey, scope: &RealmScope, governing_mint: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[REALM_SEED, scope.seed_key().as_ref(), governing_mint.as_ref()],
+            program_id,
+        )
+    }
+}
+
+impl AccountState for Realm {
+    const ACCOUNT_TYPE: AccountType = AccountType::Realm;
+    const VERSION: u8 = 1;
+    const LEN: usize = Self::LEN;
+}
+
+/// Spam protection for proposals in `realm`, stored at the PDA
+/// `[PROPOSAL_DEPOSIT_CONFIG_SEED, realm]`. Only the governance authority can
+/// set it, so it changes through executed proposals. Until it is set,
+/// proposals need no deposit or minimum holding.
+#[derive(Debug, Clone, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct ProposalDepositConfig {
+    pub is_initialized: bool,
+    pub realm: Pubkey,
+    /// Escrowed from the proposer's deposited tokens on creation.
+    pub deposit_amount: u64,
+    /// Deposited tokens the proposer must hold, before the deposit is taken.
+    pub min_tokens_to_propose: u64,
+    pub bump: u8,
+    pub updated_at: i64,
+}
+
+impl ProposalDepositConfig {
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + // account type and version
+        1 + // is_initialized
+        32 + // realm
+        8 + // deposit_amount
+        8 + // min_tokens_to_propose
+        1 + // bump
+        8; // updated_at
+
+    pub fn find_address(program_id: &Pubkey, realm: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[PROPOSAL_DEPOSIT_CONFIG_SEED, realm.as_ref()], program_id)
+    }
+
+    /// Checks `proposer` may create a proposal and takes the deposit from it.
+    /// Returns the amount escrowed.
+    pub fn charge_proposer(&self, proposer: &mut TokenOwnerRecord) -> Result<u64, ProgramError> {
+        if proposer.deposited_amount < self.min_tokens_to_propose.max(self.deposit_amount) {
+            return Err(UnityVaultError::InsufficientGoverningTokens.into());
+        }
+        proposer.escrow_proposal_deposit(self.deposit_amount)?;
+        Ok(self.deposit_amount)
+    }
+}
+
+impl AccountState for ProposalDepositConfig {
+    const ACCOUNT_TYPE: AccountType = AccountType::ProposalDepositConfig;
+    const VERSION: u8 = 1;
+    const LEN: usize = Self::LEN;
+}
+
+/// Keys that can veto passed proposals in `realm` while they wait out the
+/// execution timelock, stored at the PDA `[VETO_COUNCIL_SEED, realm]`.
+/// `threshold` distinct members must sign a veto. Only the governance
+/// authority can set it; a council with no members is disabled.
+#[derive(Debug, Clone, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct VetoCouncil {
+    pub is_initialized: bool,
+    pub realm: Pubkey,
+    pub members: Vec<Pubkey>,
+    pub threshold: u8,
+    pub bump: u8,
+    pub updated_at: i64,
+}
+
+impl VetoCouncil {
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + // account type and version
+        1 + // is_initialized
+        32 + // realm
+        4 + MAX_VETO_COUNCIL_MEMBERS * 32 + // members
+        1 + // threshold
+        1 + // bump
+        8; // updated_at
+
+    pub fn find_address(program_id: &Pubkey, realm: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[VETO_COUNCIL_SEED, realm.as_ref()], program_id)
+    }
+
+    /// Checks the members are distinct and the threshold is reachable. An
+    /// empty council must have a zero threshold.
+    pub fn validate_members(members: &[Pubkey], threshold: u8) -> Result<(), UnityVaultError> {
+        check_vec_len("members", members, MAX_VETO_COUNCIL_MEMBERS)?;
+        let distinct = members.iter().enumerate().all(|(index, member)| !members[..index].contains(member));
+        let threshold_valid = if members.is_empty() {
+            threshold == 0
+        } else {
+            threshold > 0 && usize::from(threshold) <= members.len()
+        };
+        if !distinct || !threshold_valid {
+            return Err(UnityVaultError::InvalidVetoCouncil);
+        }
+        Ok(())
+    }
+
+    /// The members among `signers`, once each. Fails unless they reach the threshold.
+    pub fn approving_members(&self, signers: &[Pubkey]) -> Result<Vec<Pubkey>, UnityVaultError> {
+        let approving: Vec<Pubkey> = self.members.iter().filter(|member| signers.contains(member)).copied().collect();
+        if self.members.is_empty() || approving.len() < usize::from(self.threshold) {
+            return Err(UnityVaultError::VetoThresholdNotMet);
+        }
+        Ok(approving)
+    }
+}
+
+impl AccountState for VetoCouncil {
+    const ACCOUNT_TYPE: AccountType = AccountType::VetoCouncil;
+    const VERSION: u8 = 1;
+    const LEN: usize = Self::LEN;
+
+    fn validate_bounds(&self) -> Result<(), UnityVaultError> {
+        check_vec_len("members", &self.members, MAX_VETO_COUNCIL_MEMBERS)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::tests::assert_max_len_fits;
+    use crate::governance::escrow::MAX_LOCK_DURATION;
+
+    fn proposal_params(title_len: usize, description_len: usize) -> ProposalParams {
+        ProposalParams {
+            title: "t".repeat(title_len),
+            description: "d".repeat(description_len),
+            vote_mode: VoteMode::YesNo,
+            options: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_proposal_params_length_boundaries() {
+        assert_eq!(proposal_params(MAX_PROPOSAL_TITLE_LEN, MAX_PROPOSAL_DESCRIPTION_LEN).validate(), Ok(()));
+        assert_eq!(
+            proposal_params(MAX_PROPOSAL_TITLE_LEN + 1, 0).validate(),
+            Err(UnityVaultError::StringTooLong)
+        );
+        assert_eq!(
+            proposal_params(0, MAX_PROPOSAL_DESCRIPTION_LEN + 1).validate(),
+            Err(UnityVaultError::StringTooLong)
+        );
+    }
+
+    fn test_proposal() -> Proposal {
+        let params = proposal_params(MAX_PROPOSAL_TITLE_LEN, MAX_PROPOSAL_DESCRIPTION_LEN);
+        Proposal {
+            is_initialized: true,
+            authority: Pubkey::new_unique(),
+            realm: Pubkey::new_unique(),
+            scope: RealmScope::Community(Pubkey::new_unique()),
+            governing_mint: Pubkey::new_unique(),
+            title: params.title.clone(),
+            description: params.description.clone(),
+            status: ProposalStatus::Draft,
+            voting_duration: 86400,
+            min_votes: 1,
+            min_approval_percentage: 50,
+            yes_votes: 0,
+            no_votes: 0,
+            abstain_votes: 0,
+            vote_mode: params.vote_mode,
+            options: params.options.clone(),
+            option_votes: Vec::new(),
+            option_voter_weight: 0,
+            winning_option: None,
+            voting_started_at: 0,
+            voting_completed_at: 0,
+            execution_delay: 0,
+            transaction_count: 0,
+            executed_transaction_count: 0,
+            transactions_hash: [0; 32],
+            content_hash: [0; 32],
+            deposit_amount: 0,
+            vetoed_by: Vec::new(),
+            created_at: 0,
+            updated_at: 0,
+            executed_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_pack_max_length_fields_fits_len() {
+        let mut proposal = test_proposal();
+        proposal.vote_mode = VoteMode::SingleChoice;
+        proposal.options = vec!["o".repeat(MAX_PROPOSAL_OPTION_LEN); MAX_PROPOSAL_OPTIONS];
+        proposal.option_votes = vec![u64::MAX; MAX_PROPOSAL_OPTIONS];
+        proposal.winning_option = Some(0);
+        proposal.vetoed_by = vec![Pubkey::new_unique(); MAX_VETO_COUNCIL_MEMBERS];
+        assert_max_len_fits(&proposal);
+    }
+
+    /// Writes `layout` the way an account was packed at `version`.
+    fn encode_layout<T: BorshSerialize>(account_type: AccountType, version: u8, layout: &T) -> Vec<u8> {
+        let mut data = vec![account_type as u8, version];
+        data.extend(borsh::to_vec(layout).unwrap());
+        data
+    }
+
+    #[test]
+    fn test_migrate_v1_proposal() {
+        let authority = Pubkey::new_unique();
+        let v1 = ProposalV1 {
+            is_initialized: true,
+            authority,
+            title: "Fund the well".to_string(),
+            description: String::new(),
+            status: ProposalStatus::Passed,
+            voting_duration: 86_400,
+            min_votes: 5,
+            min_approval_percentage: 60,
+            yes_votes: 7,
+            no_votes: 2,
+            abstain_votes: 1,
+            created_at: 100,
+            updated_at: 200,
+            executed_at: 0,
+        };
+        let data = encode_layout(AccountType::Proposal, 1, &v1);
+        // Until migrated the account can't be read as the current layout
+        assert!(Proposal::unpack(&data).is_err());
+
+        let proposal = Proposal::decode_version(1, &data).unwrap();
+        assert_eq!(proposal.migration_authority(), authority);
+        assert_eq!(proposal.title, "Fund the well");
+        assert_eq!((proposal.yes_votes, proposal.no_votes, proposal.abstain_votes), (7, 2, 1));
+        assert_eq!((proposal.voting_started_at, proposal.updated_at), (100, 200));
+        assert_eq!((proposal.realm, proposal.scope), (Pubkey::default(), RealmScope::Platform));
+        assert_eq!(proposal.vote_mode, VoteMode::YesNo);
+        // Passed, but there's no realm authority to execute it
+        assert!(!proposal.is_executable(i64::MAX));
+
+        let mut repacked = vec![0; Proposal::LEN];
+        proposal.pack(&mut repacked).unwrap();
+        assert_eq!(Proposal::unpack(&repacked).unwrap().title, "Fund the well");
+
+        assert_eq!(
+            Proposal::decode_version(Proposal::VERSION, &data).err(),
+            Some(UnityVaultError::UnsupportedAccountVersion.into())
+        );
+    }
+
+    #[test]
+    fn test_migrate_every_token_owner_record_layout() {
+        let owner = Pubkey::new_unique();
+        let governing_mint = Pubkey::new_unique();
+        let decode = |version: u8, data: Vec<u8>| {
+            let record = TokenOwnerRecord::decode_version(version, &data).unwrap();
+            assert_eq!(record.migration_authority(), owner);
+            assert_eq!(record.governing_mint, governing_mint);
+            assert_eq!((record.deposited_amount, record.active_votes), (1_000, 2));
+            assert_eq!(record.vote_escrow, VoteEscrow::default());
+            assert!(TokenOwnerRecord::unpack(&data).is_err());
+
+            let mut repacked = vec![0; TokenOwnerRecord::LEN];
+            record.pack(&mut repacked).unwrap();
+            TokenOwnerRecord::unpack(&repacked).unwrap()
+        };
+
+        let v1 = TokenOwnerRecordV1 {
+            is_initialized: true,
+            governing_mint,
+            owner,
+            deposited_amount: 1_000,
+            active_votes: 2,
+            bump: 255,
+            created_at: 100,
+            updated_at: 200,
+        };
+        let record = decode(1, encode_layout(AccountType::TokenOwnerRecord, 1, &v1));
+        assert_eq!(record.delegate, Pubkey::default());
+        assert_eq!((record.bump, record.updated_at), (255, 200));
+
+        let delegate = Pubkey::new_unique();
+        let mut v2 = TokenOwnerRecordV2::from(v1);
+        v2.delegate = delegate;
+        let record = decode(2, encode_layout(AccountType::TokenOwnerRecord, 2, &v2));
+        assert_eq!(record.delegate, delegate);
+
+        assert_eq!(
+            TokenOwnerRecord::decode_version(
+                TokenOwnerRecord::VERSION + 1,
+                &encode_layout(AccountType::TokenOwnerRecord, 2, &v2)
+            )
+            .err(),
+            Some(UnityVaultError::UnsupportedAccountVersion.into())
+        );
+    }
+
+    #[test]
+    fn test_migrate_every_vote_record_layout() {
+        let voter = Pubkey::new_unique();
+        let proposal = Pubkey::new_unique();
+        let decode = |version: u8, data: Vec<u8>| {
+            let record = VoteRecord::decode_version(version, &data).unwrap();
+            assert_eq!(record.migration_authority(), voter);
+            // Votes before delegation were always cast by the voter
+            assert_eq!(record.cast_by, voter);
+            assert_eq!(record.proposal, proposal);
+            assert_eq!((record.vote_type, record.weight), (VoteType::No, 40));
+            assert!(VoteRecord::unpack(&data).is_err());
+
+            let mut repacked = vec![0; VoteRecord::LEN];
+            record.pack(&mut repacked).unwrap();
+            VoteRecord::unpack(&repacked).unwrap()
+        };
+
+        let v1 = VoteRecordV1 {
+            is_initialized: true,
+            proposal,
+            voter,
+            vote_type: VoteType::No,
+            weight: 40,
+            bump: 254,
+            created_at: 100,
+            updated_at: 200,
+        };
+        let record = decode(1, encode_layout(AccountType::VoteRecord, 1, &v1));
+        assert!(!record.is_relinquished);
+        assert_eq!((record.bump, record.updated_at), (254, 200));
+
+        let mut v2 = VoteRecordV2::from(v1);
+        v2.is_relinquished = true;
+        let record = decode(2, encode_layout(AccountType::VoteRecord, 2, &v2));
+        assert!(record.is_relinquished);
+
+        assert_eq!(
+            VoteRecord::decode_version(VoteRecord::VERSION + 1, &encode_layout(AccountType::VoteRecord, 2, &v2)).err(),
+            Some(UnityVaultError::UnsupportedAccountVersion.into())
+        );
+    }
+
+    #[test]
+    fn test_change_vote_moves_weight() {
+        let mut proposal = test_proposal();
+        proposal.add_vote(VoteType::Yes, 1).unwrap();
+        proposal.add_vote(VoteType::No, 1).unwrap();
+
+        proposal.remove_vote(VoteType::Yes, 1).unwrap();
+        proposal.add_vote(VoteType::Abstain, 1).unwrap();
+        assert_eq!((proposal.yes_votes, proposal.no_votes, proposal.abstain_votes), (0, 1, 1));
+
+        // Removing weight that was never cast fails instead of wrapping
+        assert_eq!(proposal.remove_vote(VoteType::Yes, 1), Err(ProgramError::ArithmeticOverflow));
+    }
+
+    #[test]
+    fn test_voting_window() {
+        let mut proposal = test_proposal();
+        proposal.voting_started_at = 1_000;
+        assert!(!proposal.is_voting_open(1_000));
+
+        proposal.status = ProposalStatus::Active;
+        assert!(proposal.is_voting_open(1_000));
+        assert!(proposal.is_voting_open(1_000 + 86_399));
+        assert!(!proposal.is_voting_open(1_000 + 86_400));
+    }
+
+    #[test]
+    fn test_vote_record_address_is_per_voter() {
+        let program_id = Pubkey::new_unique();
+        let proposal = Pubkey::new_unique();
+        let (alice, _) = VoteRecord::find_address(&program_id, &proposal, &Pubkey::new_unique());
+        let (bob, _) = VoteRecord::find_address(&program_id, &proposal, &Pubkey::new_unique());
+        assert_ne!(alice, bob);
+
+        let voter = Pubkey::new_unique();
+        let (address, bump) = VoteRecord::find_address(&program_id, &proposal, &voter);
+        let derived = Pubkey::create_program_address(
+            &[VOTE_RECORD_SEED, proposal.as_ref(), voter.as_ref(), &[bump]],
+            &program_id,
+        );
+        assert_eq!(derived, Ok(address));
+
+        let record = VoteRecord {
+            is_initialized: true,
+            proposal,
+            voter,
+            cast_by: voter,
+            vote_type: VoteType::No,
+            weight: 1,
+            is_relinquished: false,
+            bump,
+            created_at: 0,
+            updated_at: 0,
+        };
+        let mut data = vec![0; VoteRecord::LEN];
+        record.pack(&mut data).unwrap();
+        assert!(VoteRecord::is_initialized_account(&data));
+        assert_eq!(VoteRecord::unpack(&data).unwrap().vote_type, VoteType::No);
+    }
+
+    #[test]
+    fn test_token_owner_record_locks_deposits_while_voting() {
+        let mut record = TokenOwnerRecord {
+            is_initialized: true,
+            governing_mint: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            delegate: Pubkey::default(),
+            deposited_amount: 0,
+            active_votes: 0,
+            vote_escrow: VoteEscrow::default(),
+            bump: 255,
+            created_at: 0,
+            updated_at: 0,
+        };
+        record.deposit(1_000).unwrap();
+        assert_eq!(record.voting_weight(0), 1_000);
+
+        record.active_votes = 1;
+        assert_eq!(record.withdraw(1), Err(UnityVaultError::GoverningTokensLocked.into()));
+
+        record.active_votes = 0;
+        assert_eq!(record.withdraw(1_001), Err(ProgramError::InsufficientFunds));
+        record.withdraw(400).unwrap();
+        assert_eq!(record.voting_weight(0), 600);
+
+        assert_eq!(record.deposit(u64::MAX), Err(ProgramError::ArithmeticOverflow));
+    }
+
+    #[test]
+    fn test_token_owner_record_adds_escrowed_weight() {
+        let mut record = TokenOwnerRecord { deposited_amount: 100, ..Default::default() };
+        record.vote_escrow.lock(1_000, MAX_LOCK_DURATION, 10).unwrap();
+
+        // Locked power counts only for proposals whose voting started after the lock
+        assert_eq!(record.voting_weight(9), 100);
+        assert_eq!(record.voting_weight(10), 4_100);
+
+        record.active_votes = 1;
+        assert_eq!(record.withdraw_locked(MAX_LOCK_DURATION + 10), Err(UnityVaultError::GoverningTokensLocked.into()));
+        record.active_votes = 0;
+        assert_eq!(record.withdraw_locked(MAX_LOCK_DURATION + 10), Ok(1_000));
+        assert_eq!(record.voting_weight(MAX_LOCK_DURATION + 10), 100);
+    }
+
+    #[test]
+    fn test_tallies_hold_token_weights() {
+        let mut proposal = test_proposal();
+        proposal.add_vote(VoteType::Yes, u64::from(u32::MAX) + 1).unwrap();
+        assert_eq!(proposal.yes_votes, 1 << 32);
+        assert_eq!(proposal.add_vote(VoteType::Yes, u64::MAX), Err(ProgramError::ArithmeticOverflow));
+    }
+
+    #[test]
+    fn test_migrate_v1_widens_tallies() {
+        let authority = Pubkey::new_unique();
+        let mut data = vec![AccountType::Proposal as u8, 1];
+        data.extend(borsh::to_vec(&(
+            (true, authority, "t".to_string(), "d".to_string(), ProposalStatus::Active),
+            (86_400i64, 3u32, 60u8, 5u32, 1u32, 2u32),
+            (10i64, 20i64, 0i64),
+        )).unwrap());
+
+        let proposal = Proposal::decode_version(1, &data).unwrap();
+        assert_eq!(proposal.migration_authority(), authority);
+        assert_eq!(proposal.governing_mint, Pubkey::default());
+        assert_eq!(proposal.min_votes, 3);
+        assert_eq!((proposal.yes_votes, proposal.no_votes, proposal.abstain_votes), (5, 1, 2));
+        assert_eq!(proposal.updated_at, 20);
+        assert!(Proposal::decode_version(Proposal::VERSION, &data).is_err());
+    }
+
+    #[test]
+    fn test_tally_after_voting_window() {
+        let mut proposal = test_proposal();
+        proposal.min_votes = 10;
+        proposal.min_approval_percentage = 60;
+
+        proposal.yes_votes = 6;
+        proposal.no_votes = 4;
+        assert!(proposal.tally().passed);
+
+        proposal.yes_votes = 5;
+        proposal.no_votes = 5;
+        assert!(!proposal.tally().passed);
+
+        // Below the minimum vote weight
+        proposal.yes_votes = 9;
+        proposal.no_votes = 0;
+        assert!(!proposal.tally().quorum_reached);
+
+        // Nobody voted
+        proposal.yes_votes = 0;
+        assert!(!proposal.tally().passed);
+    }
+
+    #[test]
+    fn test_execution_timelock() {
+        let mut proposal = test_proposal();
+        proposal.voting_completed_at = 1_000;
+        proposal.execution_delay = 3_600;
+        assert!(!proposal.is_executable(10_000));
+
+        proposal.status = ProposalStatus::Passed;
+        assert!(!proposal.is_executable(1_000 + 3_599));
+        assert!(proposal.is_executable(1_000 + 3_600));
+    }
+
+    fn governance_config() -> GovernanceConfig {
+        GovernanceConfig {
+            quorum: 10,
+            min_approval_percentage: 60,
+            voting_duration: 86400,
+            execution_delay: 3600,
+        }
+    }
+
+    #[test]
+    fn test_governance_config_rule_bounds() {
+        let valid = governance_config();
+        assert_eq!(valid.validate(), Ok(()));
+
+        let invalid = [
+            GovernanceConfig { min_approval_percentage: 101, ..valid },
+            GovernanceConfig { voting_duration: MIN_VOTING_DURATION - 1, ..valid },
+            GovernanceConfig { voting_duration: MAX_VOTING_DURATION + 1, ..valid },
+            GovernanceConfig { execution_delay: -1, ..valid },
+            GovernanceConfig { execution_delay: MAX_EXECUTION_DELAY + 1, ..valid },
+        ];
+        for config in invalid {
+            assert_eq!(config.validate(), Err(UnityVaultError::InvalidGovernanceConfig));
+        }
+
+        let boundaries = [
+            GovernanceConfig { min_approval_percentage: 100, ..valid },
+            GovernanceConfig { voting_duration: MIN_VOTING_DURATION, ..valid },
+            GovernanceConfig { voting_duration: MAX_VOTING_DURATION, ..valid },
+            GovernanceConfig { execution_delay: MAX_EXECUTION_DELAY, ..valid },
+        ];
+        for config in boundaries {
+            assert_eq!(config.validate(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn test_proposal_inherits_realm_config() {
+        let config = governance_config();
+        let mut proposal = test_proposal();
+        proposal.apply_config(&config);
+        assert_eq!(proposal.tally_rules(), TallyRules { quorum: 10, approval_threshold_bps: 6_000 });
+        assert_eq!(proposal.voting_duration, config.voting_duration);
+        assert_eq!(proposal.execution_delay, config.execution_delay);
+
+        let realm = Realm {
+            is_initialized: true,
+            scope: RealmScope::Community(Pubkey::new_unique()),
+            governing_mint: Pubkey::new_unique(),
+            config,
+            quorum_floor: 1,
+            bump: 255,
+            created_at: i64::MAX,
+            updated_at: i64::MAX,
+        };
+        let mut data = vec![0; Realm::LEN];
+        realm.pack(&mut data).unwrap();
+        assert_eq!(Realm::unpack(&data).unwrap(), realm);
+
+        // Each community, and the platform, gets its own realm per mint
+        let program_id = Pubkey::new_unique();
+        let mint = realm.governing_mint;
+        let community_realm = Realm::find_address(&program_id, &realm.scope, &mint).0;
+        assert_ne!(community_realm, Realm::find_address(&program_id, &RealmScope::Platform, &mint).0);
+        assert_ne!(community_realm, Realm::find_address(&program_id, &realm.scope, &Pubkey::new_unique()).0);
+    }
+
+    #[test]
+    fn test_content_hash_covers_rules_and_transactions() {
+        let proposal = test_proposal();
+        let hash = proposal.compute_content_hash();
+        assert_eq!(hash, proposal.clone().compute_content_hash());
+
+        let mut changed = proposal.clone();
+        changed.min_approval_percentage += 1;
+        assert_ne!(changed.compute_content_hash(), hash);
+
+        let mut changed = proposal.clone();
+        changed.realm = Pubkey::new_unique();
+        assert_ne!(changed.compute_content_hash(), hash);
+
+        // Moving text between title and description changes the hash
+        let mut moved = proposal.clone();
+        moved.title.pop();
+        moved.description.insert(0, 't');
+        assert_ne!(moved.compute_content_hash(), hash);
+
+        let instruction = ProposalInstruction { program_id: Pubkey::new_unique(), accounts: vec![], data: vec![1] };
+        let other = ProposalInstruction { data: vec![2], ..instruction.clone() };
+        let mut first = proposal.clone();
+        first.record_transaction(&instruction).unwrap();
+        first.record_transaction(&other).unwrap();
+        let mut second = proposal;
+        second.record_transaction(&other).unwrap();
+        second.record_transaction(&instruction).unwrap();
+        assert_ne!(first.compute_content_hash(), hash);
+        assert_ne!(first.compute_content_hash(), second.compute_content_hash());
+    }
+
+    #[test]
+    fn test_proposal_transaction_round_trip() {
+        let program_id = Pubkey::new_unique();
+        let proposal = Pubkey::new_unique();
+        let (address, bump) = ProposalTransaction::find_address(&program_id, &proposal, 3);
+        assert_ne!(address, ProposalTransaction::find_address(&program_id, &proposal, 4).0);
+
+        let meta = ProposalAccountMeta { pubkey: Pubkey::new_unique(), is_signer: true, is_writable: false };
+        let mut transaction = ProposalTransaction {
+            is_initialized: true,
+            proposal,
+            index: 3,
+            instruction: ProposalInstruction {
+                program_id: Pubkey::new_unique(),
+                accounts: vec![meta; MAX_INSTRUCTION_ACCOUNTS],
+                data: vec![7; MAX_INSTRUCTION_DATA_LEN],
+            },
+            bump,
+            executed_at: 0,
+        };
+
+        let mut data = vec![0; ProposalTransaction::LEN];
+        transaction.pack(&mut data).unwrap();
+        let unpacked = ProposalTransaction::unpack(&data).unwrap();
+        assert_eq!(unpacked.instruction, transaction.instruction);
+        assert!(!unpacked.is_executed());
+
+        let instruction = unpacked.instruction.to_instruction();
+        assert_eq!(instruction.accounts.len(), MAX_INSTRUCTION_ACCOUNTS);
+        assert!(instruction.accounts[0].is_signer && !instruction.accounts[0].is_writable);
+
+        transaction.instruction.data.push(0);
+        assert_eq!(transaction.pack(&mut data), Err(UnityVaultError::TooManyEntries.into()));
+    }
+
+    #[test]
+    fn test_delegate_votes_and_owner_override() {
+        let owner = Pubkey::new_unique();
+        let delegate = Pubkey::new_unique();
+        let stranger = Pubkey::new_unique();
+        let mut record = TokenOwnerRecord {
+            is_initialized: true,
+            governing_mint: Pubkey::new_unique(),
+            owner,
+            delegate: Pubkey::default(),
+            deposited_amount: 100,
+            active_votes: 0,
+            vote_escrow: VoteEscrow::default(),
+            bump: 255,
+            created_at: 0,
+            updated_at: 0,
+        };
+        assert!(record.is_owner_or_delegate(&owner));
+        assert!(!record.is_owner_or_delegate(&delegate));
+        // An unset delegate is not a wildcard
+        assert!(!record.is_owner_or_delegate(&Pubkey::default()));
+
+        record.delegate = delegate;
+        assert!(record.is_owner_or_delegate(&delegate));
+        assert!(!record.is_owner_or_delegate(&stranger));
+
+        let mut vote = VoteRecord {
+            is_initialized: true,
+            proposal: Pubkey::new_unique(),
+            voter: owner,
+            cast_by: delegate,
+            vote_type: VoteType::Yes,
+            weight: 100,
+            is_relinquished: false,
+            bump: 255,
+            created_at: 0,
+            updated_at: 0,
+        };
+        assert!(vote.can_be_changed_by(&delegate, &record));
+        assert!(vote.can_be_changed_by(&owner, &record));
+        assert!(!vote.can_be_changed_by(&stranger, &record));
+
+        // Once the owner overrides, the delegate can no longer change it back
+        vote.cast_by = owner;
+        assert!(!vote.can_be_changed_by(&delegate, &record));
+
+        // Nor after the delegation is withdrawn
+        vote.cast_by = delegate;
+        record.delegate = Pubkey::default();
+        assert!(!vote.can_be_changed_by(&delegate, &record));
+    }
+
+    #[test]
+    fn test_proposal_deposit_charge_and_settlement() {
+        let config = ProposalDepositConfig {
+            is_initialized: true,
+            realm: Pubkey::new_unique(),
+            deposit_amount: 10,
+            min_tokens_to_propose: 50,
+            bump: 255,
+            updated_at: 0,
+        };
+        let mut proposer = TokenOwnerRecord {
+            is_initialized: true,
+            governing_mint: Pubkey::new_unique(),
+            owner: Pubkey::new_unique(),
+            delegate: Pubkey::default(),
+            deposited_amount: 49,
+            active_votes: 1,
+            vote_escrow: VoteEscrow::default(),
+            bump: 255,
+            created_at: 0,
+            updated_at: 0,
+        };
+        assert_eq!(
+            config.charge_proposer(&mut proposer),
+            Err(UnityVaultError::InsufficientGoverningTokens.into())
+        );
+        assert_eq!(proposer.deposited_amount, 49);
+
+        // Active votes do not block the escrow
+        proposer.deposited_amount = 50;
+        assert_eq!(config.charge_proposer(&mut proposer), Ok(10));
+        assert_eq!(proposer.deposited_amount, 40);
+
+        // The deposit itself must be covered even without a minimum
+        let config = ProposalDepositConfig { min_tokens_to_propose: 0, deposit_amount: 41, ..config };
+        assert_eq!(
+            config.charge_proposer(&mut proposer),
+            Err(UnityVaultError::InsufficientGoverningTokens.into())
+        );
+
+        // An unset config charges nothing
+        assert_eq!(ProposalDepositConfig::default().charge_proposer(&mut proposer), Ok(0));
+        assert_eq!(proposer.deposited_amount, 40);
+
+        let mut proposal = test_proposal();
+        proposal.min_votes = 100;
+        proposal.status = ProposalStatus::Active;
+        assert_eq!(proposal.deposit_refundable(), None);
+
+        proposal.status = ProposalStatus::Rejected;
+        proposal.no_votes = 99;
+        assert_eq!(proposal.deposit_refundable(), Some(false));
+        proposal.abstain_votes = 1;
+        assert_eq!(proposal.deposit_refundable(), Some(true));
+        proposal.status = ProposalStatus::Cancelled;
+        assert_eq!(proposal.deposit_refundable(), Some(false));
+    }
+
+    #[test]
+    fn test_proposal_deposit_config_round_trip() {
+        let config = ProposalDepositConfig {
+            is_initialized: true,
+            realm: Pubkey::new_unique(),
+            deposit_amount: u64::MAX,
+            min_tokens_to_propose: u64::MAX,
+            bump: 255,
+            updated_at: i64::MAX,
+        };
+        let mut data = vec![0; ProposalDepositConfig::LEN];
+        config.pack(&mut data).unwrap();
+        assert_eq!(ProposalDepositConfig::unpack(&data).unwrap(), config);
+    }
+
+    #[test]
+    fn test_multiple_choice_params() {
+        let options = |count: usize, len: usize| -> Vec<String> { (0..count).map(|_| "o".repeat(len)).collect() };
+        let poll = |vote_mode: VoteMode, options: Vec<String>| ProposalParams {
+            vote_mode,
+            options,
+            ..proposal_params(10, 10)
+        };
+
+        assert_eq!(poll(VoteMode::SingleChoice, options(2, MAX_PROPOSAL_OPTION_LEN)).validate(), Ok(()));
+        assert_eq!(poll(VoteMode::Approval, options(MAX_PROPOSAL_OPTIONS, 1)).validate(), Ok(()));
+        assert_eq!(
+            poll(VoteMode::Approval, options(MAX_PROPOSAL_OPTIONS + 1, 1)).validate(),
+            Err(UnityVaultError::TooManyEntries)
+        );
+        assert_eq!(
+            poll(VoteMode::SingleChoice, options(2, MAX_PROPOSAL_OPTION_LEN + 1)).validate(),
+            Err(UnityVaultError::StringTooLong)
+        );
+        for invalid in [
+            poll(VoteMode::SingleChoice, options(1, 1)),
+            poll(VoteMode::Approval, options(3, 0)),
+            poll(VoteMode::YesNo, options(2, 1)),
+        ] {
+            assert_eq!(invalid.validate(), Err(UnityVaultError::InvalidProposalParams));
+        }
+
+        // A proposal with every option at its budget still fits
+        let mut proposal = test_proposal();
+        proposal.vote_mode = VoteMode::Approval;
+        proposal.options = options(MAX_PROPOSAL_OPTIONS, MAX_PROPOSAL_OPTION_LEN);
+        proposal.option_votes = vec![u64::MAX; MAX_PROPOSAL_OPTIONS];
+        proposal.winning_option = Some(0);
+        let mut data = vec![0; Proposal::LEN];
+        proposal.pack(&mut data).unwrap();
+        assert_eq!(Proposal::unpack(&data).unwrap().options, proposal.options);
+    }
+
+    #[test]
+    fn test_vote_choices_follow_vote_mode() {
+        let mut proposal = test_proposal();
+        assert_eq!(proposal.check_vote(VoteType::Yes), Ok(()));
+        assert_eq!(proposal.check_vote(VoteType::Options(1)), Err(UnityVaultError::InvalidVoteChoice));
+
+        proposal.vote_mode = VoteMode::SingleChoice;
+        proposal.options = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        proposal.option_votes = vec![0; 3];
+        assert_eq!(proposal.check_vote(VoteType::Abstain), Ok(()));
+        assert_eq!(proposal.check_vote(VoteType::Options(0b100)), Ok(()));
+        for invalid in [VoteType::Yes, VoteType::Options(0), VoteType::Options(0b11), VoteType::Options(0b1000)] {
+            assert_eq!(proposal.check_vote(invalid), Err(UnityVaultError::InvalidVoteChoice));
+        }
+        assert!(proposal.add_vote(VoteType::Options(0b11), 1).is_err());
+
+        proposal.vote_mode = VoteMode::Approval;
+        assert_eq!(proposal.check_vote(VoteType::Options(0b111)), Ok(()));
+        assert_eq!(proposal.check_vote(VoteType::Options(0)), Err(UnityVaultError::InvalidVoteChoice));
+        assert_eq!(proposal.check_vote(VoteType::Options(u16::MAX)), Err(UnityVaultError::InvalidVoteChoice));
+
+        // All sixteen options can be addressed
+        proposal.options = vec!["o".to_string(); MAX_PROPOSAL_OPTIONS];
+        assert_eq!(proposal.check_vote(VoteType::Options(u16::MAX)), Ok(()));
+    }
+
+    #[test]
+    fn test_approval_votes_count_weight_once() {
+        let mut proposal = test_proposal();
+        proposal.vote_mode = VoteMode::Approval;
+        proposal.options = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        proposal.option_votes = vec![0; 3];
+        proposal.min_votes = 10;
+        proposal.min_approval_percentage = 50;
+
+        proposal.add_vote(VoteType::Options(0b011), 6).unwrap();
+        proposal.add_vote(VoteType::Options(0b110), 4).unwrap();
+        assert_eq!(proposal.option_votes, vec![6, 10, 4]);
+        assert_eq!(proposal.option_voter_weight, 10);
+        let result = proposal.tally();
+        assert_eq!(result.winning_option, Some(1));
+        assert!(result.passed);
+
+        // Changing a ballot moves its weight between options
+        proposal.remove_vote(VoteType::Options(0b110), 4).unwrap();
+        proposal.add_vote(VoteType::Abstain, 4).unwrap();
+        assert_eq!(proposal.option_votes, vec![6, 6, 0]);
+        assert_eq!(proposal.option_voter_weight, 6);
+        let result = proposal.tally();
+        assert_eq!(result.winning_option, Some(0));
+        assert!(result.passed);
+    }
+
+    #[test]
+    fn test_veto_council_threshold() {
+        let members: Vec<Pubkey> = (0..3).map(|_| Pubkey::new_unique()).collect();
+        assert!(VetoCouncil::validate_members(&members, 2).is_ok());
+        assert!(VetoCouncil::validate_members(&[], 0).is_ok());
+        assert_eq!(VetoCouncil::validate_members(&members, 0), Err(UnityVaultError::InvalidVetoCouncil));
+        assert_eq!(VetoCouncil::validate_members(&members, 4), Err(UnityVaultError::InvalidVetoCouncil));
+        assert_eq!(VetoCouncil::validate_members(&[], 1), Err(UnityVaultError::InvalidVetoCouncil));
+        assert_eq!(
+            VetoCouncil::validate_members(&[members[0], members[1], members[0]], 1),
+            Err(UnityVaultError::InvalidVetoCouncil)
+        );
+        let too_many: Vec<Pubkey> = (0..=MAX_VETO_COUNCIL_MEMBERS).map(|_| Pubkey::new_unique()).collect();
+        assert_eq!(VetoCouncil::validate_members(&too_many, 1), Err(UnityVaultError::TooManyEntries));
+
+        let council = VetoCouncil { is_initialized: true, members: members.clone(), threshold: 2, ..Default::default() };
+        let outsider = Pubkey::new_unique();
+        assert_eq!(
+            council.approving_members(&[members[0], outsider]),
+            Err(UnityVaultError::VetoThresholdNotMet)
+        );
+        // Signers count once each, and outsiders are ignored
+        assert_eq!(
+            council.approving_members(&[members[2], outsider, members[0], members[2]]),
+            Ok(vec![members[0], members[2]])
+        );
+        assert_eq!(
+            VetoCouncil::default().approving_members(&[]),
+            Err(UnityVaultError::VetoThresholdNotMet)
+        );
+
+        let mut data = vec![0; VetoCouncil::LEN];
+        let full = VetoCouncil { members: too_many[..MAX_VETO_COUNCIL_MEMBERS].to_vec(), ..council };
+        full.pack(&mut data).unwrap();
+        assert_eq!(VetoCouncil::unpack(&data).unwrap(), full);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_veto_only_during_timelock() {
+        let signers = vec![Pubkey::new_unique()];
+        let mut proposal = test_proposal();
+        proposal.voting_completed_at = 1_000;
+        proposal.execution_delay = 3_600;
+        assert_eq!(proposal.veto(signers.clone(), 1_000), Err(UnityVaultError::VetoWindowClosed));
+
+        proposal.status = ProposalStatus::Passed;
+        assert_eq!(proposal.veto(signers.clone(), 1_000 + 3_600), Err(UnityVaultError::VetoWindowClosed));
+        proposal.veto(signers.clone(), 1_000 + 3_599).unwrap();
+        assert_eq!(proposal.status, ProposalStatus::Vetoed);
+        assert_eq!(proposal.vetoed_by, signers);
+        assert!(!proposal.is_executable(10_000));
+        assert_eq!(proposal.deposit_refundable(), Some(false));
+
+        let mut data = vec![0; Proposal::LEN];
+        proposal.vetoed_by = (0..MAX_VETO_COUNCIL_MEMBERS).map(|_| Pubkey::new_unique()).collect();
+        proposal.pack(&mut data).unwrap();
+        assert_eq!(Proposal::unpack(&data).unwrap().vetoed_by, proposal.vetoed_by);
+    }
+}
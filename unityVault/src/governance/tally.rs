@@ -0,0 +1,230 @@
+use borsh::{BorshSerialize, BorshDeserialize};
+
+/// Denominator of every ratio in this module: 10_000 basis points is 100%.
+pub const BASIS_POINTS: u16 = 10_000;
+
+/// Rules a vote is decided by.
+///
+/// Abstentions count toward `quorum` but not toward approval, which is the
+/// share of yes weight in the yes and no weight combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct TallyRules {
+    /// Minimum total weight (yes, no and abstain) for the vote to count.
+    pub quorum: u64,
+    /// Minimum approval, in basis points, for the proposal to pass.
+    pub approval_threshold_bps: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TallyResult {
+    pub total_votes: u128,
+    pub quorum_reached: bool,
+    /// Approval rounded down to whole basis points. Zero when nobody voted yes or no.
+    pub approval_bps: u16,
+    pub passed: bool,
+    /// Leading option of a multiple-choice vote. Always `None` for yes/no votes.
+    pub winning_option: Option<u8>,
+}
+
+/// Decides a vote using integer arithmetic only.
+///
+/// Totals are summed in `u128`, so no combination of `u64` tallies can
+/// overflow, and the threshold is checked by cross-multiplication, so a
+/// proposal exactly at the threshold passes regardless of rounding.
+pub fn tally(yes: u64, no: u64, abstain: u64, rules: &TallyRules) -> TallyResult {
+    let (yes, no, abstain) = (yes as u128, no as u128, abstain as u128);
+    let total_votes = yes + no + abstain;
+    let decisive = yes + no;
+
+    let quorum_reached = total_votes >= rules.quorum as u128;
+    let approval_bps = (yes * BASIS_POINTS as u128).checked_div(decisive).unwrap_or(0) as u16;
+    let threshold_met = yes > 0 && yes * BASIS_POINTS as u128 >= rules.approval_threshold_bps as u128 * decisive;
+
+    TallyResult {
+        total_votes,
+        quorum_reached,
+        approval_bps,
+        passed: quorum_reached && threshold_met,
+        winning_option: None,
+    }
+}
+
+/// Decides a multiple-choice vote.
+///
+/// `voter_weight` is the weight of everyone who picked at least one option,
+/// counted once per voter, so in approval voting `option_votes` can sum to
+/// more than it. Approval is the winner's share of `voter_weight`. The winner
+/// is the option with the most weight; a tie goes to the option listed
+/// first. Options without any weight never win.
+pub fn tally_options(option_votes: &[u64], voter_weight: u64, abstain: u64, rules: &TallyRules) -> TallyResult {
+    let mut winner: Option<(usize, u64)> = None;
+    for (index, &votes) in option_votes.iter().enumerate() {
+        if votes > 0 && winner.map_or(true, |(_, leading)| votes > leading) {
+            winner = Some((index, votes));
+        }
+    }
+
+    let (voter_weight, abstain) = (voter_weight as u128, abstain as u128);
+    let total_votes = voter_weight + abstain;
+    let winning_votes = winner.map_or(0, |(_, votes)| votes as u128);
+
+    let quorum_reached = total_votes >= rules.quorum as u128;
+    let approval_bps = (winning_votes * BASIS_POINTS as u128).checked_div(voter_weight).unwrap_or(0) as u16;
+    let threshold_met = winning_votes > 0
+        && winning_votes * BASIS_POINTS as u128 >= rules.approval_threshold_bps as u128 * voter_weight;
+
+    TallyResult {
+        total_votes,
+        quorum_reached,
+        approval_bps,
+        passed: quorum_reached && threshold_met,
+        winning_option: winner.map(|(index, _)| index as u8),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(quorum: u64, approval_threshold_bps: u16) -> TallyRules {
+        TallyRules { quorum, approval_threshold_bps }
+    }
+
+    #[test]
+    fn test_no_votes_never_passes() {
+        for threshold in [0, 1, 5_000, BASIS_POINTS] {
+            let result = tally(0, 0, 0, &rules(0, threshold));
+            assert_eq!(result.approval_bps, 0);
+            assert!(result.quorum_reached);
+            assert!(!result.passed);
+        }
+        // Only abstentions
+        assert!(!tally(0, 0, 10, &rules(0, 0)).passed);
+    }
+
+    #[test]
+    fn test_threshold_boundaries() {
+        // Exactly 60%
+        assert!(tally(3, 2, 0, &rules(0, 6_000)).passed);
+        assert!(!tally(3, 2, 0, &rules(0, 6_001)).passed);
+        // 2/3 is 6666.67 bps: reported rounded down, but compared exactly
+        let result = tally(2, 1, 0, &rules(0, 6_666));
+        assert_eq!(result.approval_bps, 6_666);
+        assert!(result.passed);
+        assert!(!tally(2, 1, 0, &rules(0, 6_667)).passed);
+        // Unanimous
+        assert!(tally(1, 0, 0, &rules(0, BASIS_POINTS)).passed);
+        assert!(!tally(99, 1, 0, &rules(0, BASIS_POINTS)).passed);
+    }
+
+    #[test]
+    fn test_abstain_counts_toward_quorum_only() {
+        let result = tally(6, 4, 90, &rules(100, 6_000));
+        assert!(result.quorum_reached);
+        assert_eq!(result.approval_bps, 6_000);
+        assert!(result.passed);
+
+        let result = tally(6, 4, 89, &rules(100, 6_000));
+        assert!(!result.quorum_reached);
+        assert!(!result.passed);
+    }
+
+    #[test]
+    fn test_extreme_weights_do_not_overflow() {
+        let result = tally(u64::MAX, u64::MAX, u64::MAX, &rules(u64::MAX, 5_000));
+        assert_eq!(result.total_votes, 3 * u64::MAX as u128);
+        assert_eq!(result.approval_bps, 5_000);
+        assert!(result.passed);
+
+        assert!(!tally(u64::MAX - 1, u64::MAX, 0, &rules(0, 5_000)).passed);
+        assert_eq!(tally(u64::MAX, 1, 0, &rules(0, 0)).approval_bps, 9_999);
+    }
+
+    /// Checks every small combination of tallies and rules against a
+    /// straightforward rational reference.
+    #[test]
+    fn test_matches_rational_reference_exhaustively() {
+        let thresholds = [0, 1, 3_333, 5_000, 5_001, 6_667, 9_999, BASIS_POINTS];
+        for yes in 0..=24u64 {
+            for no in 0..=24u64 {
+                for abstain in 0..=6u64 {
+                    for quorum in [0, 1, 10, 30, 55] {
+                        for threshold in thresholds {
+                            let result = tally(yes, no, abstain, &rules(quorum, threshold));
+
+                            let total = yes + no + abstain;
+                            assert_eq!(result.total_votes, total as u128);
+                            assert_eq!(result.quorum_reached, total >= quorum);
+
+                            // approval_bps is the floor of yes / (yes + no) in basis points
+                            if yes + no == 0 {
+                                assert_eq!(result.approval_bps, 0);
+                            } else {
+                                let bps = result.approval_bps as u64;
+                                assert!(bps * (yes + no) <= yes * 10_000);
+                                assert!((bps + 1) * (yes + no) > yes * 10_000);
+                            }
+
+                            // yes / (yes + no) >= threshold / 10_000, with at least one yes
+                            let expected = total >= quorum
+                                && yes > 0
+                                && yes * 10_000 >= threshold as u64 * (yes + no);
+                            assert_eq!(result.passed, expected, "{} {} {} {} {}", yes, no, abstain, quorum, threshold);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_monotonic_in_yes_votes() {
+        // Adding yes weight never turns a pass into a fail
+        for no in 0..=20u64 {
+            for threshold in [1, 5_000, 7_500, BASIS_POINTS] {
+                let mut passed = false;
+                for yes in 0..=60u64 {
+                    let result = tally(yes, no, 0, &rules(10, threshold));
+                    assert!(!passed || result.passed);
+                    passed = result.passed;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_options_plurality_and_ties() {
+        let result = tally_options(&[3, 5, 2], 10, 0, &rules(0, 0));
+        assert_eq!(result.winning_option, Some(1));
+        assert_eq!(result.approval_bps, 5_000);
+        assert!(result.passed);
+
+        // Ties go to the option listed first
+        assert_eq!(tally_options(&[2, 4, 4], 10, 0, &rules(0, 0)).winning_option, Some(1));
+        assert_eq!(tally_options(&[4, 4], 8, 0, &rules(0, 0)).winning_option, Some(0));
+
+        // Nobody picked an option
+        let result = tally_options(&[0, 0, 0], 0, 7, &rules(0, 0));
+        assert_eq!(result.winning_option, None);
+        assert_eq!(result.total_votes, 7);
+        assert!(!result.passed);
+    }
+
+    #[test]
+    fn test_options_threshold_and_quorum() {
+        // Approval voting: 6 of 10 voter weight approved option 2
+        let result = tally_options(&[5, 2, 6], 10, 0, &rules(0, 6_000));
+        assert_eq!(result.winning_option, Some(2));
+        assert!(result.passed);
+        assert!(!tally_options(&[5, 2, 6], 10, 0, &rules(0, 6_001)).passed);
+
+        // Abstentions count toward quorum only
+        assert!(tally_options(&[5, 5], 10, 5, &rules(15, 5_000)).passed);
+        assert!(!tally_options(&[5, 5], 10, 4, &rules(15, 5_000)).passed);
+
+        let result = tally_options(&[u64::MAX, u64::MAX], u64::MAX, u64::MAX, &rules(u64::MAX, BASIS_POINTS));
+        assert_eq!(result.winning_option, Some(0));
+        assert_eq!(result.approval_bps, BASIS_POINTS);
+        assert!(result.passed);
+    }
+}
@@ -0,0 +1,219 @@
+use solana_program::{
+    program_error::ProgramError,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+};
+use borsh::{BorshSerialize, BorshDeserialize};
+use spl_associated_token_account::get_associated_token_address;
+use crate::governance::state::{find_governance_authority, ProposalAccountMeta, ProposalInstruction};
+use crate::{GovernanceInstruction, Instruction};
+
+pub const TREASURY_SEED: &[u8] = b"treasury";
+
+/// Asset moved by a `TreasuryTransfer`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum TreasuryAsset {
+    Sol,
+    Token { mint: Pubkey },
+}
+
+/// System-owned PDA holding the SOL of `realm`. SPL tokens are held in its
+/// associated token accounts. Funds can only leave through
+/// `TreasuryTransfer`, which the realm's governance authority must sign, so
+/// an executed proposal in that realm is the only spending path.
+pub fn find_treasury_address(program_id: &Pubkey, realm: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TREASURY_SEED, realm.as_ref()], program_id)
+}
+
+/// Associated token account of the treasury for `mint`.
+pub fn find_treasury_token_account(program_id: &Pubkey, realm: &Pubkey, mint: &Pubkey) -> Pubkey {
+    let (treasury, _) = find_treasury_address(program_id, realm);
+    get_associated_token_address(&treasury, mint)
+}
+
+/// Builds the instruction a proposal carries to pay `amount` of `asset` from
+/// the treasury to `destination`. For tokens, `destination` is a token account.
+pub fn treasury_transfer_instruction(
+    program_id: &Pubkey,
+    realm: &Pubkey,
+    asset: TreasuryAsset,
+    destination: &Pubkey,
+    amount: u64,
+) -> Result<ProposalInstruction, ProgramError> {
+    let (governance_authority, _) = find_governance_authority(program_id, realm);
+    let (treasury, _) = find_treasury_address(program_id, realm);
+
+    let meta = |pubkey: Pubkey, is_signer: bool, is_writable: bool| ProposalAccountMeta {
+        pubkey,
+        is_signer,
+        is_writable,
+    };
+    let mut accounts = vec![
+        meta(governance_authority, true, false),
+        meta(*realm, false, false),
+    ];
+    match asset {
+        TreasuryAsset::Sol => accounts.extend([
+            meta(treasury, false, true),
+            meta(*destination, false, true),
+            meta(solana_program::system_program::id(), false, false),
+        ]),
+        TreasuryAsset::Token { mint } => accounts.extend([
+            meta(treasury, false, false),
+            meta(get_associated_token_address(&treasury, &mint), false, true),
+            meta(*destination, false, true),
+            meta(spl_token::id(), false, false),
+        ]),
+    }
+
+    let data = borsh::to_vec(&Instruction::Governance(GovernanceInstruction::TreasuryTransfer { asset, amount }))
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+
+    Ok(ProposalInstruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// A treasury payment as carried by a proposal transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TreasuryTransfer {
+    pub realm: Pubkey,
+    pub asset: TreasuryAsset,
+    pub destination: Pubkey,
+    pub amount: u64,
+}
+
+/// Reads back an instruction built by `treasury_transfer_instruction`, so
+/// clients can show a proposal's transactions as treasury payments. Returns
+/// `None` for any other instruction.
+///
+/// Treasury transfers are ordinary proposal transactions rather than a
+/// separate proposal type: they go through the same insert, hash, timelock,
+/// veto and execute path as every other transaction, and a proposal can
+/// bundle several payments or mix them with other actions.
+pub fn parse_treasury_transfer(program_id: &Pubkey, instruction: &ProposalInstruction) -> Option<TreasuryTransfer> {
+    if instruction.program_id != *program_id {
+        return None;
+    }
+    let (asset, amount) = match Instruction::try_from_slice(&instruction.data).ok()? {
+        Instruction::Governance(GovernanceInstruction::TreasuryTransfer { asset, amount }) => (asset, amount),
+        _ => return None,
+    };
+    let destination_index = match asset {
+        TreasuryAsset::Sol => 3,
+        TreasuryAsset::Token { .. } => 4,
+    };
+    Some(TreasuryTransfer {
+        realm: instruction.accounts.get(1)?.pubkey,
+        asset,
+        destination: instruction.accounts.get(destination_index)?.pubkey,
+        amount,
+    })
+}
+
+/// SOL the treasury can spend: its balance above the rent-exempt minimum.
+pub fn treasury_sol_balance(lamports: u64, rent: &Rent) -> u64 {
+    lamports.saturating_sub(rent.minimum_balance(0))
+}
+
+/// Token balance of a treasury token account, given its raw account data.
+pub fn treasury_token_balance(
+    program_id: &Pubkey,
+    realm: &Pubkey,
+    token_account_data: &[u8],
+) -> Result<u64, ProgramError> {
+    let (treasury, _) = find_treasury_address(program_id, realm);
+    let token_account = spl_token::state::Account::unpack(token_account_data)?;
+    if token_account.owner != treasury {
+        return Err(ProgramError::IllegalOwner);
+    }
+    Ok(token_account.amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transfer_instruction_targets_treasury() {
+        let program_id = Pubkey::new_unique();
+        let realm = Pubkey::new_unique();
+        let destination = Pubkey::new_unique();
+        let (treasury, _) = find_treasury_address(&program_id, &realm);
+        let (governance_authority, _) = find_governance_authority(&program_id, &realm);
+
+        let instruction =
+            treasury_transfer_instruction(&program_id, &realm, TreasuryAsset::Sol, &destination, 5).unwrap();
+        assert_eq!(instruction.program_id, program_id);
+        assert_eq!(instruction.accounts[0], ProposalAccountMeta { pubkey: governance_authority, is_signer: true, is_writable: false });
+        assert_eq!(instruction.accounts[2].pubkey, treasury);
+        assert!(instruction.validate().is_ok());
+        match Instruction::try_from_slice(&instruction.data).unwrap() {
+            Instruction::Governance(GovernanceInstruction::TreasuryTransfer { asset, amount }) => {
+                assert_eq!(asset, TreasuryAsset::Sol);
+                assert_eq!(amount, 5);
+            }
+            _ => panic!("expected a treasury transfer"),
+        }
+
+        let mint = Pubkey::new_unique();
+        let instruction =
+            treasury_transfer_instruction(&program_id, &realm, TreasuryAsset::Token { mint }, &destination, 5).unwrap();
+        assert_eq!(instruction.accounts[3].pubkey, find_treasury_token_account(&program_id, &realm, &mint));
+        assert_eq!(instruction.accounts[4].pubkey, destination);
+    }
+
+    #[test]
+    fn test_parse_treasury_transfer() {
+        let program_id = Pubkey::new_unique();
+        let realm = Pubkey::new_unique();
+        let destination = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+
+        for asset in [TreasuryAsset::Sol, TreasuryAsset::Token { mint }] {
+            let instruction = treasury_transfer_instruction(&program_id, &realm, asset, &destination, 5).unwrap();
+            assert_eq!(
+                parse_treasury_transfer(&program_id, &instruction),
+                Some(TreasuryTransfer { realm, asset, destination, amount: 5 })
+            );
+            // Same data sent to another program is not a treasury payment
+            assert_eq!(parse_treasury_transfer(&Pubkey::new_unique(), &instruction), None);
+        }
+
+        let other = ProposalInstruction {
+            program_id,
+            accounts: Vec::new(),
+            data: borsh::to_vec(&Instruction::Governance(GovernanceInstruction::FinalizeProposal)).unwrap(),
+        };
+        assert_eq!(parse_treasury_transfer(&program_id, &other), None);
+    }
+
+    #[test]
+    fn test_balance_helpers() {
+        let rent = Rent::default();
+        assert_eq!(treasury_sol_balance(rent.minimum_balance(0) + 42, &rent), 42);
+        assert_eq!(treasury_sol_balance(1, &rent), 0);
+
+        let program_id = Pubkey::new_unique();
+        let realm = Pubkey::new_unique();
+        let (treasury, _) = find_treasury_address(&program_id, &realm);
+
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        let mut token_account = spl_token::state::Account {
+            mint: Pubkey::new_unique(),
+            owner: treasury,
+            amount: 1_000,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        };
+        spl_token::state::Account::pack(token_account, &mut data).unwrap();
+        assert_eq!(treasury_token_balance(&program_id, &realm, &data), Ok(1_000));
+
+        token_account.owner = Pubkey::new_unique();
+        spl_token::state::Account::pack(token_account, &mut data).unwrap();
+        assert_eq!(treasury_token_balance(&program_id, &realm, &data), Err(ProgramError::IllegalOwner));
+    }
+}
@@ -1,12 +1,13 @@
 use solana_program::{
-    account_info::AccountInfo,
+    account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
     program_error::ProgramError,
-    program_pack::Pack,
     pubkey::Pubkey,
-    sysvar::Sysvar,
 };
-use std::str::FromStr;
+use crate::account::AccountState;
+use crate::context::{
+    check_program_owned, check_signer, check_system_program, check_token_program, check_uninitialized, Accounts,
+};
 use crate::lending::state::{LendingPool, Loan};
 
 pub struct InitLendingPoolContext<'a> {
@@ -19,11 +20,20 @@ pub struct InitLendingPoolContext<'a> {
     pub rent: &'a AccountInfo<'a>,
 }
 
+pub struct UpdateLendingPoolContext<'a> {
+    pub lending_pool: &'a AccountInfo<'a>,
+    pub authority: &'a AccountInfo<'a>,
+}
+
 pub struct CreateLoanContext<'a> {
     pub loan: &'a AccountInfo<'a>,
     pub lending_pool: &'a AccountInfo<'a>,
     pub borrower: &'a AccountInfo<'a>,
     pub system_program: &'a AccountInfo<'a>,
+    /// Borrower's profile, checked for the 2FA guardian co-signature
+    pub borrower_profile: &'a AccountInfo<'a>,
+    /// Only needed when the loan is above the borrower's 2FA threshold
+    pub guardian: Option<&'a AccountInfo<'a>>,
 }
 
 pub struct RepayLoanContext<'a> {
@@ -34,89 +44,130 @@ pub struct RepayLoanContext<'a> {
 }
 
 impl<'a> InitLendingPoolContext<'a> {
-    pub fn validate(&self, _program_id: &Pubkey) -> ProgramResult {
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
         // Verify lending pool is not initialized
-        let lending_pool_data = LendingPool::unpack_unchecked(&self.lending_pool.data.borrow())?;
-        if lending_pool_data.is_initialized {
-            return Err(ProgramError::AccountAlreadyInitialized);
-        }
+        check_uninitialized::<LendingPool>(program_id, self.lending_pool)?;
         
         // Verify authority is signer
-        if !self.authority.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
-        
-        // Verify system program
-        if self.system_program.key != &solana_program::system_program::id() {
-            return Err(ProgramError::IncorrectProgramId);
-        }
-        
-        // Verify token program
-        if self.token_program.key != &Pubkey::from_str(&spl_token::ID.to_string()).unwrap() {
-            return Err(ProgramError::IncorrectProgramId);
-        }
+        check_signer(self.authority)?;
         
-        Ok(())
+        // Verify system and token programs
+        check_system_program(self.system_program)?;
+        check_token_program(self.token_program)
     }
 }
 
 impl<'a> CreateLoanContext<'a> {
-    pub fn validate(&self, _program_id: &Pubkey) -> ProgramResult {
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
         // Verify loan account is not initialized
-        let loan_data = Loan::unpack_unchecked(&self.loan.data.borrow())?;
-        if loan_data.is_initialized {
-            return Err(ProgramError::AccountAlreadyInitialized);
-        }
+        check_uninitialized::<Loan>(program_id, self.loan)?;
         
         // Verify lending pool is initialized
-        let lending_pool_data = LendingPool::unpack_unchecked(&self.lending_pool.data.borrow())?;
-        if !lending_pool_data.is_initialized {
-            return Err(ProgramError::UninitializedAccount);
-        }
+        check_program_owned(program_id, self.lending_pool)?;
+        LendingPool::unpack(&self.lending_pool.data.borrow())?;
         
         // Verify borrower is signer
-        if !self.borrower.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
+        check_signer(self.borrower)?;
         
         // Verify system program
-        if self.system_program.key != &solana_program::system_program::id() {
-            return Err(ProgramError::IncorrectProgramId);
-        }
-        
-        Ok(())
+        check_system_program(self.system_program)
     }
 }
 
 impl<'a> RepayLoanContext<'a> {
-    pub fn validate(&self, _program_id: &Pubkey) -> ProgramResult {
-        // Verify loan is initialized
-        let loan_data = Loan::unpack_unchecked(&self.loan.data.borrow())?;
-        if !loan_data.is_initialized {
-            return Err(ProgramError::UninitializedAccount);
-        }
-        
-        // Verify lending pool is initialized
-        let lending_pool_data = LendingPool::unpack_unchecked(&self.lending_pool.data.borrow())?;
-        if !lending_pool_data.is_initialized {
-            return Err(ProgramError::UninitializedAccount);
-        }
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
+        // Verify loan and lending pool are initialized
+        check_program_owned(program_id, self.loan)?;
+        check_program_owned(program_id, self.lending_pool)?;
+        let loan_data = Loan::unpack(&self.loan.data.borrow())?;
+        LendingPool::unpack(&self.lending_pool.data.borrow())?;
         
         // Verify borrower is signer
-        if !self.borrower.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
+        check_signer(self.borrower)?;
         
-        // Verify borrower matches loan
-        if loan_data.borrower != *self.borrower.key {
+        // Verify the loan is the borrower's, from this lending pool
+        if loan_data.borrower != *self.borrower.key || loan_data.lending_pool != *self.lending_pool.key {
             return Err(ProgramError::InvalidAccountData);
         }
         
         // Verify token program
-        if self.token_program.key != &Pubkey::from_str(&spl_token::ID.to_string()).unwrap() {
-            return Err(ProgramError::IncorrectProgramId);
+        check_token_program(self.token_program)
+    }
+}
+
+impl<'a> UpdateLendingPoolContext<'a> {
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
+        // Verify authority is signer
+        check_signer(self.authority)?;
+        
+        // Verify lending pool is owned by this program
+        check_program_owned(program_id, self.lending_pool)?;
+        
+        // Verify authority matches
+        let lending_pool_data = LendingPool::unpack(&self.lending_pool.data.borrow())?;
+        if lending_pool_data.authority != *self.authority.key {
+            return Err(ProgramError::IllegalOwner);
         }
         
         Ok(())
     }
-} 
\ No newline at end of file
+}
+
+impl<'a> Accounts<'a> for InitLendingPoolContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = InitLendingPoolContext {
+            lending_pool: next_account_info(account_info_iter)?,
+            authority: next_account_info(account_info_iter)?,
+            token_mint: next_account_info(account_info_iter)?,
+            token_vault: next_account_info(account_info_iter)?,
+            system_program: next_account_info(account_info_iter)?,
+            token_program: next_account_info(account_info_iter)?,
+            rent: next_account_info(account_info_iter)?,
+        };
+        context.validate(program_id)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for UpdateLendingPoolContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = UpdateLendingPoolContext {
+            lending_pool: next_account_info(account_info_iter)?,
+            authority: next_account_info(account_info_iter)?,
+        };
+        context.validate(program_id)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for CreateLoanContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = CreateLoanContext {
+            loan: next_account_info(account_info_iter)?,
+            lending_pool: next_account_info(account_info_iter)?,
+            borrower: next_account_info(account_info_iter)?,
+            system_program: next_account_info(account_info_iter)?,
+            borrower_profile: next_account_info(account_info_iter)?,
+            guardian: account_info_iter.next(),
+        };
+        context.validate(program_id)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for RepayLoanContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = RepayLoanContext {
+            loan: next_account_info(account_info_iter)?,
+            lending_pool: next_account_info(account_info_iter)?,
+            borrower: next_account_info(account_info_iter)?,
+            token_program: next_account_info(account_info_iter)?,
+        };
+        context.validate(program_id)?;
+        Ok(context)
+    }
+}
@@ -1,16 +1,18 @@
 use solana_program::{
-    account_info::{next_account_info, AccountInfo},
+    account_info::AccountInfo,
     clock::Clock,
     entrypoint::ProgramResult,
     program_error::ProgramError,
-    program_pack::Pack,
     pubkey::Pubkey,
     sysvar::Sysvar,
 };
+use crate::account::AccountState;
 use crate::lending::{
     state::{LendingPool, LendingPoolParams, Loan, LoanParams, LoanStatus},
-    context::{InitLendingPoolContext, CreateLoanContext, RepayLoanContext},
+    context::{InitLendingPoolContext, UpdateLendingPoolContext, CreateLoanContext, RepayLoanContext},
 };
+use crate::context::Accounts;
+use crate::user::two_factor::check_guardian_cosign;
 use std::str::FromStr;
 
 pub fn init_lending_pool<'a>(
@@ -18,19 +20,7 @@ pub fn init_lending_pool<'a>(
     accounts: &'a [AccountInfo<'a>],
     params: LendingPoolParams,
 ) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
-    
-    let context = InitLendingPoolContext {
-        lending_pool: next_account_info(account_info_iter)?,
-        authority: next_account_info(account_info_iter)?,
-        token_mint: next_account_info(account_info_iter)?,
-        token_vault: next_account_info(account_info_iter)?,
-        system_program: next_account_info(account_info_iter)?,
-        token_program: next_account_info(account_info_iter)?,
-        rent: next_account_info(account_info_iter)?,
-    };
-    
-    context.validate(program_id)?;
+    let context = InitLendingPoolContext::try_accounts(program_id, accounts)?;
     
     let clock = Clock::get()?;
     let lending_pool_data = LendingPool {
@@ -47,26 +37,42 @@ pub fn init_lending_pool<'a>(
         updated_at: clock.unix_timestamp,
     };
     
-    LendingPool::pack(lending_pool_data, &mut context.lending_pool.data.borrow_mut())?;
+    lending_pool_data.pack(&mut context.lending_pool.data.borrow_mut())?;
     
     Ok(())
 }
 
-pub fn create_loan<'a>(
+/// Updates a pool's lending terms. When the pool's authority is a
+/// governance authority PDA this is only reachable through an executed proposal.
+pub fn update_lending_pool<'a>(
     program_id: &Pubkey,
     accounts: &'a [AccountInfo<'a>],
-    params: LoanParams,
+    params: LendingPoolParams,
 ) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
+    let UpdateLendingPoolContext { lending_pool, .. } = UpdateLendingPoolContext::try_accounts(program_id, accounts)?;
     
-    let context = CreateLoanContext {
-        loan: next_account_info(account_info_iter)?,
-        lending_pool: next_account_info(account_info_iter)?,
-        borrower: next_account_info(account_info_iter)?,
-        system_program: next_account_info(account_info_iter)?,
-    };
+    let mut lending_pool_data = LendingPool::unpack(&lending_pool.data.borrow())?;
     
-    context.validate(program_id)?;
+    if params.min_loan_amount > params.max_loan_amount {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    lending_pool_data.interest_rate = params.interest_rate;
+    lending_pool_data.max_loan_amount = params.max_loan_amount;
+    lending_pool_data.min_loan_amount = params.min_loan_amount;
+    lending_pool_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    lending_pool_data.pack(&mut lending_pool.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+pub fn create_loan<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    params: LoanParams,
+) -> ProgramResult {
+    let context = CreateLoanContext::try_accounts(program_id, accounts)?;
     
     let lending_pool_data = LendingPool::unpack(&context.lending_pool.data.borrow())?;
     
@@ -75,6 +81,8 @@ pub fn create_loan<'a>(
         return Err(ProgramError::InvalidArgument);
     }
     
+    check_guardian_cosign(program_id, context.borrower.key, context.borrower_profile, context.guardian, params.amount)?;
+    
     let clock = Clock::get()?;
     let loan_data = Loan {
         is_initialized: true,
@@ -90,14 +98,14 @@ pub fn create_loan<'a>(
     };
     
     let loan_data_clone = loan_data.clone();
-    Loan::pack(loan_data, &mut context.loan.data.borrow_mut())?;
+    loan_data.pack(&mut context.loan.data.borrow_mut())?;
     
     // Update lending pool total borrowed
     let mut lending_pool_data = LendingPool::unpack(&context.lending_pool.data.borrow())?;
     let loan_amount = loan_data_clone.amount;
     lending_pool_data.total_borrowed += loan_amount;
     lending_pool_data.updated_at = clock.unix_timestamp;
-    LendingPool::pack(lending_pool_data, &mut context.lending_pool.data.borrow_mut())?;
+    lending_pool_data.pack(&mut context.lending_pool.data.borrow_mut())?;
     
     Ok(())
 }
@@ -106,16 +114,7 @@ pub fn repay_loan<'a>(
     program_id: &Pubkey,
     accounts: &'a [AccountInfo<'a>],
 ) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
-    
-    let context = RepayLoanContext {
-        loan: next_account_info(account_info_iter)?,
-        lending_pool: next_account_info(account_info_iter)?,
-        borrower: next_account_info(account_info_iter)?,
-        token_program: next_account_info(account_info_iter)?,
-    };
-    
-    context.validate(program_id)?;
+    let context = RepayLoanContext::try_accounts(program_id, accounts)?;
     
     let clock = Clock::get()?;
     
@@ -124,13 +123,13 @@ pub fn repay_loan<'a>(
     let loan_amount = loan_data.amount;
     loan_data.status = LoanStatus::Repaid;
     loan_data.updated_at = clock.unix_timestamp;
-    Loan::pack(loan_data, &mut context.loan.data.borrow_mut())?;
+    loan_data.pack(&mut context.loan.data.borrow_mut())?;
     
     // Update lending pool total borrowed
     let mut lending_pool_data = LendingPool::unpack(&context.lending_pool.data.borrow())?;
     lending_pool_data.total_borrowed -= loan_amount;
     lending_pool_data.updated_at = clock.unix_timestamp;
-    LendingPool::pack(lending_pool_data, &mut context.lending_pool.data.borrow_mut())?;
+    lending_pool_data.pack(&mut context.lending_pool.data.borrow_mut())?;
     
     Ok(())
 }
@@ -211,4 +210,57 @@ mod tests {
         assert_eq!(lending_pool.total_borrowed, 0);
         assert_eq!(lending_pool.total_deposited, 0);
     }
-} 
\ No newline at end of file
+    
+    #[test]
+    fn test_create_loan_requires_borrower_profile() {
+        let program_id = Pubkey::new_unique();
+        let system_program_id = solana_program::system_program::id();
+        
+        let mut loan_data = vec![0; Loan::LEN];
+        let mut lending_pool_data = vec![0; LendingPool::LEN];
+        let mut borrower_data = vec![];
+        let mut system_program_data = vec![];
+        
+        // Without the trailing profile the guardian co-sign can't be skipped
+        let accounts = vec![
+            create_test_account(0, &program_id, &mut loan_data),
+            create_test_account(0, &program_id, &mut lending_pool_data),
+            create_test_account(0, &system_program_id, &mut borrower_data),
+            create_test_account(0, &system_program_id, &mut system_program_data),
+        ];
+        let params = LoanParams {
+            amount: u64::MAX,
+            duration: 0,
+        };
+        
+        assert_eq!(create_loan(&program_id, &accounts, params), Err(ProgramError::NotEnoughAccountKeys));
+    }
+    
+    #[test]
+    fn test_create_loan_rejects_foreign_lending_pool() {
+        let program_id = Pubkey::new_unique();
+        let other_program_id = Pubkey::new_unique();
+        let system_program_id = solana_program::system_program::id();
+        
+        let mut loan_data = vec![0; Loan::LEN];
+        let mut lending_pool_data = vec![0; LendingPool::LEN];
+        let mut borrower_data = vec![];
+        let mut system_program_data = vec![];
+        let mut profile_data = vec![];
+        
+        // A pool with the right layout but another owner could claim any rate and limits
+        let accounts = vec![
+            create_test_account(0, &program_id, &mut loan_data),
+            create_test_account(0, &other_program_id, &mut lending_pool_data),
+            create_test_account(0, &system_program_id, &mut borrower_data),
+            create_test_account(0, &system_program_id, &mut system_program_data),
+            create_test_account(0, &program_id, &mut profile_data),
+        ];
+        let params = LoanParams {
+            amount: 1,
+            duration: 0,
+        };
+        
+        assert_eq!(create_loan(&program_id, &accounts, params), Err(ProgramError::IncorrectProgramId));
+    }
+}
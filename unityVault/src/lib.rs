@@ -7,6 +7,11 @@ use solana_program::{
 };
 use borsh::{BorshDeserialize, BorshSerialize};
 
+pub mod account;
+pub mod migration;
+pub mod error;
+pub mod validation;
+pub mod context;
 pub mod user;
 pub mod governance;
 pub mod community;
@@ -35,12 +40,46 @@ pub fn process_instruction<'a>(
             UserInstruction::UpdateUserProfile(params) => {
                 user::instructions::update_user_profile(program_id, accounts, params)
             }
-            UserInstruction::EnableTwoFactor { secret, backup_codes } => {
-                user::instructions::enable_two_factor(program_id, accounts, secret, backup_codes)
+            UserInstruction::EnableTwoFactor { secret_commitment, backup_codes, guardian, high_value_threshold } => {
+                user::instructions::enable_two_factor(
+                    program_id,
+                    accounts,
+                    secret_commitment,
+                    backup_codes,
+                    guardian,
+                    high_value_threshold,
+                )
+            }
+            UserInstruction::DisableTwoFactor { backup_code } => {
+                user::instructions::disable_two_factor(program_id, accounts, backup_code)
             }
             UserInstruction::VerifyKyc(kyc_data) => {
                 user::instructions::verify_kyc(program_id, accounts, kyc_data)
             }
+            UserInstruction::RotateAuthority => {
+                user::instructions::rotate_authority(program_id, accounts)
+            }
+            UserInstruction::SetRecoveryGuardians { guardians, threshold, timelock } => {
+                user::instructions::set_recovery_guardians(program_id, accounts, guardians, threshold, timelock)
+            }
+            UserInstruction::InitiateRecovery { new_authority } => {
+                user::instructions::initiate_recovery(program_id, accounts, new_authority)
+            }
+            UserInstruction::ApproveRecovery => {
+                user::instructions::approve_recovery(program_id, accounts)
+            }
+            UserInstruction::CompleteRecovery => {
+                user::instructions::complete_recovery(program_id, accounts)
+            }
+            UserInstruction::CancelRecovery => {
+                user::instructions::cancel_recovery(program_id, accounts)
+            }
+            UserInstruction::MigrateUserProfile => {
+                migration::migrate_account::<user::UserProfile>(program_id, accounts)
+            }
+            UserInstruction::SetUserRole(role) => {
+                user::instructions::set_user_role(program_id, accounts, role)
+            }
         },
         Instruction::Governance(governance_instruction) => match governance_instruction {
             GovernanceInstruction::CreateProposal(params) => {
@@ -52,6 +91,86 @@ pub fn process_instruction<'a>(
             GovernanceInstruction::VoteProposal(vote_type) => {
                 governance::instructions::vote_proposal(program_id, accounts, vote_type)
             }
+            GovernanceInstruction::MigrateProposal => {
+                migration::migrate_account::<governance::state::Proposal>(program_id, accounts)
+            }
+            GovernanceInstruction::ChangeVote(vote_type) => {
+                governance::instructions::change_vote(program_id, accounts, vote_type)
+            }
+            GovernanceInstruction::DepositGoverningTokens(amount) => {
+                governance::instructions::deposit_governing_tokens(program_id, accounts, amount)
+            }
+            GovernanceInstruction::WithdrawGoverningTokens(amount) => {
+                governance::instructions::withdraw_governing_tokens(program_id, accounts, amount)
+            }
+            GovernanceInstruction::RelinquishVote => {
+                governance::instructions::relinquish_vote(program_id, accounts)
+            }
+            GovernanceInstruction::ActivateProposal => {
+                governance::instructions::activate_proposal(program_id, accounts)
+            }
+            GovernanceInstruction::CancelProposal => {
+                governance::instructions::cancel_proposal(program_id, accounts)
+            }
+            GovernanceInstruction::FinalizeProposal => {
+                governance::instructions::finalize_proposal(program_id, accounts)
+            }
+            GovernanceInstruction::InsertTransaction(instruction) => {
+                governance::instructions::insert_transaction(program_id, accounts, instruction)
+            }
+            GovernanceInstruction::ExecuteProposal => {
+                governance::instructions::execute_proposal(program_id, accounts)
+            }
+            GovernanceInstruction::TreasuryTransfer { asset, amount } => {
+                governance::instructions::treasury_transfer(program_id, accounts, asset, amount)
+            }
+            GovernanceInstruction::Delegate(delegate) => {
+                governance::instructions::set_delegate(program_id, accounts, delegate)
+            }
+            GovernanceInstruction::Undelegate => {
+                governance::instructions::set_delegate(program_id, accounts, Pubkey::default())
+            }
+            GovernanceInstruction::SetProposalDepositConfig { deposit_amount, min_tokens_to_propose } => {
+                governance::instructions::set_proposal_deposit_config(
+                    program_id,
+                    accounts,
+                    deposit_amount,
+                    min_tokens_to_propose,
+                )
+            }
+            GovernanceInstruction::ReleaseProposalDeposit => {
+                governance::instructions::release_proposal_deposit(program_id, accounts)
+            }
+            GovernanceInstruction::CreateRealm { scope, config, quorum_floor } => {
+                governance::instructions::create_realm(program_id, accounts, scope, config, quorum_floor)
+            }
+            GovernanceInstruction::Lock { amount, duration } => {
+                governance::instructions::lock_governing_tokens(program_id, accounts, amount, duration)
+            }
+            GovernanceInstruction::ExtendLock { duration } => {
+                governance::instructions::extend_lock(program_id, accounts, duration)
+            }
+            GovernanceInstruction::IncreaseLock { amount } => {
+                governance::instructions::increase_lock(program_id, accounts, amount)
+            }
+            GovernanceInstruction::WithdrawLocked => {
+                governance::instructions::withdraw_locked_tokens(program_id, accounts)
+            }
+            GovernanceInstruction::SetVetoCouncil { members, threshold } => {
+                governance::instructions::set_veto_council(program_id, accounts, members, threshold)
+            }
+            GovernanceInstruction::VetoProposal => {
+                governance::instructions::veto_proposal(program_id, accounts)
+            }
+            GovernanceInstruction::SetGovernanceConfig(config) => {
+                governance::instructions::set_governance_config(program_id, accounts, config)
+            }
+            GovernanceInstruction::MigrateTokenOwnerRecord => {
+                migration::migrate_account::<governance::state::TokenOwnerRecord>(program_id, accounts)
+            }
+            GovernanceInstruction::MigrateVoteRecord => {
+                migration::migrate_account::<governance::state::VoteRecord>(program_id, accounts)
+            }
         },
         Instruction::Community(community_instruction) => match community_instruction {
             CommunityInstruction::CreateCommunity(params) => {
@@ -63,6 +182,78 @@ pub fn process_instruction<'a>(
             CommunityInstruction::SuspendCommunity => {
                 community::instructions::suspend_community(program_id, accounts)
             }
+            CommunityInstruction::MigrateCommunity => {
+                migration::migrate_account::<community::state::Community>(program_id, accounts)
+            }
+            CommunityInstruction::JoinCommunity => {
+                community::instructions::join_community(program_id, accounts)
+            }
+            CommunityInstruction::InviteMember(member) => {
+                community::instructions::invite_member(program_id, accounts, member)
+            }
+            CommunityInstruction::LeaveCommunity => {
+                community::instructions::leave_community(program_id, accounts)
+            }
+            CommunityInstruction::SetMemberRole(role) => {
+                community::instructions::set_member_role(program_id, accounts, role)
+            }
+            CommunityInstruction::KickMember => {
+                community::instructions::kick_member(program_id, accounts)
+            }
+            CommunityInstruction::BanMember(member) => {
+                community::instructions::ban_member(program_id, accounts, member)
+            }
+            CommunityInstruction::TransferCommunityAuthority(new_authority) => {
+                community::instructions::transfer_community_authority(program_id, accounts, new_authority)
+            }
+            CommunityInstruction::AcceptCommunityAuthority => {
+                community::instructions::accept_community_authority(program_id, accounts)
+            }
+            CommunityInstruction::ReinstateCommunity => {
+                community::instructions::reinstate_community(program_id, accounts)
+            }
+            CommunityInstruction::ArchiveCommunity => {
+                community::instructions::archive_community(program_id, accounts)
+            }
+            CommunityInstruction::CreateProject(params) => {
+                community::instructions::create_project(program_id, accounts, params)
+            }
+            CommunityInstruction::Contribute(amount) => {
+                community::instructions::contribute(program_id, accounts, amount)
+            }
+            CommunityInstruction::SubmitMilestone => {
+                community::instructions::submit_milestone(program_id, accounts)
+            }
+            CommunityInstruction::VoteMilestone { approve } => {
+                community::instructions::vote_milestone(program_id, accounts, approve)
+            }
+            CommunityInstruction::FinalizeMilestone => {
+                community::instructions::finalize_milestone(program_id, accounts)
+            }
+            CommunityInstruction::CancelProject => {
+                community::instructions::cancel_project(program_id, accounts)
+            }
+            CommunityInstruction::ClaimRefund => {
+                community::instructions::claim_refund(program_id, accounts)
+            }
+            CommunityInstruction::SetMembershipDues(dues) => {
+                community::instructions::set_membership_dues(program_id, accounts, dues)
+            }
+            CommunityInstruction::PayDues { periods } => {
+                community::instructions::pay_dues(program_id, accounts, periods)
+            }
+            CommunityInstruction::LapseMembership => {
+                community::instructions::lapse_membership(program_id, accounts)
+            }
+            CommunityInstruction::SetTreasuryRealm => {
+                community::instructions::set_treasury_realm(program_id, accounts)
+            }
+            CommunityInstruction::TreasuryTransfer { asset, amount } => {
+                community::instructions::treasury_transfer(program_id, accounts, asset, amount)
+            }
+            CommunityInstruction::MigrateMembership => {
+                migration::migrate_account::<community::state::Membership>(program_id, accounts)
+            }
         },
         Instruction::Lending(lending_instruction) => match lending_instruction {
             LendingInstruction::InitLendingPool(params) => {
@@ -74,6 +265,15 @@ pub fn process_instruction<'a>(
             LendingInstruction::RepayLoan => {
                 lending::instructions::repay_loan(program_id, accounts)
             }
+            LendingInstruction::MigrateLendingPool => {
+                migration::migrate_account::<lending::state::LendingPool>(program_id, accounts)
+            }
+            LendingInstruction::MigrateLoan => {
+                migration::migrate_account::<lending::state::Loan>(program_id, accounts)
+            }
+            LendingInstruction::UpdateLendingPool(params) => {
+                lending::instructions::update_lending_pool(program_id, accounts, params)
+            }
         },
         Instruction::Tokenization(tokenization_instruction) => match tokenization_instruction {
             TokenizationInstruction::CreateToken(params) => {
@@ -85,6 +285,9 @@ pub fn process_instruction<'a>(
             TokenizationInstruction::BurnTokens(amount) => {
                 tokenization::instructions::burn_tokens(program_id, accounts, amount)
             }
+            TokenizationInstruction::MigrateTokenInfo => {
+                migration::migrate_account::<tokenization::state::TokenInfo>(program_id, accounts)
+            }
         },
     }
 }
@@ -103,10 +306,29 @@ pub enum UserInstruction {
     CreateUserProfile(user::UserProfileParams),
     UpdateUserProfile(user::UserProfileParams),
     EnableTwoFactor {
-        secret: String,
+        secret_commitment: [u8; 32],
         backup_codes: Vec<String>,
+        guardian: Pubkey,
+        high_value_threshold: u64,
+    },
+    DisableTwoFactor {
+        backup_code: String,
     },
     VerifyKyc(user::KycData),
+    RotateAuthority,
+    SetRecoveryGuardians {
+        guardians: Vec<Pubkey>,
+        threshold: u8,
+        timelock: i64,
+    },
+    InitiateRecovery {
+        new_authority: Pubkey,
+    },
+    ApproveRecovery,
+    CompleteRecovery,
+    CancelRecovery,
+    MigrateUserProfile,
+    SetUserRole(user::UserRole),
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
@@ -114,6 +336,51 @@ pub enum GovernanceInstruction {
     CreateProposal(crate::governance::state::ProposalParams),
     UpdateProposal(crate::governance::state::ProposalParams),
     VoteProposal(crate::governance::state::VoteType),
+    MigrateProposal,
+    ChangeVote(crate::governance::state::VoteType),
+    DepositGoverningTokens(u64),
+    WithdrawGoverningTokens(u64),
+    RelinquishVote,
+    ActivateProposal,
+    CancelProposal,
+    FinalizeProposal,
+    InsertTransaction(crate::governance::state::ProposalInstruction),
+    ExecuteProposal,
+    TreasuryTransfer {
+        asset: crate::governance::treasury::TreasuryAsset,
+        amount: u64,
+    },
+    Delegate(Pubkey),
+    Undelegate,
+    SetProposalDepositConfig {
+        deposit_amount: u64,
+        min_tokens_to_propose: u64,
+    },
+    ReleaseProposalDeposit,
+    CreateRealm {
+        scope: crate::governance::state::RealmScope,
+        config: crate::governance::state::GovernanceConfig,
+        quorum_floor: u64,
+    },
+    Lock {
+        amount: u64,
+        duration: i64,
+    },
+    ExtendLock {
+        duration: i64,
+    },
+    IncreaseLock {
+        amount: u64,
+    },
+    WithdrawLocked,
+    SetVetoCouncil {
+        members: Vec<Pubkey>,
+        threshold: u8,
+    },
+    VetoProposal,
+    SetGovernanceConfig(crate::governance::state::GovernanceConfig),
+    MigrateTokenOwnerRecord,
+    MigrateVoteRecord,
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
@@ -121,6 +388,37 @@ pub enum CommunityInstruction {
     CreateCommunity(crate::community::state::CommunityParams),
     UpdateCommunity(crate::community::state::CommunityParams),
     SuspendCommunity,
+    MigrateCommunity,
+    JoinCommunity,
+    InviteMember(Pubkey),
+    LeaveCommunity,
+    SetMemberRole(crate::community::state::CommunityRole),
+    KickMember,
+    BanMember(Pubkey),
+    TransferCommunityAuthority(Option<Pubkey>),
+    AcceptCommunityAuthority,
+    ReinstateCommunity,
+    ArchiveCommunity,
+    CreateProject(crate::community::state::ProjectParams),
+    Contribute(u64),
+    SubmitMilestone,
+    VoteMilestone {
+        approve: bool,
+    },
+    FinalizeMilestone,
+    CancelProject,
+    ClaimRefund,
+    SetMembershipDues(Option<crate::community::state::MembershipDuesParams>),
+    PayDues {
+        periods: u32,
+    },
+    LapseMembership,
+    SetTreasuryRealm,
+    TreasuryTransfer {
+        asset: crate::governance::treasury::TreasuryAsset,
+        amount: u64,
+    },
+    MigrateMembership,
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
@@ -128,6 +426,9 @@ pub enum LendingInstruction {
     InitLendingPool(crate::lending::state::LendingPoolParams),
     CreateLoan(crate::lending::state::LoanParams),
     RepayLoan,
+    MigrateLendingPool,
+    MigrateLoan,
+    UpdateLendingPool(crate::lending::state::LendingPoolParams),
 }
 
 #[derive(BorshSerialize, BorshDeserialize)]
@@ -135,4 +436,5 @@ pub enum TokenizationInstruction {
     CreateToken(crate::tokenization::state::TokenParams),
     TransferTokens(u64),
     BurnTokens(u64),
+    MigrateTokenInfo,
 }
\ No newline at end of file
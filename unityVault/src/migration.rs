@@ -0,0 +1,476 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    msg,
+    program::invoke,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
+};
+use borsh::BorshDeserialize;
+use crate::account::{AccountState, AccountType, ACCOUNT_HEADER_LEN, LEGACY_INITIALIZED_FLAG};
+use crate::error::UnityVaultError;
+
+/// Version assigned to the headerless layouts written before accounts
+/// carried an account type and version byte.
+pub const LEGACY_LAYOUT_VERSION: u8 = 0;
+
+/// An account type whose older layouts can be upgraded to the current one.
+pub trait Migrate: AccountState {
+    /// Size of this type's accounts written before layouts were versioned,
+    /// or `None` if the type didn't exist yet. Legacy accounts carry no type
+    /// tag, so their size is all that tells them apart.
+    const LEGACY_LEN: Option<usize> = None;
+
+    /// Decodes `src` written in layout `version`, which is older than `Self::VERSION`.
+    fn decode_version(version: u8, src: &[u8]) -> Result<Self, ProgramError>;
+
+    /// Key that must sign the migration of this account.
+    fn migration_authority(&self) -> Pubkey;
+}
+
+/// Layout version stored in `data` for an account of type `T`.
+pub fn stored_version<T: AccountState>(data: &[u8]) -> Result<u8, ProgramError> {
+    match data.first() {
+        None => Err(ProgramError::AccountDataTooSmall),
+        Some(&tag) if tag == AccountType::Uninitialized as u8 => Err(ProgramError::UninitializedAccount),
+        Some(&LEGACY_INITIALIZED_FLAG) => Ok(LEGACY_LAYOUT_VERSION),
+        Some(&tag) if tag == T::ACCOUNT_TYPE as u8 => data.get(1).copied().ok_or(ProgramError::AccountDataTooSmall),
+        Some(_) => Err(UnityVaultError::AccountTypeMismatch.into()),
+    }
+}
+
+/// Layout version stored in `data` for an account of type `T`, rejecting
+/// legacy accounts whose size shows they hold another type.
+pub fn stored_migration_version<T: Migrate>(data: &[u8]) -> Result<u8, ProgramError> {
+    let version = stored_version::<T>(data)?;
+    if version == LEGACY_LAYOUT_VERSION && T::LEGACY_LEN != Some(data.len()) {
+        return Err(UnityVaultError::AccountTypeMismatch.into());
+    }
+    Ok(version)
+}
+
+/// Decodes a headerless legacy account whose layout matches the Borsh encoding of `T`.
+pub fn decode_legacy<T: BorshDeserialize>(src: &[u8]) -> Result<T, ProgramError> {
+    let mut payload = src;
+    T::deserialize(&mut payload).map_err(|_| ProgramError::InvalidAccountData)
+}
+
+/// One step of an upgrade chain: converts `previous` when an older layout has
+/// already been decoded, otherwise decodes `src` if `version` is `layout`.
+/// Walking the versions in order lets each layout only describe its own change.
+pub fn upgrade_or_decode<P, N>(previous: Option<P>, version: u8, layout: u8, src: &[u8]) -> Result<Option<N>, ProgramError>
+where
+    P: Into<N>,
+    N: BorshDeserialize,
+{
+    match previous {
+        Some(previous) => Ok(Some(previous.into())),
+        None if version == layout => {
+            let payload = src.get(ACCOUNT_HEADER_LEN..).ok_or(ProgramError::AccountDataTooSmall)?;
+            decode_legacy(payload).map(Some)
+        }
+        None => Ok(None),
+    }
+}
+
+/// Reads a length-prefixed string stored in a fixed `capacity` byte slot.
+pub fn read_padded_string(src: &mut &[u8], capacity: usize) -> Result<String, ProgramError> {
+    let len = u32::deserialize(src).map_err(|_| ProgramError::InvalidAccountData)? as usize;
+    if len > capacity || src.len() < capacity {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    let value = String::from_utf8(src[..len].to_vec()).map_err(|_| ProgramError::InvalidAccountData)?;
+    *src = &src[capacity..];
+    Ok(value)
+}
+
+/// Upgrades an account of type `T` to its current layout.
+///
+/// Accounts expected:
+/// 0. `[writable]` The account to migrate
+/// 1. `[signer]` The account's authority
+/// 2. `[writable, signer]` Payer for the additional rent
+/// 3. `[]` System program
+pub fn migrate_account<'a, T: Migrate>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let account = next_account_info(account_info_iter)?;
+    let authority = next_account_info(account_info_iter)?;
+    let payer = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    // Verify account is owned by this program
+    if account.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    // Verify signers
+    if !authority.is_signer || !payer.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Verify system program
+    if system_program.key != &solana_program::system_program::id() {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+
+    let version = stored_migration_version::<T>(&account.data.borrow())?;
+    if version == T::VERSION {
+        msg!("{:?} is already at layout version {}", T::ACCOUNT_TYPE, version);
+        return Ok(());
+    }
+    if version > T::VERSION {
+        return Err(UnityVaultError::UnsupportedAccountVersion.into());
+    }
+
+    let upgraded = T::decode_version(version, &account.data.borrow())?;
+    if upgraded.migration_authority() != *authority.key {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    // Top up rent for the new size
+    let required_lamports = Rent::get()?.minimum_balance(T::LEN);
+    let shortfall = required_lamports.saturating_sub(account.lamports());
+    if shortfall > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, account.key, shortfall),
+            &[payer.clone(), account.clone(), system_program.clone()],
+        )?;
+    }
+
+    if account.data_len() < T::LEN {
+        account.realloc(T::LEN, false)?;
+    }
+
+    upgraded.pack(&mut account.data.borrow_mut())?;
+
+    msg!("Migrated {:?} from layout version {} to {}", T::ACCOUNT_TYPE, version, T::VERSION);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::community::state::{Community, CommunityStatus};
+    use crate::governance::state::{Proposal, ProposalStatus};
+    use crate::lending::state::{LendingPool, Loan, LoanStatus};
+    use crate::tokenization::state::{TokenInfo, TokenStatus};
+    use crate::user::state::{KycStatus, UserProfile, UserRole, UserStatus};
+    use crate::user::two_factor::{commit_two_factor_secret, hash_backup_code};
+
+    /// Writes fields the way the hand-written `Pack` impls did before layouts were versioned.
+    #[derive(Default)]
+    struct LegacyWriter(Vec<u8>);
+
+    impl LegacyWriter {
+        fn u8(mut self, value: u8) -> Self {
+            self.0.push(value);
+            self
+        }
+
+        fn u32(mut self, value: u32) -> Self {
+            self.0.extend_from_slice(&value.to_le_bytes());
+            self
+        }
+
+        fn u64(mut self, value: u64) -> Self {
+            self.0.extend_from_slice(&value.to_le_bytes());
+            self
+        }
+
+        fn i64(mut self, value: i64) -> Self {
+            self.0.extend_from_slice(&value.to_le_bytes());
+            self
+        }
+
+        fn pubkey(mut self, value: &Pubkey) -> Self {
+            self.0.extend_from_slice(value.as_ref());
+            self
+        }
+
+        fn string(self, value: &str) -> Self {
+            let mut writer = self.u32(value.len() as u32);
+            writer.0.extend_from_slice(value.as_bytes());
+            writer
+        }
+
+        fn padded_string(self, value: &str, capacity: usize) -> Self {
+            let mut writer = self.u32(value.len() as u32);
+            writer.0.extend_from_slice(value.as_bytes());
+            writer.0.resize(writer.0.len() + capacity - value.len(), 0);
+            writer
+        }
+
+        /// Legacy accounts were allocated larger than the data written to them.
+        fn finish<T: Migrate>(mut self) -> Vec<u8> {
+            let allocated = T::LEGACY_LEN.unwrap();
+            assert!(self.0.len() <= allocated);
+            self.0.resize(allocated, 0);
+            self.0
+        }
+    }
+
+    fn upgrade<T: Migrate>(data: &[u8]) -> T {
+        let version = stored_migration_version::<T>(data).unwrap();
+        assert_eq!(version, LEGACY_LAYOUT_VERSION);
+        T::decode_version(version, data).unwrap()
+    }
+
+    /// Packs the upgraded account in the current layout and reads it back.
+    fn repack<T: Migrate>(account: &T) -> T {
+        let mut data = vec![0; T::LEN];
+        account.pack(&mut data).unwrap();
+        assert_eq!(stored_version::<T>(&data).unwrap(), T::VERSION);
+        T::unpack(&data).unwrap()
+    }
+
+    #[test]
+    fn test_stored_version() {
+        assert_eq!(stored_version::<Loan>(&[]), Err(ProgramError::AccountDataTooSmall));
+        assert_eq!(stored_version::<Loan>(&[0, 0]), Err(ProgramError::UninitializedAccount));
+        assert_eq!(stored_version::<Loan>(&[LEGACY_INITIALIZED_FLAG, 9]), Ok(LEGACY_LAYOUT_VERSION));
+        assert_eq!(stored_version::<Loan>(&[AccountType::Loan as u8, 1]), Ok(1));
+        assert_eq!(
+            stored_version::<Loan>(&[AccountType::TokenInfo as u8, 1]),
+            Err(UnityVaultError::AccountTypeMismatch.into())
+        );
+    }
+
+    #[test]
+    fn test_legacy_user_profile() {
+        let authority = Pubkey::new_unique();
+        let data = LegacyWriter::default()
+            .u8(1)
+            .pubkey(&authority)
+            .string("Ada Lovelace")
+            .string("ada@example.com")
+            .u8(UserRole::Moderator as u8)
+            .u8(UserStatus::Active as u8)
+            .u8(1)
+            .string("JBSWY3DPEHPK3PXP")
+            .u32(2)
+            .string("code1")
+            .string("code2")
+            .u8(1)
+            .u8(KycStatus::Verified as u8)
+            .string("Passport")
+            .string("123456789")
+            .i64(1_700_000_000)
+            .u8(0)
+            .i64(1_600_000_000)
+            .i64(1_650_000_000)
+            .finish::<UserProfile>();
+
+        let profile: UserProfile = upgrade(&data);
+        assert_eq!(profile.migration_authority(), authority);
+        assert_eq!(profile.full_name, "Ada Lovelace");
+        assert_eq!(profile.role, UserRole::Moderator);
+        assert!(profile.two_factor_enabled);
+        assert_eq!(profile.two_factor_secret_commitment, commit_two_factor_secret("JBSWY3DPEHPK3PXP"));
+        // Plaintext backup codes are replaced by their hashes
+        assert_eq!(profile.two_factor_backup_codes, vec![hash_backup_code("code1"), hash_backup_code("code2")]);
+        assert_eq!(profile.two_factor_guardian, Pubkey::default());
+        assert!(profile.recovery_guardians.is_empty());
+        assert_eq!(profile.kyc_status, KycStatus::Verified);
+        assert_eq!(profile.kyc_data.document_number, "123456789");
+        assert_eq!(profile.updated_at, 1_650_000_000);
+
+        let repacked = repack(&profile);
+        assert_eq!(repacked.email, "ada@example.com");
+        assert_eq!(repacked.two_factor_backup_codes, profile.two_factor_backup_codes);
+    }
+
+    #[test]
+    fn test_legacy_proposal() {
+        let authority = Pubkey::new_unique();
+        let data = LegacyWriter::default()
+            .u8(1)
+            .pubkey(&authority)
+            .string("Fund the well")
+            .string("Drill a new well for the village")
+            .u8(ProposalStatus::Active as u8)
+            .i64(86_400)
+            .u32(10)
+            .u8(60)
+            .u32(7)
+            .u32(2)
+            .u32(1)
+            .i64(100)
+            .i64(200)
+            .i64(0)
+            .finish::<Proposal>();
+
+        let proposal: Proposal = upgrade(&data);
+        assert_eq!(proposal.migration_authority(), authority);
+        assert_eq!(proposal.title, "Fund the well");
+        assert_eq!(proposal.status, ProposalStatus::Active);
+        assert_eq!(proposal.min_approval_percentage, 60);
+        assert_eq!((proposal.yes_votes, proposal.no_votes, proposal.abstain_votes), (7, 2, 1));
+        assert_eq!(proposal.updated_at, 200);
+
+        let repacked = repack(&proposal);
+        assert_eq!(repacked.description, proposal.description);
+        assert_eq!(repacked.yes_votes, 7);
+    }
+
+    #[test]
+    fn test_legacy_community() {
+        let authority = Pubkey::new_unique();
+        let data = LegacyWriter::default()
+            .u8(1)
+            .pubkey(&authority)
+            .string("Farmers")
+            .string("Local farming co-op")
+            .string("Be nice")
+            .u8(1)
+            .u8(CommunityStatus::Suspended as u8)
+            .u32(12)
+            .i64(100)
+            .i64(200)
+            .finish::<Community>();
+
+        let community: Community = upgrade(&data);
+        assert_eq!(community.migration_authority(), authority);
+        assert_eq!(community.rules, "Be nice");
+        assert!(community.is_private);
+        assert_eq!(community.status, CommunityStatus::Suspended);
+        assert_eq!(community.member_count, 12);
+
+        let repacked = repack(&community);
+        assert_eq!(repacked.name, "Farmers");
+        assert_eq!(repacked.member_count, 12);
+    }
+
+    #[test]
+    fn test_legacy_lending_pool() {
+        let authority = Pubkey::new_unique();
+        let token_mint = Pubkey::new_unique();
+        let token_vault = Pubkey::new_unique();
+        let data = LegacyWriter::default()
+            .u8(1)
+            .pubkey(&authority)
+            .pubkey(&token_mint)
+            .pubkey(&token_vault)
+            .u64(500)
+            .u64(1_000_000)
+            .u64(1_000)
+            .u64(50_000)
+            .u64(900_000)
+            .i64(100)
+            .i64(200)
+            .finish::<LendingPool>();
+
+        let pool: LendingPool = upgrade(&data);
+        assert_eq!(pool.migration_authority(), authority);
+        assert_eq!(pool.token_vault, token_vault);
+        assert_eq!(pool.interest_rate, 500);
+        assert_eq!(pool.total_borrowed, 50_000);
+        assert_eq!(pool.total_deposited, 900_000);
+
+        let repacked = repack(&pool);
+        assert_eq!(repacked.token_mint, token_mint);
+        assert_eq!(repacked.max_loan_amount, 1_000_000);
+    }
+
+    #[test]
+    fn test_legacy_loan() {
+        let borrower = Pubkey::new_unique();
+        let lending_pool = Pubkey::new_unique();
+        let data = LegacyWriter::default()
+            .u8(1)
+            .pubkey(&borrower)
+            .pubkey(&lending_pool)
+            .u64(25_000)
+            .u64(500)
+            .i64(100)
+            .i64(100 + 86_400)
+            .u8(LoanStatus::Repaid as u8)
+            .i64(100)
+            .i64(300)
+            .finish::<Loan>();
+
+        let loan: Loan = upgrade(&data);
+        assert_eq!(loan.migration_authority(), borrower);
+        assert_eq!(loan.lending_pool, lending_pool);
+        assert_eq!(loan.amount, 25_000);
+        assert_eq!(loan.due_time, 100 + 86_400);
+        assert_eq!(loan.status, LoanStatus::Repaid);
+
+        let repacked = repack(&loan);
+        assert_eq!(repacked.status, LoanStatus::Repaid);
+        assert_eq!(repacked.updated_at, 300);
+    }
+
+    #[test]
+    fn test_legacy_token_info() {
+        let creator = Pubkey::new_unique();
+        let mint = Pubkey::new_unique();
+        let data = LegacyWriter::default()
+            .u8(1)
+            .pubkey(&creator)
+            .pubkey(&mint)
+            .padded_string("Unity Token", 100)
+            .padded_string("UNITY", 10)
+            .u8(9)
+            .u64(1_000_000_000)
+            .u8(TokenStatus::Paused as u8)
+            .i64(100)
+            .i64(200)
+            .finish::<TokenInfo>();
+
+        let token_info: TokenInfo = upgrade(&data);
+        assert_eq!(token_info.migration_authority(), creator);
+        assert_eq!(token_info.mint, mint);
+        assert_eq!(token_info.name, "Unity Token");
+        assert_eq!(token_info.symbol, "UNITY");
+        assert_eq!(token_info.decimals, 9);
+        assert_eq!(token_info.status, TokenStatus::Paused);
+
+        let repacked = repack(&token_info);
+        assert_eq!(repacked.symbol, "UNITY");
+        assert_eq!(repacked.total_supply, 1_000_000_000);
+    }
+
+    #[test]
+    fn test_legacy_account_of_another_type_is_rejected() {
+        // A draft proposal whose bytes also decode as a profile
+        let data = LegacyWriter::default()
+            .u8(1)
+            .pubkey(&Pubkey::new_unique())
+            .string("Make me admin")
+            .string("")
+            .u8(ProposalStatus::Draft as u8)
+            .i64(86_400)
+            .u32(1)
+            .u8(UserRole::Admin as u8)
+            .finish::<Proposal>();
+
+        assert_eq!(stored_migration_version::<Proposal>(&data), Ok(LEGACY_LAYOUT_VERSION));
+        assert_eq!(
+            stored_migration_version::<UserProfile>(&data),
+            Err(UnityVaultError::AccountTypeMismatch.into())
+        );
+        // Types that didn't exist before versioning have no legacy accounts at all
+        assert_eq!(
+            stored_migration_version::<crate::community::state::Membership>(&data),
+            Err(UnityVaultError::AccountTypeMismatch.into())
+        );
+    }
+
+    #[test]
+    fn test_read_padded_string_rejects_bad_lengths() {
+        let mut data = LegacyWriter::default().u32(11).0;
+        data.resize(4 + 10, 0);
+        assert_eq!(read_padded_string(&mut &data[..], 10), Err(ProgramError::InvalidAccountData));
+
+        let data = LegacyWriter::default().padded_string("abc", 10).0;
+        assert_eq!(read_padded_string(&mut &data[..data.len() - 1], 10), Err(ProgramError::InvalidAccountData));
+    }
+}
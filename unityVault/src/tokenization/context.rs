@@ -1,12 +1,13 @@
 use solana_program::{
-    account_info::AccountInfo,
+    account_info::{next_account_info, AccountInfo},
     entrypoint::ProgramResult,
     program_error::ProgramError,
-    program_pack::Pack,
     pubkey::Pubkey,
-    sysvar::Sysvar,
 };
-use std::str::FromStr;
+use crate::account::AccountState;
+use crate::context::{
+    check_program_owned, check_signer, check_system_program, check_token_program, check_uninitialized, Accounts,
+};
 use crate::tokenization::state::TokenInfo;
 
 pub struct CreateTokenContext<'a> {
@@ -24,6 +25,10 @@ pub struct TransferTokensContext<'a> {
     pub to: &'a AccountInfo<'a>,
     pub authority: &'a AccountInfo<'a>,
     pub token_program: &'a AccountInfo<'a>,
+    /// Authority's profile, checked for the 2FA guardian co-signature
+    pub authority_profile: &'a AccountInfo<'a>,
+    /// Only needed when the transfer is above the authority's 2FA threshold
+    pub guardian: Option<&'a AccountInfo<'a>>,
 }
 
 pub struct BurnTokensContext<'a> {
@@ -35,66 +40,93 @@ pub struct BurnTokensContext<'a> {
 }
 
 impl<'a> CreateTokenContext<'a> {
-    pub fn validate(&self, _program_id: &Pubkey) -> ProgramResult {
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
         // Verify token info is not initialized
-        let token_info_data = TokenInfo::unpack_unchecked(&self.token_info.data.borrow())?;
-        if token_info_data.is_initialized {
-            return Err(ProgramError::AccountAlreadyInitialized);
-        }
+        check_uninitialized::<TokenInfo>(program_id, self.token_info)?;
         
         // Verify creator is signer
-        if !self.creator.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
+        check_signer(self.creator)?;
         
-        // Verify system program
-        if self.system_program.key != &solana_program::system_program::id() {
-            return Err(ProgramError::IncorrectProgramId);
-        }
-        
-        // Verify token program
-        if self.token_program.key != &Pubkey::from_str(&spl_token::ID.to_string()).unwrap() {
-            return Err(ProgramError::IncorrectProgramId);
-        }
-        
-        Ok(())
+        // Verify system and token programs
+        check_system_program(self.system_program)?;
+        check_token_program(self.token_program)
     }
 }
 
 impl<'a> TransferTokensContext<'a> {
-    pub fn validate(&self, _program_id: &Pubkey) -> ProgramResult {
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
         // Verify authority is signer
-        if !self.authority.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
+        check_signer(self.authority)?;
         
-        // Verify token program
-        if self.token_program.key != &Pubkey::from_str(&spl_token::ID.to_string()).unwrap() {
-            return Err(ProgramError::IncorrectProgramId);
-        }
+        // Verify the authority's profile is owned by this program
+        check_program_owned(program_id, self.authority_profile)?;
         
-        Ok(())
+        // Verify token program
+        check_token_program(self.token_program)
     }
 }
 
 impl<'a> BurnTokensContext<'a> {
-    pub fn validate(&self, _program_id: &Pubkey) -> ProgramResult {
-        // Verify token info is initialized
-        let token_info_data = TokenInfo::unpack_unchecked(&self.token_info.data.borrow())?;
-        if !token_info_data.is_initialized {
-            return Err(ProgramError::UninitializedAccount);
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
+        // Verify token info is initialized and describes this mint
+        check_program_owned(program_id, self.token_info)?;
+        let token_info_data = TokenInfo::unpack(&self.token_info.data.borrow())?;
+        if token_info_data.mint != *self.mint.key {
+            return Err(ProgramError::InvalidAccountData);
         }
         
         // Verify authority is signer
-        if !self.authority.is_signer {
-            return Err(ProgramError::MissingRequiredSignature);
-        }
+        check_signer(self.authority)?;
         
         // Verify token program
-        if self.token_program.key != &Pubkey::from_str(&spl_token::ID.to_string()).unwrap() {
-            return Err(ProgramError::IncorrectProgramId);
-        }
-        
-        Ok(())
+        check_token_program(self.token_program)
+    }
+}
+
+impl<'a> Accounts<'a> for CreateTokenContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = CreateTokenContext {
+            token_info: next_account_info(account_info_iter)?,
+            mint: next_account_info(account_info_iter)?,
+            creator_token_account: next_account_info(account_info_iter)?,
+            creator: next_account_info(account_info_iter)?,
+            token_program: next_account_info(account_info_iter)?,
+            system_program: next_account_info(account_info_iter)?,
+            rent: next_account_info(account_info_iter)?,
+        };
+        context.validate(program_id)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for TransferTokensContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = TransferTokensContext {
+            from: next_account_info(account_info_iter)?,
+            to: next_account_info(account_info_iter)?,
+            authority: next_account_info(account_info_iter)?,
+            token_program: next_account_info(account_info_iter)?,
+            authority_profile: next_account_info(account_info_iter)?,
+            guardian: account_info_iter.next(),
+        };
+        context.validate(program_id)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for BurnTokensContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = BurnTokensContext {
+            token_info: next_account_info(account_info_iter)?,
+            mint: next_account_info(account_info_iter)?,
+            from: next_account_info(account_info_iter)?,
+            authority: next_account_info(account_info_iter)?,
+            token_program: next_account_info(account_info_iter)?,
+        };
+        context.validate(program_id)?;
+        Ok(context)
     }
-} 
\ No newline at end of file
+}
@@ -1,37 +1,29 @@
 use solana_program::{
-    account_info::{next_account_info, AccountInfo},
+    account_info::AccountInfo,
     clock::Clock,
     entrypoint::ProgramResult,
     program_error::ProgramError,
-    program_pack::Pack,
     pubkey::Pubkey,
     sysvar::Sysvar,
     program::invoke_signed,
 };
+use crate::account::AccountState;
 use spl_token::instruction as token_instruction;
 use crate::tokenization::{
     state::{TokenInfo, TokenParams, TokenStatus},
     context::{CreateTokenContext, TransferTokensContext, BurnTokensContext},
 };
+use crate::context::Accounts;
+use crate::user::two_factor::check_guardian_cosign;
 
 pub fn create_token<'a>(
     program_id: &Pubkey,
     accounts: &'a [AccountInfo<'a>],
     params: TokenParams,
 ) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
-    
-    let context = CreateTokenContext {
-        token_info: next_account_info(account_info_iter)?,
-        mint: next_account_info(account_info_iter)?,
-        creator_token_account: next_account_info(account_info_iter)?,
-        creator: next_account_info(account_info_iter)?,
-        token_program: next_account_info(account_info_iter)?,
-        system_program: next_account_info(account_info_iter)?,
-        rent: next_account_info(account_info_iter)?,
-    };
+    params.validate()?;
     
-    context.validate(program_id)?;
+    let context = CreateTokenContext::try_accounts(program_id, accounts)?;
     
     let clock = Clock::get()?;
     let token_info_data = TokenInfo {
@@ -47,7 +39,7 @@ pub fn create_token<'a>(
         updated_at: clock.unix_timestamp,
     };
     
-    TokenInfo::pack(token_info_data, &mut context.token_info.data.borrow_mut())?;
+    token_info_data.pack(&mut context.token_info.data.borrow_mut())?;
     
     // Initialize mint
     let init_mint_ix = token_instruction::initialize_mint(
@@ -115,18 +107,11 @@ pub fn create_token<'a>(
 pub fn transfer_tokens<'a>(
     program_id: &Pubkey,
     accounts: &'a [AccountInfo<'a>],
-    _amount: u64,
+    amount: u64,
 ) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
-    
-    let context = TransferTokensContext {
-        from: next_account_info(account_info_iter)?,
-        to: next_account_info(account_info_iter)?,
-        authority: next_account_info(account_info_iter)?,
-        token_program: next_account_info(account_info_iter)?,
-    };
+    let context = TransferTokensContext::try_accounts(program_id, accounts)?;
     
-    context.validate(program_id)?;
+    check_guardian_cosign(program_id, context.authority.key, context.authority_profile, context.guardian, amount)?;
     
     // Transfer tokens using CPI calls
     // This part would need to be implemented using CPI calls to the SPL Token program
@@ -140,17 +125,7 @@ pub fn burn_tokens<'a>(
     accounts: &'a [AccountInfo<'a>],
     amount: u64,
 ) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
-    
-    let context = BurnTokensContext {
-        token_info: next_account_info(account_info_iter)?,
-        mint: next_account_info(account_info_iter)?,
-        from: next_account_info(account_info_iter)?,
-        authority: next_account_info(account_info_iter)?,
-        token_program: next_account_info(account_info_iter)?,
-    };
-    
-    context.validate(program_id)?;
+    let context = BurnTokensContext::try_accounts(program_id, accounts)?;
     
     // Burn tokens using CPI calls
     // This part would need to be implemented using CPI calls to the SPL Token program
@@ -161,7 +136,7 @@ pub fn burn_tokens<'a>(
     token_info_data.total_supply = token_info_data.total_supply.checked_sub(amount)
         .ok_or(ProgramError::InvalidArgument)?;
     token_info_data.updated_at = Clock::get()?.unix_timestamp;
-    TokenInfo::pack(token_info_data, &mut context.token_info.data.borrow_mut())?;
+    token_info_data.pack(&mut context.token_info.data.borrow_mut())?;
     
     Ok(())
 }
@@ -1,9 +1,13 @@
-use solana_program::{
-    program_error::ProgramError,
-    program_pack::{IsInitialized, Pack, Sealed},
-    pubkey::Pubkey,
-};
+use solana_program::pubkey::Pubkey;
 use borsh::{BorshSerialize, BorshDeserialize};
+use crate::account::{AccountState, AccountType, ACCOUNT_HEADER_LEN};
+use solana_program::program_error::ProgramError;
+use crate::error::UnityVaultError;
+use crate::migration::{read_padded_string, Migrate, LEGACY_LAYOUT_VERSION};
+use crate::validation::check_str_len;
+
+pub const MAX_TOKEN_NAME_LEN: usize = 100;
+pub const MAX_TOKEN_SYMBOL_LEN: usize = 10;
 
 #[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TokenStatus {
@@ -20,6 +24,14 @@ pub struct TokenParams {
     pub total_supply: u64,
 }
 
+impl TokenParams {
+    pub fn validate(&self) -> Result<(), UnityVaultError> {
+        check_str_len("name", &self.name, MAX_TOKEN_NAME_LEN)?;
+        check_str_len("symbol", &self.symbol, MAX_TOKEN_SYMBOL_LEN)?;
+        Ok(())
+    }
+}
+
 #[derive(Debug, BorshSerialize, BorshDeserialize, Clone)]
 pub struct TokenInfo {
     pub is_initialized: bool,
@@ -35,11 +47,12 @@ pub struct TokenInfo {
 }
 
 impl TokenInfo {
-    pub const LEN: usize = 1 + // is_initialized
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + // account type and version
+        1 + // is_initialized
         32 + // creator
         32 + // mint
-        4 + 100 + // name (max 100 chars)
-        4 + 10 + // symbol (max 10 chars)
+        4 + MAX_TOKEN_NAME_LEN + // name
+        4 + MAX_TOKEN_SYMBOL_LEN + // symbol
         1 + // decimals
         8 + // total_supply
         1 + // status
@@ -47,118 +60,97 @@ impl TokenInfo {
         8; // updated_at
 }
 
-impl Sealed for TokenInfo {}
+impl AccountState for TokenInfo {
+    const ACCOUNT_TYPE: AccountType = AccountType::TokenInfo;
+    const VERSION: u8 = 1;
+    const LEN: usize = Self::LEN;
 
-impl IsInitialized for TokenInfo {
-    fn is_initialized(&self) -> bool {
-        self.is_initialized
+    fn validate_bounds(&self) -> Result<(), UnityVaultError> {
+        check_str_len("name", &self.name, MAX_TOKEN_NAME_LEN)?;
+        check_str_len("symbol", &self.symbol, MAX_TOKEN_SYMBOL_LEN)?;
+        Ok(())
     }
 }
 
-impl Pack for TokenInfo {
-    const LEN: usize = Self::LEN;
+impl Migrate for TokenInfo {
+    const LEGACY_LEN: Option<usize> = Some(209);
 
-    fn pack_into_slice(&self, dst: &mut [u8]) {
-        let mut offset = 0;
-        
-        dst[offset] = self.is_initialized as u8;
-        offset += 1;
-        
-        dst[offset..offset + 32].copy_from_slice(&self.creator.to_bytes());
-        offset += 32;
-        
-        dst[offset..offset + 32].copy_from_slice(&self.mint.to_bytes());
-        offset += 32;
-        
-        // Pack name
-        let name_bytes = self.name.as_bytes();
-        dst[offset..offset + 4].copy_from_slice(&(name_bytes.len() as u32).to_le_bytes());
-        offset += 4;
-        dst[offset..offset + name_bytes.len()].copy_from_slice(name_bytes);
-        offset += 100;
-        
-        // Pack symbol
-        let symbol_bytes = self.symbol.as_bytes();
-        dst[offset..offset + 4].copy_from_slice(&(symbol_bytes.len() as u32).to_le_bytes());
-        offset += 4;
-        dst[offset..offset + symbol_bytes.len()].copy_from_slice(symbol_bytes);
-        offset += 10;
-        
-        dst[offset] = self.decimals;
-        offset += 1;
-        
-        dst[offset..offset + 8].copy_from_slice(&self.total_supply.to_le_bytes());
-        offset += 8;
-        
-        dst[offset] = self.status as u8;
-        offset += 1;
-        
-        dst[offset..offset + 8].copy_from_slice(&self.created_at.to_le_bytes());
-        offset += 8;
-        
-        dst[offset..offset + 8].copy_from_slice(&self.updated_at.to_le_bytes());
-    }
+    fn decode_version(version: u8, src: &[u8]) -> Result<Self, ProgramError> {
+        if version != LEGACY_LAYOUT_VERSION {
+            return Err(UnityVaultError::UnsupportedAccountVersion.into());
+        }
+
+        // The legacy layout stored name and symbol in fixed-width slots
+        let src = &mut &src[..];
+        let decode_error = |_| ProgramError::InvalidAccountData;
+        let is_initialized = bool::deserialize(src).map_err(decode_error)?;
+        let creator = Pubkey::deserialize(src).map_err(decode_error)?;
+        let mint = Pubkey::deserialize(src).map_err(decode_error)?;
+        let name = read_padded_string(src, MAX_TOKEN_NAME_LEN)?;
+        let symbol = read_padded_string(src, MAX_TOKEN_SYMBOL_LEN)?;
 
-    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
-        let mut offset = 0;
-        
-        let is_initialized = src[offset] != 0;
-        offset += 1;
-        
-        let creator_bytes: [u8; 32] = src[offset..offset + 32].try_into()
-            .map_err(|_| ProgramError::InvalidAccountData)?;
-        let creator = Pubkey::from(creator_bytes);
-        offset += 32;
-        
-        let mint_bytes: [u8; 32] = src[offset..offset + 32].try_into()
-            .map_err(|_| ProgramError::InvalidAccountData)?;
-        let mint = Pubkey::from(mint_bytes);
-        offset += 32;
-        
-        // Unpack name
-        let name_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
-        offset += 4;
-        let name = String::from_utf8(src[offset..offset + name_len].to_vec())
-            .map_err(|_| ProgramError::InvalidAccountData)?;
-        offset += 100;
-        
-        // Unpack symbol
-        let symbol_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
-        offset += 4;
-        let symbol = String::from_utf8(src[offset..offset + symbol_len].to_vec())
-            .map_err(|_| ProgramError::InvalidAccountData)?;
-        offset += 10;
-        
-        let decimals = src[offset];
-        offset += 1;
-        
-        let total_supply = u64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
-        offset += 8;
-        
-        let status = match src[offset] {
-            0 => TokenStatus::Active,
-            1 => TokenStatus::Paused,
-            2 => TokenStatus::Frozen,
-            _ => return Err(ProgramError::InvalidAccountData),
-        };
-        offset += 1;
-        
-        let created_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
-        offset += 8;
-        
-        let updated_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
-        
         Ok(TokenInfo {
             is_initialized,
             creator,
             mint,
             name,
             symbol,
-            decimals,
-            total_supply,
-            status,
-            created_at,
-            updated_at,
+            decimals: u8::deserialize(src).map_err(decode_error)?,
+            total_supply: u64::deserialize(src).map_err(decode_error)?,
+            status: TokenStatus::deserialize(src).map_err(decode_error)?,
+            created_at: i64::deserialize(src).map_err(decode_error)?,
+            updated_at: i64::deserialize(src).map_err(decode_error)?,
         })
     }
-} 
\ No newline at end of file
+
+    fn migration_authority(&self) -> Pubkey {
+        self.creator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::account::tests::assert_max_len_fits;
+
+    fn token_params(name_len: usize, symbol_len: usize) -> TokenParams {
+        TokenParams {
+            name: "n".repeat(name_len),
+            symbol: "S".repeat(symbol_len),
+            decimals: 9,
+            total_supply: 1_000_000,
+        }
+    }
+
+    #[test]
+    fn test_token_params_length_boundaries() {
+        assert_eq!(token_params(MAX_TOKEN_NAME_LEN, MAX_TOKEN_SYMBOL_LEN).validate(), Ok(()));
+        assert_eq!(
+            token_params(MAX_TOKEN_NAME_LEN + 1, 1).validate(),
+            Err(UnityVaultError::StringTooLong)
+        );
+        assert_eq!(
+            token_params(1, MAX_TOKEN_SYMBOL_LEN + 1).validate(),
+            Err(UnityVaultError::StringTooLong)
+        );
+    }
+
+    #[test]
+    fn test_pack_max_length_fields_fits_len() {
+        let params = token_params(MAX_TOKEN_NAME_LEN, MAX_TOKEN_SYMBOL_LEN);
+        let token_info = TokenInfo {
+            is_initialized: true,
+            creator: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            name: params.name.clone(),
+            symbol: params.symbol.clone(),
+            decimals: params.decimals,
+            total_supply: params.total_supply,
+            status: TokenStatus::Active,
+            created_at: 0,
+            updated_at: 0,
+        };
+
+        assert_max_len_fits(&token_info);
+    }
+}
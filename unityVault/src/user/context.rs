@@ -0,0 +1,155 @@
+use solana_program::{
+    account_info::{next_account_info, AccountInfo},
+    entrypoint::ProgramResult,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use crate::account::AccountState;
+use crate::context::{
+    check_program_owned, check_signer, check_system_program, check_upgrade_authority, Accounts,
+};
+use crate::user::state::UserProfile;
+
+pub struct CreateUserProfileContext<'a> {
+    pub user_profile: &'a AccountInfo<'a>,
+    pub authority: &'a AccountInfo<'a>,
+    pub system_program: &'a AccountInfo<'a>,
+}
+
+/// A profile and its signing authority, for the handlers only the
+/// authority can call.
+pub struct UserProfileAuthorityContext<'a> {
+    pub user_profile: &'a AccountInfo<'a>,
+    pub authority: &'a AccountInfo<'a>,
+}
+
+pub struct RotateAuthorityContext<'a> {
+    pub user_profile: &'a AccountInfo<'a>,
+    pub authority: &'a AccountInfo<'a>,
+    pub new_authority: &'a AccountInfo<'a>,
+}
+
+/// A profile and the program's upgrade authority, the only key that can
+/// change a profile's platform role.
+pub struct SetUserRoleContext<'a> {
+    pub user_profile: &'a AccountInfo<'a>,
+    pub platform_authority: &'a AccountInfo<'a>,
+    pub program_data: &'a AccountInfo<'a>,
+}
+
+/// A profile under recovery and the guardians signing for it.
+pub struct RecoveryContext<'a> {
+    pub user_profile: &'a AccountInfo<'a>,
+    pub guardians: &'a [AccountInfo<'a>],
+}
+
+impl<'a> CreateUserProfileContext<'a> {
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
+        // Verify user profile is not created yet; the handler creates it at its PDA
+        if self.user_profile.owner == program_id || UserProfile::is_initialized_account(&self.user_profile.data.borrow()) {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+
+        // Verify authority is signer
+        check_signer(self.authority)?;
+
+        // Verify system program
+        check_system_program(self.system_program)
+    }
+}
+
+impl<'a> Accounts<'a> for CreateUserProfileContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = CreateUserProfileContext {
+            user_profile: next_account_info(account_info_iter)?,
+            authority: next_account_info(account_info_iter)?,
+            system_program: next_account_info(account_info_iter)?,
+        };
+        context.validate(program_id)?;
+        Ok(context)
+    }
+}
+
+impl<'a> UserProfileAuthorityContext<'a> {
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
+        // Verify authority is signer
+        check_signer(self.authority)?;
+
+        // Verify authority matches
+        check_profile_authority(program_id, self.user_profile, self.authority)
+    }
+}
+
+impl<'a> Accounts<'a> for UserProfileAuthorityContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = UserProfileAuthorityContext {
+            user_profile: next_account_info(account_info_iter)?,
+            authority: next_account_info(account_info_iter)?,
+        };
+        context.validate(program_id)?;
+        Ok(context)
+    }
+}
+
+impl<'a> RotateAuthorityContext<'a> {
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
+        // Both the current and the new key must sign so a typo can't strand the profile
+        check_signer(self.authority)?;
+        check_signer(self.new_authority)?;
+
+        // Verify authority matches
+        check_profile_authority(program_id, self.user_profile, self.authority)
+    }
+}
+
+impl<'a> Accounts<'a> for RotateAuthorityContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = RotateAuthorityContext {
+            user_profile: next_account_info(account_info_iter)?,
+            authority: next_account_info(account_info_iter)?,
+            new_authority: next_account_info(account_info_iter)?,
+        };
+        context.validate(program_id)?;
+        Ok(context)
+    }
+}
+
+impl<'a> SetUserRoleContext<'a> {
+    pub fn validate(&self, program_id: &Pubkey) -> ProgramResult {
+        check_program_owned(program_id, self.user_profile)?;
+        check_upgrade_authority(program_id, self.program_data, self.platform_authority)
+    }
+}
+
+impl<'a> Accounts<'a> for SetUserRoleContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let account_info_iter = &mut accounts.iter();
+        let context = SetUserRoleContext {
+            user_profile: next_account_info(account_info_iter)?,
+            platform_authority: next_account_info(account_info_iter)?,
+            program_data: next_account_info(account_info_iter)?,
+        };
+        context.validate(program_id)?;
+        Ok(context)
+    }
+}
+
+impl<'a> Accounts<'a> for RecoveryContext<'a> {
+    fn try_accounts(program_id: &Pubkey, accounts: &'a [AccountInfo<'a>]) -> Result<Self, ProgramError> {
+        let (user_profile, guardians) = accounts.split_first().ok_or(ProgramError::NotEnoughAccountKeys)?;
+        check_program_owned(program_id, user_profile)?;
+        Ok(RecoveryContext { user_profile, guardians })
+    }
+}
+
+fn check_profile_authority(program_id: &Pubkey, user_profile: &AccountInfo, authority: &AccountInfo) -> ProgramResult {
+    check_program_owned(program_id, user_profile)?;
+    let user_profile_data = UserProfile::unpack(&user_profile.data.borrow())?;
+    if user_profile_data.authority != *authority.key {
+        return Err(ProgramError::IllegalOwner);
+    }
+    Ok(())
+}
@@ -1,54 +1,100 @@
 use solana_program::{
-    account_info::{next_account_info, AccountInfo},
+    account_info::AccountInfo,
     clock::Clock,
     entrypoint::ProgramResult,
     program_error::ProgramError,
-    program_pack::Pack,
     pubkey::Pubkey,
     sysvar::Sysvar,
 };
-use crate::user::state::{UserProfile, UserRole, UserStatus, KycStatus, KycData};
+use crate::account::{create_pda_account, AccountState};
+use crate::context::Accounts;
+use crate::error::UnityVaultError;
+use crate::user::context::{
+    CreateUserProfileContext, RecoveryContext, RotateAuthorityContext, SetUserRoleContext, UserProfileAuthorityContext,
+};
+use crate::user::state::{
+    UserProfile, UserRole, UserStatus, KycStatus, KycData, MAX_BACKUP_CODES, MAX_BACKUP_CODE_LEN,
+    MAX_EMAIL_LEN, MAX_FULL_NAME_LEN, MAX_RECOVERY_GUARDIANS, MIN_RECOVERY_TIMELOCK, USER_PROFILE_SEED,
+};
+use crate::user::two_factor::hash_backup_code;
+use crate::validation::{check_str_len, check_vec_len};
 use borsh::{BorshSerialize, BorshDeserialize};
 
+/// Profile details the authority sets itself. The platform role isn't one
+/// of them: profiles start as `UserRole::User` and only `set_user_role`
+/// changes that.
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct UserProfileParams {
     pub full_name: String,
     pub email: String,
-    pub role: UserRole,
 }
 
-pub fn create_user_profile(
-    _program_id: &Pubkey,
-    accounts: &[AccountInfo],
+impl UserProfileParams {
+    pub fn validate(&self) -> Result<(), UnityVaultError> {
+        check_str_len("full_name", &self.full_name, MAX_FULL_NAME_LEN)?;
+        check_str_len("email", &self.email, MAX_EMAIL_LEN)?;
+        Ok(())
+    }
+}
+
+fn validate_two_factor_setup(backup_codes: &[String]) -> Result<(), UnityVaultError> {
+    check_vec_len("two_factor_backup_codes", backup_codes, MAX_BACKUP_CODES)?;
+    for code in backup_codes {
+        check_str_len("two_factor_backup_code", code, MAX_BACKUP_CODE_LEN)?;
+    }
+    Ok(())
+}
+
+/// Creates the profile of `authority` at its PDA. A key can have only one
+/// profile, so the 2FA settings on it can't be sidestepped with another.
+///
+/// 0. `[writable]` The user profile PDA
+/// 1. `[signer, writable]` The authority, paying for the profile
+/// 2. `[]` The system program
+pub fn create_user_profile<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
     params: UserProfileParams,
 ) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
+    params.validate()?;
     
-    let user_profile = next_account_info(account_info_iter)?;
-    let authority = next_account_info(account_info_iter)?;
-    let system_program = next_account_info(account_info_iter)?;
+    let CreateUserProfileContext { user_profile, authority, system_program } =
+        CreateUserProfileContext::try_accounts(program_id, accounts)?;
     
-    // Verify authority is signer
-    if !authority.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
+    // Verify user profile address
+    let (user_profile_key, bump) = UserProfile::find_address(program_id, authority.key);
+    if user_profile.key != &user_profile_key {
+        return Err(ProgramError::InvalidSeeds);
     }
     
-    // Verify system program
-    if system_program.key != &solana_program::system_program::id() {
-        return Err(ProgramError::IncorrectProgramId);
-    }
+    create_pda_account(
+        program_id,
+        authority,
+        user_profile,
+        system_program,
+        UserProfile::LEN,
+        &[USER_PROFILE_SEED, authority.key.as_ref(), &[bump]],
+    )?;
     
-    // Create and initialize user profile
+    // Initialize user profile
     let mut user_profile_data = UserProfile {
         is_initialized: true,
         authority: *authority.key,
         full_name: params.full_name,
         email: params.email,
-        role: params.role,
+        role: UserRole::User,
         status: UserStatus::Active,
         two_factor_enabled: false,
-        two_factor_secret: String::new(),
+        two_factor_secret_commitment: [0; 32],
         two_factor_backup_codes: Vec::new(),
+        two_factor_guardian: Pubkey::default(),
+        two_factor_threshold: 0,
+        recovery_guardians: Vec::new(),
+        recovery_threshold: 0,
+        recovery_timelock: 0,
+        pending_recovery_authority: Pubkey::default(),
+        recovery_approvals: 0,
+        recovery_initiated_at: 0,
         kyc_verified: false,
         kyc_status: KycStatus::Pending,
         kyc_data: KycData {
@@ -62,99 +108,141 @@ pub fn create_user_profile(
     };
     
     // Pack the data into the account
-    user_profile_data.pack_into_slice(&mut user_profile.data.borrow_mut());
+    user_profile_data.pack(&mut user_profile.data.borrow_mut())?;
     
     Ok(())
 }
 
-pub fn update_user_profile(
-    _program_id: &Pubkey,
-    accounts: &[AccountInfo],
+pub fn update_user_profile<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
     params: UserProfileParams,
 ) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
-    
-    let user_profile = next_account_info(account_info_iter)?;
-    let authority = next_account_info(account_info_iter)?;
+    params.validate()?;
     
-    // Verify authority is signer
-    if !authority.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    let UserProfileAuthorityContext { user_profile, .. } = UserProfileAuthorityContext::try_accounts(program_id, accounts)?;
     
-    // Verify authority matches
-    let mut user_profile_data = UserProfile::unpack_from_slice(&user_profile.data.borrow())?;
-    if user_profile_data.authority != *authority.key {
-        return Err(ProgramError::IllegalOwner);
-    }
+    let mut user_profile_data = UserProfile::unpack(&user_profile.data.borrow())?;
     
     // Update profile
     user_profile_data.full_name = params.full_name;
     user_profile_data.email = params.email;
-    user_profile_data.role = params.role;
     user_profile_data.updated_at = Clock::get()?.unix_timestamp;
     
     // Pack the updated data
-    user_profile_data.pack_into_slice(&mut user_profile.data.borrow_mut());
+    user_profile_data.pack(&mut user_profile.data.borrow_mut())?;
     
     Ok(())
 }
 
-pub fn enable_two_factor(
-    _program_id: &Pubkey,
-    accounts: &[AccountInfo],
-    secret: String,
+/// Grants or revokes a platform role. Platform moderators and admins can
+/// suspend and reinstate any community, so the role is set by the program's
+/// upgrade authority rather than by the profile's own authority.
+///
+/// 0. `[writable]` The user profile
+/// 1. `[signer]` The program's upgrade authority
+/// 2. `[]` The program's data account
+pub fn set_user_role<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    role: UserRole,
+) -> ProgramResult {
+    let SetUserRoleContext { user_profile, .. } = SetUserRoleContext::try_accounts(program_id, accounts)?;
+    
+    let mut user_profile_data = UserProfile::unpack(&user_profile.data.borrow())?;
+    
+    user_profile_data.role = role;
+    user_profile_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    // Pack the updated data
+    user_profile_data.pack(&mut user_profile.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+pub fn enable_two_factor<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    secret_commitment: [u8; 32],
     backup_codes: Vec<String>,
+    guardian: Pubkey,
+    high_value_threshold: u64,
 ) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
+    validate_two_factor_setup(&backup_codes)?;
     
-    let user_profile = next_account_info(account_info_iter)?;
-    let authority = next_account_info(account_info_iter)?;
+    let UserProfileAuthorityContext { user_profile, .. } = UserProfileAuthorityContext::try_accounts(program_id, accounts)?;
     
-    // Verify authority is signer
-    if !authority.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
-    }
+    let mut user_profile_data = UserProfile::unpack(&user_profile.data.borrow())?;
     
-    // Verify authority matches
-    let mut user_profile_data = UserProfile::unpack_from_slice(&user_profile.data.borrow())?;
-    if user_profile_data.authority != *authority.key {
-        return Err(ProgramError::IllegalOwner);
+    // Reconfiguring would swap the guardian and backup codes without a backup code,
+    // so 2FA has to go through disable_two_factor first
+    if user_profile_data.two_factor_enabled {
+        return Err(UnityVaultError::TwoFactorAlreadyEnabled.into());
     }
     
     // Enable 2FA
     user_profile_data.two_factor_enabled = true;
-    user_profile_data.two_factor_secret = secret;
-    user_profile_data.two_factor_backup_codes = backup_codes;
+    user_profile_data.two_factor_secret_commitment = secret_commitment;
+    user_profile_data.two_factor_backup_codes = backup_codes
+        .iter()
+        .map(|code| hash_backup_code(code))
+        .collect();
+    user_profile_data.two_factor_guardian = guardian;
+    user_profile_data.two_factor_threshold = high_value_threshold;
     user_profile_data.updated_at = Clock::get()?.unix_timestamp;
     
     // Pack the updated data
-    user_profile_data.pack_into_slice(&mut user_profile.data.borrow_mut());
+    user_profile_data.pack(&mut user_profile.data.borrow_mut())?;
     
     Ok(())
 }
 
-pub fn verify_kyc(
-    _program_id: &Pubkey,
-    accounts: &[AccountInfo],
-    kyc_data: KycData,
+pub fn disable_two_factor<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    backup_code: String,
 ) -> ProgramResult {
-    let account_info_iter = &mut accounts.iter();
+    check_str_len("two_factor_backup_code", &backup_code, MAX_BACKUP_CODE_LEN)?;
     
-    let user_profile = next_account_info(account_info_iter)?;
-    let authority = next_account_info(account_info_iter)?;
+    let UserProfileAuthorityContext { user_profile, .. } = UserProfileAuthorityContext::try_accounts(program_id, accounts)?;
     
-    // Verify authority is signer
-    if !authority.is_signer {
-        return Err(ProgramError::MissingRequiredSignature);
+    let mut user_profile_data = UserProfile::unpack(&user_profile.data.borrow())?;
+    
+    if !user_profile_data.two_factor_enabled {
+        return Err(UnityVaultError::TwoFactorNotEnabled.into());
     }
     
-    // Verify authority matches
-    let mut user_profile_data = UserProfile::unpack_from_slice(&user_profile.data.borrow())?;
-    if user_profile_data.authority != *authority.key {
-        return Err(ProgramError::IllegalOwner);
+    // Verify backup code
+    let backup_code_hash = hash_backup_code(&backup_code);
+    if !user_profile_data.two_factor_backup_codes.contains(&backup_code_hash) {
+        return Err(UnityVaultError::InvalidBackupCode.into());
     }
     
+    // Disable 2FA
+    user_profile_data.two_factor_enabled = false;
+    user_profile_data.two_factor_secret_commitment = [0; 32];
+    user_profile_data.two_factor_backup_codes = Vec::new();
+    user_profile_data.two_factor_guardian = Pubkey::default();
+    user_profile_data.two_factor_threshold = 0;
+    user_profile_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    // Pack the updated data
+    user_profile_data.pack(&mut user_profile.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+pub fn verify_kyc<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    kyc_data: KycData,
+) -> ProgramResult {
+    kyc_data.validate()?;
+    
+    let UserProfileAuthorityContext { user_profile, .. } = UserProfileAuthorityContext::try_accounts(program_id, accounts)?;
+    
+    let mut user_profile_data = UserProfile::unpack(&user_profile.data.borrow())?;
+    
     // Update KYC status
     user_profile_data.kyc_verified = true;
     user_profile_data.kyc_status = KycStatus::Verified;
@@ -162,7 +250,341 @@ pub fn verify_kyc(
     user_profile_data.updated_at = Clock::get()?.unix_timestamp;
     
     // Pack the updated data
-    user_profile_data.pack_into_slice(&mut user_profile.data.borrow_mut());
+    user_profile_data.pack(&mut user_profile.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+pub fn rotate_authority<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let RotateAuthorityContext { user_profile, new_authority, .. } = RotateAuthorityContext::try_accounts(program_id, accounts)?;
+    
+    let mut user_profile_data = UserProfile::unpack(&user_profile.data.borrow())?;
+    
+    // Rotate authority, dropping any recovery in flight
+    user_profile_data.authority = *new_authority.key;
+    user_profile_data.clear_pending_recovery();
+    user_profile_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    // Pack the updated data
+    user_profile_data.pack(&mut user_profile.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+pub fn set_recovery_guardians<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    guardians: Vec<Pubkey>,
+    threshold: u8,
+    timelock: i64,
+) -> ProgramResult {
+    check_vec_len("recovery_guardians", &guardians, MAX_RECOVERY_GUARDIANS)?;
+    
+    let UserProfileAuthorityContext { user_profile, .. } = UserProfileAuthorityContext::try_accounts(program_id, accounts)?;
+    
+    let mut user_profile_data = UserProfile::unpack(&user_profile.data.borrow())?;
+    
+    // Validate guardian set; an empty set with a zero threshold turns recovery off
+    let disabling = guardians.is_empty() && threshold == 0;
+    if !disabling {
+        if threshold == 0
+            || threshold as usize > guardians.len()
+            || timelock < MIN_RECOVERY_TIMELOCK
+        {
+            return Err(UnityVaultError::InvalidRecoveryConfig.into());
+        }
+        for (i, guardian) in guardians.iter().enumerate() {
+            if *guardian == user_profile_data.authority || guardians[..i].contains(guardian) {
+                return Err(UnityVaultError::InvalidRecoveryConfig.into());
+            }
+        }
+    }
+    
+    // Update guardians; approvals refer to guardian positions so any pending recovery is void
+    user_profile_data.recovery_guardians = guardians;
+    user_profile_data.recovery_threshold = threshold;
+    user_profile_data.recovery_timelock = if disabling { 0 } else { timelock };
+    user_profile_data.clear_pending_recovery();
+    user_profile_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    // Pack the updated data
+    user_profile_data.pack(&mut user_profile.data.borrow_mut())?;
     
     Ok(())
-} 
\ No newline at end of file
+}
+
+pub fn initiate_recovery<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+    new_authority: Pubkey,
+) -> ProgramResult {
+    let RecoveryContext { user_profile, guardians } = RecoveryContext::try_accounts(program_id, accounts)?;
+    
+    let mut user_profile_data = UserProfile::unpack(&user_profile.data.borrow())?;
+    if new_authority == Pubkey::default() || new_authority == user_profile_data.authority {
+        return Err(ProgramError::InvalidArgument);
+    }
+    
+    user_profile_data.recovery_approvals = 0;
+    let approvals = user_profile_data.approve_recovery(
+        guardians.iter().filter(|account| account.is_signer).map(|account| account.key),
+    );
+    if approvals == 0 {
+        return Err(UnityVaultError::NotARecoveryGuardian.into());
+    }
+    
+    // A pending recovery can only be replaced by a quorum of guardians, otherwise a single
+    // guardian could keep restarting the timelock
+    if user_profile_data.has_pending_recovery() && approvals < user_profile_data.recovery_threshold as u32 {
+        return Err(UnityVaultError::RecoveryThresholdNotMet.into());
+    }
+    
+    let current_time = Clock::get()?.unix_timestamp;
+    user_profile_data.pending_recovery_authority = new_authority;
+    user_profile_data.recovery_initiated_at = current_time;
+    user_profile_data.updated_at = current_time;
+    
+    // Pack the updated data
+    user_profile_data.pack(&mut user_profile.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+pub fn approve_recovery<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let RecoveryContext { user_profile, guardians } = RecoveryContext::try_accounts(program_id, accounts)?;
+    
+    let mut user_profile_data = UserProfile::unpack(&user_profile.data.borrow())?;
+    if !user_profile_data.has_pending_recovery() {
+        return Err(UnityVaultError::NoPendingRecovery.into());
+    }
+    
+    let approvals = user_profile_data.approve_recovery(
+        guardians.iter().filter(|account| account.is_signer).map(|account| account.key),
+    );
+    if approvals == 0 {
+        return Err(UnityVaultError::NotARecoveryGuardian.into());
+    }
+    
+    user_profile_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    // Pack the updated data
+    user_profile_data.pack(&mut user_profile.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+pub fn complete_recovery<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let RecoveryContext { user_profile, .. } = RecoveryContext::try_accounts(program_id, accounts)?;
+    
+    let mut user_profile_data = UserProfile::unpack(&user_profile.data.borrow())?;
+    let current_time = Clock::get()?.unix_timestamp;
+    user_profile_data.check_recovery_ready(current_time)?;
+    
+    // Move the profile to the recovered authority
+    user_profile_data.authority = user_profile_data.pending_recovery_authority;
+    user_profile_data.clear_pending_recovery();
+    user_profile_data.updated_at = current_time;
+    
+    // Pack the updated data
+    user_profile_data.pack(&mut user_profile.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+pub fn cancel_recovery<'a>(
+    program_id: &Pubkey,
+    accounts: &'a [AccountInfo<'a>],
+) -> ProgramResult {
+    let UserProfileAuthorityContext { user_profile, .. } = UserProfileAuthorityContext::try_accounts(program_id, accounts)?;
+    
+    let mut user_profile_data = UserProfile::unpack(&user_profile.data.borrow())?;
+    
+    if !user_profile_data.has_pending_recovery() {
+        return Err(UnityVaultError::NoPendingRecovery.into());
+    }
+    
+    user_profile_data.clear_pending_recovery();
+    user_profile_data.updated_at = Clock::get()?.unix_timestamp;
+    
+    // Pack the updated data
+    user_profile_data.pack(&mut user_profile.data.borrow_mut())?;
+    
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::user::state::{MAX_DOCUMENT_NUMBER_LEN, MAX_DOCUMENT_TYPE_LEN};
+    
+    fn profile_params(full_name_len: usize, email_len: usize) -> UserProfileParams {
+        UserProfileParams {
+            full_name: "a".repeat(full_name_len),
+            email: "b".repeat(email_len),
+        }
+    }
+    
+    #[test]
+    fn test_user_profile_params_length_boundaries() {
+        assert_eq!(profile_params(MAX_FULL_NAME_LEN, MAX_EMAIL_LEN).validate(), Ok(()));
+        assert_eq!(
+            profile_params(MAX_FULL_NAME_LEN + 1, MAX_EMAIL_LEN).validate(),
+            Err(UnityVaultError::StringTooLong)
+        );
+        assert_eq!(
+            profile_params(MAX_FULL_NAME_LEN, MAX_EMAIL_LEN + 1).validate(),
+            Err(UnityVaultError::StringTooLong)
+        );
+    }
+    
+    #[test]
+    fn test_two_factor_setup_length_boundaries() {
+        let codes = vec!["c".repeat(MAX_BACKUP_CODE_LEN); MAX_BACKUP_CODES];
+        assert_eq!(validate_two_factor_setup(&codes), Ok(()));
+        
+        let too_many_codes = vec!["c".to_string(); MAX_BACKUP_CODES + 1];
+        assert_eq!(validate_two_factor_setup(&too_many_codes), Err(UnityVaultError::TooManyEntries));
+        
+        let mut long_code = codes.clone();
+        long_code[MAX_BACKUP_CODES - 1] = "c".repeat(MAX_BACKUP_CODE_LEN + 1);
+        assert_eq!(validate_two_factor_setup(&long_code), Err(UnityVaultError::StringTooLong));
+    }
+    
+    #[test]
+    fn test_kyc_data_length_boundaries() {
+        let kyc_data = |document_type_len: usize, document_number_len: usize| KycData {
+            document_type: "t".repeat(document_type_len),
+            document_number: "n".repeat(document_number_len),
+            verified_at: 0,
+        };
+        assert_eq!(kyc_data(MAX_DOCUMENT_TYPE_LEN, MAX_DOCUMENT_NUMBER_LEN).validate(), Ok(()));
+        assert_eq!(
+            kyc_data(MAX_DOCUMENT_TYPE_LEN + 1, MAX_DOCUMENT_NUMBER_LEN).validate(),
+            Err(UnityVaultError::StringTooLong)
+        );
+        assert_eq!(
+            kyc_data(MAX_DOCUMENT_TYPE_LEN, MAX_DOCUMENT_NUMBER_LEN + 1).validate(),
+            Err(UnityVaultError::StringTooLong)
+        );
+    }
+    
+    #[test]
+    fn test_oversized_inputs_rejected_before_touching_accounts() {
+        // No accounts are passed: validation must fail first instead of NotEnoughAccountKeys
+        let program_id = Pubkey::new_unique();
+        assert_eq!(
+            create_user_profile(&program_id, &[], profile_params(MAX_FULL_NAME_LEN + 1, 0)),
+            Err(UnityVaultError::StringTooLong.into())
+        );
+        assert_eq!(
+            set_recovery_guardians(&program_id, &[], vec![Pubkey::new_unique(); MAX_RECOVERY_GUARDIANS + 1], 1, 0),
+            Err(UnityVaultError::TooManyEntries.into())
+        );
+        assert_eq!(
+            disable_two_factor(&program_id, &[], "c".repeat(MAX_BACKUP_CODE_LEN + 1)),
+            Err(UnityVaultError::StringTooLong.into())
+        );
+    }
+    
+    #[test]
+    fn test_create_user_profile_only_at_authority_pda() {
+        use solana_program::clock::Epoch;
+        
+        let program_id = Pubkey::new_unique();
+        let system_program = solana_program::system_program::id();
+        let authority_key = Pubkey::new_unique();
+        
+        // A second profile for the same key at a keypair address
+        let profile_key = Pubkey::new_unique();
+        let mut profile_lamports = 0;
+        let mut profile_data = vec![];
+        let mut authority_lamports = 0;
+        let mut authority_data = vec![];
+        let mut system_program_lamports = 0;
+        let mut system_program_data = vec![];
+        let accounts = vec![
+            AccountInfo::new(&profile_key, true, true, &mut profile_lamports, &mut profile_data, &system_program, false, Epoch::default()),
+            AccountInfo::new(&authority_key, true, true, &mut authority_lamports, &mut authority_data, &system_program, false, Epoch::default()),
+            AccountInfo::new(&system_program, false, false, &mut system_program_lamports, &mut system_program_data, &system_program, true, Epoch::default()),
+        ];
+        
+        assert_eq!(
+            create_user_profile(&program_id, &accounts, profile_params(1, 1)),
+            Err(ProgramError::InvalidSeeds)
+        );
+    }
+    
+    #[test]
+    fn test_enable_two_factor_rejects_reconfiguration() {
+        use crate::user::state::tests::test_profile;
+        use solana_program::clock::Epoch;
+        
+        let program_id = Pubkey::new_unique();
+        let mut profile = test_profile();
+        profile.two_factor_enabled = true;
+        profile.two_factor_guardian = Pubkey::new_unique();
+        profile.two_factor_threshold = 1_000;
+        let authority_key = profile.authority;
+        
+        let profile_key = Pubkey::new_unique();
+        let mut profile_lamports = 0;
+        let mut profile_data = vec![0; UserProfile::LEN];
+        profile.pack(&mut profile_data).unwrap();
+        let mut authority_lamports = 0;
+        let mut authority_data = vec![];
+        let system_program = solana_program::system_program::id();
+        let accounts = vec![
+            AccountInfo::new(&profile_key, false, true, &mut profile_lamports, &mut profile_data, &program_id, false, Epoch::default()),
+            AccountInfo::new(&authority_key, true, false, &mut authority_lamports, &mut authority_data, &system_program, false, Epoch::default()),
+        ];
+        
+        // Without this the authority key alone could zero the threshold and replace the guardian
+        assert_eq!(
+            enable_two_factor(&program_id, &accounts, [0; 32], Vec::new(), Pubkey::default(), 0),
+            Err(UnityVaultError::TwoFactorAlreadyEnabled.into())
+        );
+    }
+    
+    #[test]
+    fn test_set_user_role_requires_upgrade_authority() {
+        use crate::user::state::tests::test_profile;
+        use solana_program::{bpf_loader_upgradeable, clock::Epoch};
+        
+        let program_id = Pubkey::new_unique();
+        let profile = test_profile();
+        let authority_key = profile.authority;
+        
+        let profile_key = Pubkey::new_unique();
+        let mut profile_lamports = 0;
+        let mut profile_data = vec![0; UserProfile::LEN];
+        profile.pack(&mut profile_data).unwrap();
+        let mut authority_lamports = 0;
+        let mut authority_data = vec![];
+        let loader = bpf_loader_upgradeable::id();
+        let (program_data_key, _) = Pubkey::find_program_address(&[program_id.as_ref()], &loader);
+        let mut program_data_lamports = 0;
+        let mut program_data = vec![3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1];
+        program_data.extend_from_slice(Pubkey::new_unique().as_ref());
+        let system_program = solana_program::system_program::id();
+        let accounts = vec![
+            AccountInfo::new(&profile_key, false, true, &mut profile_lamports, &mut profile_data, &program_id, false, Epoch::default()),
+            AccountInfo::new(&authority_key, true, false, &mut authority_lamports, &mut authority_data, &system_program, false, Epoch::default()),
+            AccountInfo::new(&program_data_key, false, false, &mut program_data_lamports, &mut program_data, &loader, false, Epoch::default()),
+        ];
+        
+        // A profile's own authority can't make itself a platform moderator
+        assert_eq!(
+            set_user_role(&program_id, &accounts, UserRole::Moderator),
+            Err(UnityVaultError::NotPlatformAuthority.into())
+        );
+    }
+}
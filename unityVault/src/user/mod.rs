@@ -1,5 +1,7 @@
 pub mod state;
 pub mod instructions;
+pub mod two_factor;
+pub mod context;
 
 pub use state::*;
 pub use instructions::*; 
\ No newline at end of file
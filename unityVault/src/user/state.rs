@@ -1,9 +1,22 @@
-use solana_program::{
-    program_pack::{IsInitialized, Pack, Sealed},
-    pubkey::Pubkey,
-};
-use std::convert::TryInto;
+use solana_program::pubkey::Pubkey;
 use borsh::{BorshSerialize, BorshDeserialize};
+use crate::account::{AccountState, AccountType, ACCOUNT_HEADER_LEN};
+use solana_program::program_error::ProgramError;
+use crate::error::UnityVaultError;
+use crate::migration::{decode_legacy, upgrade_or_decode, Migrate, LEGACY_LAYOUT_VERSION};
+use crate::user::two_factor::{commit_two_factor_secret, hash_backup_code};
+use crate::validation::{check_str_len, check_vec_len};
+
+pub const USER_PROFILE_SEED: &[u8] = b"user_profile";
+
+pub const MAX_FULL_NAME_LEN: usize = 100;
+pub const MAX_EMAIL_LEN: usize = 100;
+pub const MAX_BACKUP_CODES: usize = 10;
+pub const MAX_BACKUP_CODE_LEN: usize = 50;
+pub const MAX_DOCUMENT_TYPE_LEN: usize = 100;
+pub const MAX_DOCUMENT_NUMBER_LEN: usize = 100;
+pub const MAX_RECOVERY_GUARDIANS: usize = 5;
+pub const MIN_RECOVERY_TIMELOCK: i64 = 24 * 60 * 60;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
 pub enum UserRole {
@@ -12,6 +25,13 @@ pub enum UserRole {
     User,
 }
 
+impl UserRole {
+    /// Whether the role can suspend, reinstate and archive any community.
+    pub fn can_moderate(&self) -> bool {
+        matches!(self, UserRole::Admin | UserRole::Moderator)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
 pub enum UserStatus {
     Active,
@@ -33,6 +53,14 @@ pub struct KycData {
     pub verified_at: i64,
 }
 
+impl KycData {
+    pub fn validate(&self) -> Result<(), UnityVaultError> {
+        check_str_len("document_type", &self.document_type, MAX_DOCUMENT_TYPE_LEN)?;
+        check_str_len("document_number", &self.document_number, MAX_DOCUMENT_NUMBER_LEN)?;
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
 pub struct UserProfile {
     pub is_initialized: bool,
@@ -42,8 +70,18 @@ pub struct UserProfile {
     pub role: UserRole,
     pub status: UserStatus,
     pub two_factor_enabled: bool,
-    pub two_factor_secret: String,
+    /// `commit_two_factor_secret` of the TOTP secret. The secret itself stays
+    /// with the user and the guardian, which checks codes off-chain.
+    pub two_factor_secret_commitment: [u8; 32],
     pub two_factor_backup_codes: Vec<String>,
+    pub two_factor_guardian: Pubkey,
+    pub two_factor_threshold: u64,
+    pub recovery_guardians: Vec<Pubkey>,
+    pub recovery_threshold: u8,
+    pub recovery_timelock: i64,
+    pub pending_recovery_authority: Pubkey,
+    pub recovery_approvals: u8,
+    pub recovery_initiated_at: i64,
     pub kyc_verified: bool,
     pub kyc_status: KycStatus,
     pub kyc_data: KycData,
@@ -53,252 +91,410 @@ pub struct UserProfile {
 }
 
 impl UserProfile {
-    pub const LEN: usize = 1 + // is_initialized
+    pub const LEN: usize = ACCOUNT_HEADER_LEN + // account type and version
+        1 + // is_initialized
         32 + // authority
-        4 + 100 + // full_name (max 100 chars)
-        4 + 100 + // email (max 100 chars)
+        4 + MAX_FULL_NAME_LEN + // full_name
+        4 + MAX_EMAIL_LEN + // email
         1 + // role
         1 + // status
         1 + // two_factor_enabled
-        4 + 100 + // two_factor_secret (max 100 chars)
-        4 + 10 * 50 + // two_factor_backup_codes (max 10 codes, 50 chars each)
+        32 + // two_factor_secret_commitment
+        4 + MAX_BACKUP_CODES * (4 + MAX_BACKUP_CODE_LEN) + // two_factor_backup_codes
+        32 + // two_factor_guardian
+        8 + // two_factor_threshold
+        4 + MAX_RECOVERY_GUARDIANS * 32 + // recovery_guardians
+        1 + // recovery_threshold
+        8 + // recovery_timelock
+        32 + // pending_recovery_authority
+        1 + // recovery_approvals (bitmap over recovery_guardians)
+        8 + // recovery_initiated_at
         1 + // kyc_verified
         1 + // kyc_status
-        4 + 100 + // kyc_data.document_type (max 100 chars)
-        4 + 100 + // kyc_data.document_number (max 100 chars)
+        4 + MAX_DOCUMENT_TYPE_LEN + // kyc_data.document_type
+        4 + MAX_DOCUMENT_NUMBER_LEN + // kyc_data.document_number
         8 + // kyc_data.verified_at
         1 + // accredited_status
         8 + // created_at
         8; // updated_at
-}
 
-impl Sealed for UserProfile {}
+    /// Address of the profile created for `authority`,
+    /// `[USER_PROFILE_SEED, authority]`. Each key has at most one profile
+    /// there, so checks that read a key's profile can't be handed another.
+    pub fn find_address(program_id: &Pubkey, authority: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[USER_PROFILE_SEED, authority.as_ref()], program_id)
+    }
+
+    /// Whether an operation moving `amount` needs the 2FA guardian's co-signature.
+    /// A zero threshold means the user has not opted in.
+    pub fn requires_guardian(&self, amount: u64) -> bool {
+        self.two_factor_enabled && self.two_factor_threshold > 0 && amount >= self.two_factor_threshold
+    }
+
+    /// Whether the profile holds an active platform moderator or admin role.
+    pub fn is_platform_moderator(&self) -> bool {
+        self.status == UserStatus::Active && self.role.can_moderate()
+    }
+
+    pub fn guardian_index(&self, key: &Pubkey) -> Option<usize> {
+        self.recovery_guardians.iter().position(|guardian| guardian == key)
+    }
+
+    pub fn has_pending_recovery(&self) -> bool {
+        self.recovery_initiated_at != 0
+    }
+
+    pub fn clear_pending_recovery(&mut self) {
+        self.pending_recovery_authority = Pubkey::default();
+        self.recovery_approvals = 0;
+        self.recovery_initiated_at = 0;
+    }
 
-impl IsInitialized for UserProfile {
-    fn is_initialized(&self) -> bool {
-        self.is_initialized
+    /// Records approvals from every signer that is a recovery guardian and
+    /// returns how many were added.
+    pub fn approve_recovery<'a>(&mut self, signers: impl Iterator<Item = &'a Pubkey>) -> u32 {
+        let before = self.recovery_approvals.count_ones();
+        for signer in signers {
+            if let Some(index) = self.guardian_index(signer) {
+                self.recovery_approvals |= 1 << index;
+            }
+        }
+        self.recovery_approvals.count_ones() - before
+    }
+
+    /// Checks that the pending recovery has enough guardian approvals and
+    /// that its timelock has elapsed at `now`.
+    pub fn check_recovery_ready(&self, now: i64) -> Result<(), UnityVaultError> {
+        if !self.has_pending_recovery() {
+            return Err(UnityVaultError::NoPendingRecovery);
+        }
+        if self.recovery_approvals.count_ones() < self.recovery_threshold as u32 {
+            return Err(UnityVaultError::RecoveryThresholdNotMet);
+        }
+        if now < self.recovery_initiated_at.saturating_add(self.recovery_timelock) {
+            return Err(UnityVaultError::RecoveryTimelockActive);
+        }
+        Ok(())
     }
 }
 
-impl Pack for UserProfile {
+impl AccountState for UserProfile {
+    const ACCOUNT_TYPE: AccountType = AccountType::UserProfile;
+    const VERSION: u8 = 2;
     const LEN: usize = Self::LEN;
 
-    fn pack_into_slice(&self, dst: &mut [u8]) {
-        let mut offset = 0;
-        
-        // Pack is_initialized
-        dst[offset] = self.is_initialized as u8;
-        offset += 1;
-        
-        // Pack authority
-        dst[offset..offset + 32].copy_from_slice(&self.authority.to_bytes());
-        offset += 32;
-        
-        // Pack full_name
-        let full_name_bytes = self.full_name.as_bytes();
-        dst[offset..offset + 4].copy_from_slice(&(full_name_bytes.len() as u32).to_le_bytes());
-        offset += 4;
-        dst[offset..offset + full_name_bytes.len()].copy_from_slice(full_name_bytes);
-        offset += full_name_bytes.len();
-        
-        // Pack email
-        let email_bytes = self.email.as_bytes();
-        dst[offset..offset + 4].copy_from_slice(&(email_bytes.len() as u32).to_le_bytes());
-        offset += 4;
-        dst[offset..offset + email_bytes.len()].copy_from_slice(email_bytes);
-        offset += email_bytes.len();
-        
-        // Pack role
-        dst[offset] = self.role as u8;
-        offset += 1;
-        
-        // Pack status
-        dst[offset] = self.status as u8;
-        offset += 1;
-        
-        // Pack two_factor_enabled
-        dst[offset] = self.two_factor_enabled as u8;
-        offset += 1;
-        
-        // Pack two_factor_secret
-        let secret_bytes = self.two_factor_secret.as_bytes();
-        dst[offset..offset + 4].copy_from_slice(&(secret_bytes.len() as u32).to_le_bytes());
-        offset += 4;
-        dst[offset..offset + secret_bytes.len()].copy_from_slice(secret_bytes);
-        offset += secret_bytes.len();
-        
-        // Pack two_factor_backup_codes
-        dst[offset..offset + 4].copy_from_slice(&(self.two_factor_backup_codes.len() as u32).to_le_bytes());
-        offset += 4;
+    fn validate_bounds(&self) -> Result<(), UnityVaultError> {
+        check_str_len("full_name", &self.full_name, MAX_FULL_NAME_LEN)?;
+        check_str_len("email", &self.email, MAX_EMAIL_LEN)?;
+        check_vec_len("two_factor_backup_codes", &self.two_factor_backup_codes, MAX_BACKUP_CODES)?;
         for code in &self.two_factor_backup_codes {
-            let code_bytes = code.as_bytes();
-            dst[offset..offset + 4].copy_from_slice(&(code_bytes.len() as u32).to_le_bytes());
-            offset += 4;
-            dst[offset..offset + code_bytes.len()].copy_from_slice(code_bytes);
-            offset += code_bytes.len();
+            check_str_len("two_factor_backup_code", code, MAX_BACKUP_CODE_LEN)?;
+        }
+        check_vec_len("recovery_guardians", &self.recovery_guardians, MAX_RECOVERY_GUARDIANS)?;
+        self.kyc_data.validate()
+    }
+}
+
+/// `UserProfile` as written before layouts were versioned, with plaintext
+/// backup codes and no guardian or recovery settings.
+#[derive(BorshDeserialize)]
+struct LegacyUserProfile {
+    is_initialized: bool,
+    authority: Pubkey,
+    full_name: String,
+    email: String,
+    role: UserRole,
+    status: UserStatus,
+    two_factor_enabled: bool,
+    two_factor_secret: String,
+    two_factor_backup_codes: Vec<String>,
+    kyc_verified: bool,
+    kyc_status: KycStatus,
+    kyc_data: KycData,
+    accredited_status: bool,
+    created_at: i64,
+    updated_at: i64,
+}
+
+/// `UserProfile` layout version 1: the TOTP secret itself was stored.
+#[derive(BorshDeserialize)]
+#[cfg_attr(test, derive(BorshSerialize))]
+struct UserProfileV1 {
+    is_initialized: bool,
+    authority: Pubkey,
+    full_name: String,
+    email: String,
+    role: UserRole,
+    status: UserStatus,
+    two_factor_enabled: bool,
+    two_factor_secret: String,
+    two_factor_backup_codes: Vec<String>,
+    two_factor_guardian: Pubkey,
+    two_factor_threshold: u64,
+    recovery_guardians: Vec<Pubkey>,
+    recovery_threshold: u8,
+    recovery_timelock: i64,
+    pending_recovery_authority: Pubkey,
+    recovery_approvals: u8,
+    recovery_initiated_at: i64,
+    kyc_verified: bool,
+    kyc_status: KycStatus,
+    kyc_data: KycData,
+    accredited_status: bool,
+    created_at: i64,
+    updated_at: i64,
+}
+
+impl From<LegacyUserProfile> for UserProfileV1 {
+    fn from(legacy: LegacyUserProfile) -> Self {
+        UserProfileV1 {
+            is_initialized: legacy.is_initialized,
+            authority: legacy.authority,
+            full_name: legacy.full_name,
+            email: legacy.email,
+            role: legacy.role,
+            status: legacy.status,
+            two_factor_enabled: legacy.two_factor_enabled,
+            two_factor_secret: legacy.two_factor_secret,
+            two_factor_backup_codes: legacy.two_factor_backup_codes
+                .iter()
+                .map(|code| hash_backup_code(code))
+                .collect(),
+            two_factor_guardian: Pubkey::default(),
+            two_factor_threshold: 0,
+            recovery_guardians: Vec::new(),
+            recovery_threshold: 0,
+            recovery_timelock: 0,
+            pending_recovery_authority: Pubkey::default(),
+            recovery_approvals: 0,
+            recovery_initiated_at: 0,
+            kyc_verified: legacy.kyc_verified,
+            kyc_status: legacy.kyc_status,
+            kyc_data: legacy.kyc_data,
+            accredited_status: legacy.accredited_status,
+            created_at: legacy.created_at,
+            updated_at: legacy.updated_at,
         }
-        
-        // Pack kyc_verified
-        dst[offset] = self.kyc_verified as u8;
-        offset += 1;
-        
-        // Pack kyc_status
-        dst[offset] = self.kyc_status as u8;
-        offset += 1;
-        
-        // Pack kyc_data
-        let doc_type_bytes = self.kyc_data.document_type.as_bytes();
-        dst[offset..offset + 4].copy_from_slice(&(doc_type_bytes.len() as u32).to_le_bytes());
-        offset += 4;
-        dst[offset..offset + doc_type_bytes.len()].copy_from_slice(doc_type_bytes);
-        offset += doc_type_bytes.len();
-        
-        let doc_number_bytes = self.kyc_data.document_number.as_bytes();
-        dst[offset..offset + 4].copy_from_slice(&(doc_number_bytes.len() as u32).to_le_bytes());
-        offset += 4;
-        dst[offset..offset + doc_number_bytes.len()].copy_from_slice(doc_number_bytes);
-        offset += doc_number_bytes.len();
-        
-        dst[offset..offset + 8].copy_from_slice(&self.kyc_data.verified_at.to_le_bytes());
-        offset += 8;
-        
-        // Pack accredited_status
-        dst[offset] = self.accredited_status as u8;
-        offset += 1;
-        
-        // Pack timestamps
-        dst[offset..offset + 8].copy_from_slice(&self.created_at.to_le_bytes());
-        offset += 8;
-        dst[offset..offset + 8].copy_from_slice(&self.updated_at.to_le_bytes());
     }
+}
 
-    fn unpack_from_slice(src: &[u8]) -> Result<Self, solana_program::program_error::ProgramError> {
-        let mut offset = 0;
-        
-        // Unpack is_initialized
-        let is_initialized = src[offset] != 0;
-        offset += 1;
-        
-        // Unpack authority
-        let authority_bytes: [u8; 32] = src[offset..offset + 32].try_into()
-            .map_err(|_| solana_program::program_error::ProgramError::InvalidAccountData)?;
-        let authority = Pubkey::from(authority_bytes);
-        offset += 32;
-        
-        // Unpack full_name
-        let full_name_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
-        offset += 4;
-        let full_name = String::from_utf8(src[offset..offset + full_name_len].to_vec())
-            .map_err(|_| solana_program::program_error::ProgramError::InvalidAccountData)?;
-        offset += full_name_len;
-        
-        // Unpack email
-        let email_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
-        offset += 4;
-        let email = String::from_utf8(src[offset..offset + email_len].to_vec())
-            .map_err(|_| solana_program::program_error::ProgramError::InvalidAccountData)?;
-        offset += email_len;
-        
-        // Unpack role
-        let role = match src[offset] {
-            0 => UserRole::Admin,
-            1 => UserRole::Moderator,
-            2 => UserRole::User,
-            _ => return Err(solana_program::program_error::ProgramError::InvalidAccountData),
-        };
-        offset += 1;
-        
-        // Unpack status
-        let status = match src[offset] {
-            0 => UserStatus::Active,
-            1 => UserStatus::Suspended,
-            2 => UserStatus::Banned,
-            _ => return Err(solana_program::program_error::ProgramError::InvalidAccountData),
+impl From<UserProfileV1> for UserProfile {
+    fn from(v1: UserProfileV1) -> Self {
+        // Migrating drops the stored secret from the account, but it has been
+        // public all along; users should re-enroll with a fresh secret.
+        let two_factor_secret_commitment = if v1.two_factor_secret.is_empty() {
+            [0; 32]
+        } else {
+            commit_two_factor_secret(&v1.two_factor_secret)
         };
-        offset += 1;
-        
-        // Unpack two_factor_enabled
-        let two_factor_enabled = src[offset] != 0;
-        offset += 1;
-        
-        // Unpack two_factor_secret
-        let secret_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
-        offset += 4;
-        let two_factor_secret = String::from_utf8(src[offset..offset + secret_len].to_vec())
-            .map_err(|_| solana_program::program_error::ProgramError::InvalidAccountData)?;
-        offset += secret_len;
-        
-        // Unpack two_factor_backup_codes
-        let codes_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
-        offset += 4;
-        let mut two_factor_backup_codes = Vec::with_capacity(codes_len);
-        for _ in 0..codes_len {
-            let code_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
-            offset += 4;
-            let code = String::from_utf8(src[offset..offset + code_len].to_vec())
-                .map_err(|_| solana_program::program_error::ProgramError::InvalidAccountData)?;
-            offset += code_len;
-            two_factor_backup_codes.push(code);
+        UserProfile {
+            is_initialized: v1.is_initialized,
+            authority: v1.authority,
+            full_name: v1.full_name,
+            email: v1.email,
+            role: v1.role,
+            status: v1.status,
+            two_factor_enabled: v1.two_factor_enabled,
+            two_factor_secret_commitment,
+            two_factor_backup_codes: v1.two_factor_backup_codes,
+            two_factor_guardian: v1.two_factor_guardian,
+            two_factor_threshold: v1.two_factor_threshold,
+            recovery_guardians: v1.recovery_guardians,
+            recovery_threshold: v1.recovery_threshold,
+            recovery_timelock: v1.recovery_timelock,
+            pending_recovery_authority: v1.pending_recovery_authority,
+            recovery_approvals: v1.recovery_approvals,
+            recovery_initiated_at: v1.recovery_initiated_at,
+            kyc_verified: v1.kyc_verified,
+            kyc_status: v1.kyc_status,
+            kyc_data: v1.kyc_data,
+            accredited_status: v1.accredited_status,
+            created_at: v1.created_at,
+            updated_at: v1.updated_at,
         }
-        
-        // Unpack kyc_verified
-        let kyc_verified = src[offset] != 0;
-        offset += 1;
-        
-        // Unpack kyc_status
-        let kyc_status = match src[offset] {
-            0 => KycStatus::Pending,
-            1 => KycStatus::Verified,
-            2 => KycStatus::Rejected,
-            _ => return Err(solana_program::program_error::ProgramError::InvalidAccountData),
+    }
+}
+
+impl Migrate for UserProfile {
+    // What clients allocated for a profile before layouts were versioned
+    const LEGACY_LEN: Option<usize> = Some(1126);
+
+    fn decode_version(version: u8, src: &[u8]) -> Result<Self, ProgramError> {
+        let legacy: Option<LegacyUserProfile> = if version == LEGACY_LAYOUT_VERSION {
+            Some(decode_legacy(src)?)
+        } else {
+            None
         };
-        offset += 1;
-        
-        // Unpack kyc_data
-        let doc_type_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
-        offset += 4;
-        let document_type = String::from_utf8(src[offset..offset + doc_type_len].to_vec())
-            .map_err(|_| solana_program::program_error::ProgramError::InvalidAccountData)?;
-        offset += doc_type_len;
-        
-        let doc_number_len = u32::from_le_bytes(src[offset..offset + 4].try_into().unwrap()) as usize;
-        offset += 4;
-        let document_number = String::from_utf8(src[offset..offset + doc_number_len].to_vec())
-            .map_err(|_| solana_program::program_error::ProgramError::InvalidAccountData)?;
-        offset += doc_number_len;
-        
-        let verified_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
-        offset += 8;
-        
-        // Unpack accredited_status
-        let accredited_status = src[offset] != 0;
-        offset += 1;
-        
-        // Unpack timestamps
-        let created_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
-        offset += 8;
-        let updated_at = i64::from_le_bytes(src[offset..offset + 8].try_into().unwrap());
-        
-        Ok(UserProfile {
-            is_initialized,
-            authority,
-            full_name,
-            email,
-            role,
-            status,
-            two_factor_enabled,
-            two_factor_secret,
-            two_factor_backup_codes,
-            kyc_verified,
-            kyc_status,
+        let v1: Option<UserProfileV1> = upgrade_or_decode(legacy, version, 1, src)?;
+        v1.map(UserProfile::from).ok_or_else(|| UnityVaultError::UnsupportedAccountVersion.into())
+    }
+
+    fn migration_authority(&self) -> Pubkey {
+        self.authority
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::account::tests::assert_max_len_fits;
+
+    pub(crate) fn test_profile() -> UserProfile {
+        UserProfile {
+            is_initialized: true,
+            authority: Pubkey::new_unique(),
+            full_name: "Test User".to_string(),
+            email: "test@example.com".to_string(),
+            role: UserRole::User,
+            status: UserStatus::Active,
+            two_factor_enabled: false,
+            two_factor_secret_commitment: [0; 32],
+            two_factor_backup_codes: Vec::new(),
+            two_factor_guardian: Pubkey::default(),
+            two_factor_threshold: 0,
+            recovery_guardians: Vec::new(),
+            recovery_threshold: 0,
+            recovery_timelock: 0,
+            pending_recovery_authority: Pubkey::default(),
+            recovery_approvals: 0,
+            recovery_initiated_at: 0,
+            kyc_verified: false,
+            kyc_status: KycStatus::Pending,
             kyc_data: KycData {
-                document_type,
-                document_number,
-                verified_at,
+                document_type: String::new(),
+                document_number: String::new(),
+                verified_at: 0,
             },
-            accredited_status,
-            created_at,
-            updated_at,
-        })
+            accredited_status: false,
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn test_pack_round_trip() {
+        let mut profile = test_profile();
+        profile.two_factor_enabled = true;
+        profile.two_factor_guardian = Pubkey::new_unique();
+        profile.two_factor_threshold = 1_000;
+        profile.recovery_guardians = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        profile.recovery_threshold = 2;
+        profile.recovery_timelock = MIN_RECOVERY_TIMELOCK;
+
+        let mut data = vec![0; UserProfile::LEN];
+        profile.pack(&mut data).unwrap();
+        let unpacked = UserProfile::unpack(&data).unwrap();
+
+        assert_eq!(unpacked.authority, profile.authority);
+        assert_eq!(unpacked.two_factor_guardian, profile.two_factor_guardian);
+        assert_eq!(unpacked.two_factor_threshold, 1_000);
+        assert_eq!(unpacked.recovery_guardians, profile.recovery_guardians);
+        assert_eq!(unpacked.recovery_threshold, 2);
+        assert_eq!(unpacked.recovery_timelock, MIN_RECOVERY_TIMELOCK);
+    }
+
+    #[test]
+    fn test_pack_max_length_fields_fits_len() {
+        let mut profile = test_profile();
+        profile.full_name = "a".repeat(MAX_FULL_NAME_LEN);
+        profile.email = "b".repeat(MAX_EMAIL_LEN);
+        profile.two_factor_backup_codes = vec!["d".repeat(MAX_BACKUP_CODE_LEN); MAX_BACKUP_CODES];
+        profile.recovery_guardians = vec![Pubkey::new_unique(); MAX_RECOVERY_GUARDIANS];
+        profile.kyc_data.document_type = "e".repeat(MAX_DOCUMENT_TYPE_LEN);
+        profile.kyc_data.document_number = "f".repeat(MAX_DOCUMENT_NUMBER_LEN);
+
+        assert_max_len_fits(&profile);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_migrate_v1_replaces_secret_with_commitment() {
+        let profile = test_profile();
+        let v1 = UserProfileV1 {
+            is_initialized: true,
+            authority: profile.authority,
+            full_name: profile.full_name.clone(),
+            email: profile.email.clone(),
+            role: UserRole::User,
+            status: UserStatus::Active,
+            two_factor_enabled: true,
+            two_factor_secret: "JBSWY3DPEHPK3PXP".to_string(),
+            two_factor_backup_codes: vec![hash_backup_code("code1")],
+            two_factor_guardian: Pubkey::new_unique(),
+            two_factor_threshold: 1_000,
+            recovery_guardians: Vec::new(),
+            recovery_threshold: 0,
+            recovery_timelock: 0,
+            pending_recovery_authority: Pubkey::default(),
+            recovery_approvals: 0,
+            recovery_initiated_at: 0,
+            kyc_verified: false,
+            kyc_status: KycStatus::Pending,
+            kyc_data: profile.kyc_data.clone(),
+            accredited_status: false,
+            created_at: 100,
+            updated_at: 200,
+        };
+        let guardian = v1.two_factor_guardian;
+        let mut data = vec![AccountType::UserProfile as u8, 1];
+        data.extend(borsh::to_vec(&v1).unwrap());
+
+        let migrated = UserProfile::decode_version(1, &data).unwrap();
+        assert_eq!(migrated.two_factor_secret_commitment, commit_two_factor_secret("JBSWY3DPEHPK3PXP"));
+        assert_eq!(migrated.two_factor_guardian, guardian);
+        assert_eq!((migrated.two_factor_threshold, migrated.updated_at), (1_000, 200));
+
+        // The secret no longer appears anywhere in the repacked account
+        let mut repacked = vec![0; UserProfile::LEN];
+        migrated.pack(&mut repacked).unwrap();
+        assert!(!repacked.windows(16).any(|w| w == b"JBSWY3DPEHPK3PXP"));
+    }
+
+    #[test]
+    fn test_requires_guardian() {
+        let mut profile = test_profile();
+        profile.two_factor_threshold = 1_000;
+        assert!(!profile.requires_guardian(5_000));
+
+        profile.two_factor_enabled = true;
+        assert!(!profile.requires_guardian(999));
+        assert!(profile.requires_guardian(1_000));
+
+        profile.two_factor_threshold = 0;
+        assert!(!profile.requires_guardian(u64::MAX));
+    }
+
+    #[test]
+    fn test_recovery_approvals_and_timelock() {
+        let guardians = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let outsider = Pubkey::new_unique();
+
+        let mut profile = test_profile();
+        profile.recovery_guardians = guardians.to_vec();
+        profile.recovery_threshold = 2;
+        profile.recovery_timelock = MIN_RECOVERY_TIMELOCK;
+
+        assert_eq!(profile.check_recovery_ready(0), Err(UnityVaultError::NoPendingRecovery));
+
+        profile.pending_recovery_authority = Pubkey::new_unique();
+        profile.recovery_initiated_at = 1_000;
+
+        // Outsiders and repeat approvals don't count
+        assert_eq!(profile.approve_recovery([outsider, guardians[0]].iter()), 1);
+        assert_eq!(profile.approve_recovery([guardians[0]].iter()), 0);
+        assert_eq!(
+            profile.check_recovery_ready(1_000 + MIN_RECOVERY_TIMELOCK),
+            Err(UnityVaultError::RecoveryThresholdNotMet)
+        );
+
+        assert_eq!(profile.approve_recovery([guardians[2]].iter()), 1);
+        assert_eq!(
+            profile.check_recovery_ready(1_000 + MIN_RECOVERY_TIMELOCK - 1),
+            Err(UnityVaultError::RecoveryTimelockActive)
+        );
+        assert_eq!(profile.check_recovery_ready(1_000 + MIN_RECOVERY_TIMELOCK), Ok(()));
+
+        profile.clear_pending_recovery();
+        assert!(!profile.has_pending_recovery());
+        assert_eq!(profile.recovery_approvals, 0);
+    }
+}
@@ -0,0 +1,352 @@
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    hash::hash,
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+use crate::account::AccountState;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use sha2::{Sha256, Sha512};
+use borsh::{BorshSerialize, BorshDeserialize};
+use crate::error::UnityVaultError;
+use crate::user::state::UserProfile;
+
+/// HMAC hash function used to derive one-time passwords (RFC 6238, section 1.2).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub enum TotpAlgorithm {
+    Sha1,
+    Sha256,
+    Sha512,
+}
+
+/// TOTP parameters. The defaults match what authenticator apps expect:
+/// SHA-1, 6 digits, 30 second steps starting at the Unix epoch, and one
+/// step of tolerated clock drift either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TotpConfig {
+    pub algorithm: TotpAlgorithm,
+    pub digits: u32,
+    pub step: u64,
+    pub t0: u64,
+    pub skew: u64,
+}
+
+impl Default for TotpConfig {
+    fn default() -> Self {
+        TotpConfig {
+            algorithm: TotpAlgorithm::Sha1,
+            digits: 6,
+            step: 30,
+            t0: 0,
+            skew: 1,
+        }
+    }
+}
+
+const BASE32_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Decodes an RFC 4648 base32 secret as handed out in `otpauth://` URIs.
+/// Padding, spaces and lowercase letters are accepted.
+pub fn decode_base32_secret(secret: &str) -> Option<Vec<u8>> {
+    let mut buffer: u64 = 0;
+    let mut bits = 0;
+    let mut out = Vec::with_capacity(secret.len() * 5 / 8);
+
+    for c in secret.bytes() {
+        if c == b'=' || c == b' ' {
+            continue;
+        }
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|&a| a == c.to_ascii_uppercase())? as u64;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    if out.is_empty() {
+        None
+    } else {
+        Some(out)
+    }
+}
+
+fn hmac_digest(algorithm: TotpAlgorithm, key: &[u8], message: &[u8]) -> Vec<u8> {
+    match algorithm {
+        TotpAlgorithm::Sha1 => {
+            let mut mac = Hmac::<Sha1>::new_from_slice(key).expect("HMAC accepts keys of any length");
+            mac.update(message);
+            mac.finalize().into_bytes().to_vec()
+        }
+        TotpAlgorithm::Sha256 => {
+            let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+            mac.update(message);
+            mac.finalize().into_bytes().to_vec()
+        }
+        TotpAlgorithm::Sha512 => {
+            let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts keys of any length");
+            mac.update(message);
+            mac.finalize().into_bytes().to_vec()
+        }
+    }
+}
+
+/// HOTP value for `counter` (RFC 4226, section 5.3). Returns `None` unless
+/// `digits` is between 6 and 9.
+pub fn hotp(algorithm: TotpAlgorithm, key: &[u8], counter: u64, digits: u32) -> Option<u32> {
+    if !(6..=9).contains(&digits) {
+        return None;
+    }
+
+    let digest = hmac_digest(algorithm, key, &counter.to_be_bytes());
+
+    // Dynamic truncation
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = ((digest[offset] as u32 & 0x7f) << 24)
+        | ((digest[offset + 1] as u32) << 16)
+        | ((digest[offset + 2] as u32) << 8)
+        | (digest[offset + 3] as u32);
+
+    Some(binary % 10u32.pow(digits))
+}
+
+/// Time step containing `unix_time`, or `None` if it precedes `config.t0`.
+pub fn time_step(unix_time: i64, config: &TotpConfig) -> Option<u64> {
+    if config.step == 0 {
+        return None;
+    }
+    let elapsed = u64::try_from(unix_time).ok()?.checked_sub(config.t0)?;
+    Some(elapsed / config.step)
+}
+
+/// TOTP value at `unix_time` (RFC 6238, section 4.2).
+pub fn totp(key: &[u8], unix_time: i64, config: &TotpConfig) -> Option<u32> {
+    hotp(config.algorithm, key, time_step(unix_time, config)?, config.digits)
+}
+
+/// Checks `code` against the steps within `config.skew` of `unix_time`.
+///
+/// On success returns the matched time step, which the caller must persist
+/// and pass back as `last_used_step` so the same code (or an older one) can
+/// not be replayed (RFC 6238, section 5.2).
+pub fn verify_totp(
+    key: &[u8],
+    code: &str,
+    unix_time: i64,
+    config: &TotpConfig,
+    last_used_step: Option<u64>,
+) -> Option<u64> {
+    if code.len() != config.digits as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let expected: u32 = code.parse().ok()?;
+    let current = time_step(unix_time, config)?;
+
+    let first = current.saturating_sub(config.skew);
+    let last = current.saturating_add(config.skew);
+    (first..=last)
+        .filter(|step| !matches!(last_used_step, Some(used) if *step <= used))
+        .find(|step| hotp(config.algorithm, key, *step, config.digits) == Some(expected))
+}
+
+/// Representation of a backup code as stored on a `UserProfile`. Only the
+/// hash is kept on-chain since account data is public.
+pub fn hash_backup_code(code: &str) -> String {
+    hash(code.as_bytes()).to_string()
+}
+
+/// Commitment to a TOTP secret, as handed out in `otpauth://` URIs, kept on a
+/// `UserProfile` in place of the secret. Clients compute it off-chain so the
+/// secret never appears in instruction or account data.
+pub fn commit_two_factor_secret(secret: &str) -> [u8; 32] {
+    hash(secret.as_bytes()).to_bytes()
+}
+
+/// Enforces the 2FA guardian co-signature on a high-value operation.
+///
+/// `user_profile` is the profile created for `authority` at its PDA and is
+/// required, so the check can't be skipped by leaving it out or passing a
+/// second profile without 2FA. A key whose profile was rotated to another
+/// authority is refused outright. When the profile has 2FA enabled with a
+/// threshold at or below `amount`, `guardian` must be the registered
+/// guardian key and must have signed.
+pub fn check_guardian_cosign(
+    program_id: &Pubkey,
+    authority: &Pubkey,
+    user_profile: &AccountInfo,
+    guardian: Option<&AccountInfo>,
+    amount: u64,
+) -> ProgramResult {
+    if user_profile.owner != program_id {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    if *user_profile.key != UserProfile::find_address(program_id, authority).0 {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    let user_profile_data = UserProfile::unpack(&user_profile.data.borrow())?;
+    if user_profile_data.authority != *authority {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    if !user_profile_data.requires_guardian(amount) {
+        return Ok(());
+    }
+
+    match guardian {
+        Some(guardian) if guardian.is_signer && *guardian.key == user_profile_data.two_factor_guardian => Ok(()),
+        _ => Err(UnityVaultError::GuardianSignatureRequired.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SEED_SHA1: &[u8] = b"12345678901234567890";
+    const SEED_SHA256: &[u8] = b"12345678901234567890123456789012";
+    const SEED_SHA512: &[u8] = b"1234567890123456789012345678901234567890123456789012345678901234";
+
+    fn rfc_config(algorithm: TotpAlgorithm) -> TotpConfig {
+        TotpConfig {
+            algorithm,
+            digits: 8,
+            ..TotpConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_hotp_rfc4226_vectors() {
+        let expected = [
+            755224, 287082, 359152, 969429, 338314,
+            254676, 287922, 162583, 399871, 520489,
+        ];
+        for (counter, code) in expected.iter().enumerate() {
+            assert_eq!(hotp(TotpAlgorithm::Sha1, SEED_SHA1, counter as u64, 6), Some(*code));
+        }
+    }
+
+    #[test]
+    fn test_totp_rfc6238_vectors() {
+        let vectors: [(i64, u32, u32, u32); 6] = [
+            (59, 94287082, 46119246, 90693936),
+            (1111111109, 7081804, 68084774, 25091201),
+            (1111111111, 14050471, 67062674, 99943326),
+            (1234567890, 89005924, 91819424, 93441116),
+            (2000000000, 69279037, 90698825, 38618901),
+            (20000000000, 65353130, 77737706, 47863826),
+        ];
+        for (time, sha1, sha256, sha512) in vectors {
+            assert_eq!(totp(SEED_SHA1, time, &rfc_config(TotpAlgorithm::Sha1)), Some(sha1));
+            assert_eq!(totp(SEED_SHA256, time, &rfc_config(TotpAlgorithm::Sha256)), Some(sha256));
+            assert_eq!(totp(SEED_SHA512, time, &rfc_config(TotpAlgorithm::Sha512)), Some(sha512));
+        }
+    }
+
+    #[test]
+    fn test_verify_totp_window_and_replay() {
+        let config = rfc_config(TotpAlgorithm::Sha1);
+
+        // Code for step 37037036 (t = 1111111109) is accepted one step late...
+        assert_eq!(verify_totp(SEED_SHA1, "07081804", 1111111109 + 30, &config, None), Some(37037036));
+        // ...but not two steps late
+        assert_eq!(verify_totp(SEED_SHA1, "07081804", 1111111109 + 60, &config, None), None);
+        // and never after it has been used
+        assert_eq!(verify_totp(SEED_SHA1, "07081804", 1111111109, &config, Some(37037036)), None);
+
+        // Malformed codes
+        assert_eq!(verify_totp(SEED_SHA1, "7081804", 1111111109, &config, None), None);
+        assert_eq!(verify_totp(SEED_SHA1, "0708180a", 1111111109, &config, None), None);
+        assert_eq!(verify_totp(SEED_SHA1, "07081804", -1, &config, None), None);
+    }
+
+    #[test]
+    fn test_decode_base32_secret() {
+        assert_eq!(
+            decode_base32_secret("GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ").as_deref(),
+            Some(SEED_SHA1)
+        );
+        assert_eq!(
+            decode_base32_secret("gezd gnbv gy3t qojq gezd gnbv gy3t qojq").as_deref(),
+            Some(SEED_SHA1)
+        );
+        assert_eq!(decode_base32_secret("MZXW6==="), Some(b"foo".to_vec()));
+        assert_eq!(decode_base32_secret("GEZ1"), None);
+        assert_eq!(decode_base32_secret(""), None);
+    }
+
+    #[test]
+    fn test_check_guardian_cosign() {
+        use crate::user::state::tests::test_profile;
+        use solana_program::clock::Epoch;
+
+        let program_id = Pubkey::new_unique();
+        let mut profile = test_profile();
+        profile.two_factor_enabled = true;
+        profile.two_factor_guardian = Pubkey::new_unique();
+        profile.two_factor_threshold = 1_000;
+        let authority = profile.authority;
+        let guardian_key = profile.two_factor_guardian;
+
+        let (profile_key, _) = UserProfile::find_address(&program_id, &authority);
+        let mut profile_lamports = 0;
+        let mut data = vec![0; UserProfile::LEN];
+        profile.pack(&mut data).unwrap();
+        let mut second_data = data.clone();
+        let user_profile = AccountInfo::new(
+            &profile_key, false, false, &mut profile_lamports, &mut data, &program_id, false, Epoch::default(),
+        );
+
+        let mut guardian_lamports = 0;
+        let mut guardian_data = vec![];
+        let system_program = solana_program::system_program::id();
+        let mut guardian = AccountInfo::new(
+            &guardian_key, false, false, &mut guardian_lamports, &mut guardian_data, &system_program, false, Epoch::default(),
+        );
+
+        assert_eq!(check_guardian_cosign(&program_id, &authority, &user_profile, None, 999), Ok(()));
+        assert_eq!(
+            check_guardian_cosign(&program_id, &authority, &user_profile, None, 1_000),
+            Err(UnityVaultError::GuardianSignatureRequired.into())
+        );
+        assert_eq!(
+            check_guardian_cosign(&program_id, &authority, &user_profile, Some(&guardian), 1_000),
+            Err(UnityVaultError::GuardianSignatureRequired.into())
+        );
+        guardian.is_signer = true;
+        assert_eq!(check_guardian_cosign(&program_id, &authority, &user_profile, Some(&guardian), 1_000), Ok(()));
+
+        // Someone else's profile can't stand in for the authority's
+        assert_eq!(
+            check_guardian_cosign(&program_id, &Pubkey::new_unique(), &user_profile, None, 1_000),
+            Err(ProgramError::InvalidSeeds)
+        );
+
+        // Nor can a second profile of the authority's own, e.g. one without 2FA
+        let second_key = Pubkey::new_unique();
+        let mut second_lamports = 0;
+        let second_profile = AccountInfo::new(
+            &second_key, false, false, &mut second_lamports, &mut second_data, &program_id, false, Epoch::default(),
+        );
+        assert_eq!(
+            check_guardian_cosign(&program_id, &authority, &second_profile, None, 1_000),
+            Err(ProgramError::InvalidSeeds)
+        );
+        assert_eq!(
+            check_guardian_cosign(&Pubkey::new_unique(), &authority, &user_profile, None, 1_000),
+            Err(ProgramError::IncorrectProgramId)
+        );
+    }
+
+    #[test]
+    fn test_hotp_rejects_unsupported_digits() {
+        assert_eq!(hotp(TotpAlgorithm::Sha1, SEED_SHA1, 0, 5), None);
+        assert_eq!(hotp(TotpAlgorithm::Sha1, SEED_SHA1, 0, 10), None);
+    }
+}
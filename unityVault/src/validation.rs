@@ -0,0 +1,40 @@
+use solana_program::msg;
+use crate::error::UnityVaultError;
+
+/// Checks that `value` fits in the `max` bytes budgeted for `field` in the account layout.
+pub fn check_str_len(field: &str, value: &str, max: usize) -> Result<(), UnityVaultError> {
+    if value.len() > max {
+        msg!("{} is {} bytes, max is {}", field, value.len(), max);
+        return Err(UnityVaultError::StringTooLong);
+    }
+    Ok(())
+}
+
+/// Checks that `values` has no more than the `max` entries budgeted for `field`.
+pub fn check_vec_len<T>(field: &str, values: &[T], max: usize) -> Result<(), UnityVaultError> {
+    if values.len() > max {
+        msg!("{} has {} entries, max is {}", field, values.len(), max);
+        return Err(UnityVaultError::TooManyEntries);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_str_len_counts_bytes() {
+        assert_eq!(check_str_len("name", "abcd", 4), Ok(()));
+        assert_eq!(check_str_len("name", "abcde", 4), Err(UnityVaultError::StringTooLong));
+        // "é" is two bytes in UTF-8
+        assert_eq!(check_str_len("name", "abé", 4), Ok(()));
+        assert_eq!(check_str_len("name", "abcé", 4), Err(UnityVaultError::StringTooLong));
+    }
+
+    #[test]
+    fn test_check_vec_len() {
+        assert_eq!(check_vec_len("codes", &[1, 2], 2), Ok(()));
+        assert_eq!(check_vec_len("codes", &[1, 2, 3], 2), Err(UnityVaultError::TooManyEntries));
+    }
+}